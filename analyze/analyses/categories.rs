@@ -0,0 +1,34 @@
+//! Filtering items by the categories that classification rules put them in.
+
+use anyhow::anyhow;
+use twiggy_ir as ir;
+use twiggy_traits as traits;
+
+/// The category of items that no rule matched.
+pub(crate) const UNCATEGORIZED: &str = "[uncategorized]";
+
+/// Get the category of the given item.
+pub(crate) fn category(items: &ir::Items, id: ir::Id) -> &str {
+    items.category(id).unwrap_or(UNCATEGORIZED)
+}
+
+/// Is the given item in one of the given `categories`? Every item is if
+/// `categories` is empty.
+pub(crate) fn in_categories(items: &ir::Items, id: ir::Id, categories: &[String]) -> bool {
+    categories.is_empty() || categories.iter().any(|c| c == category(items, id))
+}
+
+/// Make sure that items were classified, if categories are asked for.
+pub(crate) fn check_classified(
+    items: &ir::Items,
+    categories: &[String],
+    group_by: Option<traits::GroupBy>,
+) -> anyhow::Result<()> {
+    let group_by_category = group_by == Some(traits::GroupBy::Category);
+    if (!categories.is_empty() || group_by_category) && items.categories().is_empty() {
+        return Err(anyhow!(
+            "no items were classified into categories; pass a rules file with `--rules`"
+        ));
+    }
+    Ok(())
+}
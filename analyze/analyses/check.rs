@@ -0,0 +1,329 @@
+//! Checking the sizes of a binary and its parts against budgets.
+
+use std::collections::BTreeSet;
+use std::io;
+
+#[cfg(feature = "emit_csv")]
+use serde_derive::Serialize;
+
+use crate::budgets::{Budget, Budgets, Limit};
+#[cfg(feature = "emit_json")]
+use crate::formats::json;
+#[cfg(feature = "emit_text")]
+use crate::formats::table::{Align, Table};
+use twiggy_ir as ir;
+use twiggy_traits as traits;
+
+/// The result of checking a binary against budgets: whether each limit of each
+/// budget was kept.
+#[derive(Debug)]
+pub struct CheckReport {
+    checks: Vec<LimitCheck>,
+}
+
+/// Whether one limit of a budget was kept.
+#[derive(Debug)]
+struct LimitCheck {
+    budget: String,
+    limit: Limit,
+    /// What was measured, if it could be.
+    actual: Option<Measurement>,
+    status: Status,
+}
+
+/// What was measured for a limit: a size, a growth in bytes, or a growth as a
+/// percentage.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Measurement {
+    Size(u64),
+    Growth(i64),
+    GrowthPercent(f64),
+}
+
+impl Measurement {
+    /// Format this measurement for text output.
+    #[cfg(feature = "emit_text")]
+    fn format(self) -> String {
+        match self {
+            Measurement::Size(size) => size.to_string(),
+            Measurement::Growth(growth) => format!("{:+}", growth),
+            Measurement::GrowthPercent(percent) => format!("{:+.2}%", percent),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Status {
+    Ok,
+    Exceeded,
+    /// Growth limits can't be checked without a baseline.
+    Skipped,
+}
+
+impl Status {
+    fn name(self) -> &'static str {
+        match self {
+            Status::Ok => "ok",
+            Status::Exceeded => "exceeded",
+            Status::Skipped => "skipped",
+        }
+    }
+}
+
+impl Limit {
+    /// The name of what this limits.
+    fn measure(self) -> &'static str {
+        match self {
+            Limit::Size(_) => "size",
+            Limit::RetainedSize(_) => "retained_size",
+            Limit::Growth(_) => "growth",
+            Limit::GrowthPercent(_) => "growth_percent",
+        }
+    }
+
+    /// Format the limit itself for text output.
+    #[cfg(feature = "emit_text")]
+    fn format(self) -> String {
+        match self {
+            Limit::Size(max) | Limit::RetainedSize(max) => max.to_string(),
+            Limit::Growth(max) => format!("{:+}", max),
+            Limit::GrowthPercent(max) => format!("{:+.2}%", max),
+        }
+    }
+
+    /// Does the given measurement exceed this limit?
+    fn is_exceeded_by(self, actual: Measurement) -> bool {
+        match (self, actual) {
+            (Limit::Size(max), Measurement::Size(size))
+            | (Limit::RetainedSize(max), Measurement::Size(size)) => size > max,
+            (Limit::Growth(max), Measurement::Growth(growth)) => {
+                i128::from(growth) > i128::from(max)
+            }
+            (Limit::GrowthPercent(max), Measurement::GrowthPercent(percent)) => percent > max,
+            _ => unreachable!("limits are only checked against their own measurements"),
+        }
+    }
+}
+
+impl CheckReport {
+    /// The number of limits that were exceeded.
+    pub fn violations(&self) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| check.status == Status::Exceeded)
+            .count()
+    }
+
+    /// The number of limits that were checked.
+    pub fn checked(&self) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| check.status != Status::Skipped)
+            .count()
+    }
+
+    #[cfg(feature = "emit_text")]
+    fn summary(&self) -> String {
+        format!(
+            "Σ [{} of {} Limits Exceeded]",
+            self.violations(),
+            self.checked()
+        )
+    }
+}
+
+impl traits::Emit for CheckReport {
+    #[cfg(feature = "emit_text")]
+    fn emit_text(&self, _items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+        let mut table = Table::with_header(vec![
+            (Align::Left, "Status".to_string()),
+            (Align::Right, "Actual".to_string()),
+            (Align::Right, "Limit".to_string()),
+            (Align::Left, "Measure".to_string()),
+            (Align::Left, "Budget".to_string()),
+        ]);
+        for check in &self.checks {
+            table.add_row(vec![
+                check.status.name().to_string(),
+                check.actual.map_or_else(String::new, Measurement::format),
+                check.limit.format(),
+                check.limit.measure().replace('_', " "),
+                check.budget.clone(),
+            ]);
+        }
+        table.add_row(vec![
+            String::new(),
+            String::new(),
+            String::new(),
+            String::new(),
+            self.summary(),
+        ]);
+
+        write!(dest, "{}", &table)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_json")]
+    fn emit_json(&self, _items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+        let mut arr = json::array(dest)?;
+        for check in &self.checks {
+            let mut obj = arr.object()?;
+            obj.field("budget", check.budget.as_str())?;
+            obj.field("measure", check.limit.measure())?;
+            match check.limit {
+                Limit::Size(max) | Limit::RetainedSize(max) | Limit::Growth(max) => {
+                    obj.field("limit", max)?
+                }
+                Limit::GrowthPercent(max) => obj.field("limit", max)?,
+            }
+            match check.actual {
+                Some(Measurement::Size(size)) => obj.field("actual", size)?,
+                Some(Measurement::Growth(growth)) => obj.field("actual", growth)?,
+                Some(Measurement::GrowthPercent(percent)) => obj.field("actual", percent)?,
+                None => {}
+            }
+            obj.field("status", check.status.name())?;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_csv")]
+    fn emit_csv(&self, _items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+        #[derive(Serialize, Debug)]
+        #[serde(rename_all = "PascalCase")]
+        struct CsvRecord<'a> {
+            budget: &'a str,
+            measure: &'a str,
+            limit: String,
+            actual: Option<String>,
+            status: &'a str,
+        }
+
+        let mut wtr = csv::Writer::from_writer(dest);
+        for check in &self.checks {
+            wtr.serialize(CsvRecord {
+                budget: &check.budget,
+                measure: check.limit.measure(),
+                limit: match check.limit {
+                    Limit::Size(max) | Limit::RetainedSize(max) | Limit::Growth(max) => {
+                        max.to_string()
+                    }
+                    Limit::GrowthPercent(max) => max.to_string(),
+                },
+                actual: check.actual.map(|actual| match actual {
+                    Measurement::Size(size) => size.to_string(),
+                    Measurement::Growth(growth) => growth.to_string(),
+                    Measurement::GrowthPercent(percent) => percent.to_string(),
+                }),
+                status: check.status.name(),
+            })?;
+            wtr.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// The sizes of the items that a budget covers in one version of a binary.
+struct Covered {
+    size: u64,
+    retained: u64,
+}
+
+impl Covered {
+    /// Measure the items that the given budget covers. Retained sizes must
+    /// already be computed if `retained` is set.
+    fn measure(items: &ir::Items, budget: &Budget, retained: bool) -> Covered {
+        // A budget without patterns covers the whole binary, including the
+        // bytes that no item accounts for.
+        if budget.selector.is_empty() {
+            return Covered {
+                size: items.size(),
+                retained: items.size(),
+            };
+        }
+        let ids: BTreeSet<ir::Id> = items
+            .iter()
+            .filter(|item| item.id() != items.meta_root())
+            .filter(|item| budget.selector.matches(item))
+            .map(|item| item.id())
+            .collect();
+        Covered {
+            size: super::total_size(ids.iter().map(|&id| items[id].size())),
+            retained: if retained {
+                super::retained_size_of(items, &ids)
+            } else {
+                0
+            },
+        }
+    }
+}
+
+/// Check the given items against the given budgets, and their growth against
+/// the given baseline, if any.
+pub fn check(
+    items: &mut ir::Items,
+    baseline: Option<&mut ir::Items>,
+    budgets: &Budgets,
+) -> anyhow::Result<CheckReport> {
+    let retained = budgets.iter().any(|budget| {
+        budget
+            .limits
+            .iter()
+            .any(|limit| matches!(limit, Limit::RetainedSize(_)))
+    });
+    if retained {
+        items.compute_retained_sizes();
+    }
+    let items = &*items;
+    let baseline = baseline.map(|baseline| &*baseline);
+
+    let mut checks = vec![];
+    for budget in budgets.iter() {
+        let covered = Covered::measure(items, budget, retained);
+        let old = baseline.map(|baseline| Covered::measure(baseline, budget, false));
+        for &limit in &budget.limits {
+            let actual = match (limit, old.as_ref()) {
+                (Limit::Size(_), _) => Some(Measurement::Size(covered.size)),
+                (Limit::RetainedSize(_), _) => Some(Measurement::Size(covered.retained)),
+                (Limit::Growth(_), Some(old)) => Some(Measurement::Growth(super::size_delta(
+                    old.size,
+                    covered.size,
+                ))),
+                // Anything that appeared since the baseline has grown by more
+                // than any percentage.
+                (Limit::GrowthPercent(_), Some(old)) if old.size == 0 => {
+                    Some(Measurement::GrowthPercent(if covered.size > 0 {
+                        f64::INFINITY
+                    } else {
+                        0.0
+                    }))
+                }
+                (Limit::GrowthPercent(_), Some(old)) => {
+                    let growth = super::size_delta(old.size, covered.size);
+                    Some(Measurement::GrowthPercent(
+                        growth as f64 / old.size as f64 * 100.0,
+                    ))
+                }
+                (Limit::Growth(_), None) | (Limit::GrowthPercent(_), None) => None,
+            };
+            let status = match actual {
+                None => Status::Skipped,
+                Some(actual) if limit.is_exceeded_by(actual) => Status::Exceeded,
+                Some(_) => Status::Ok,
+            };
+            // Infinite growth can't be written out, so it is left unmeasured.
+            let actual = actual.filter(|actual| match *actual {
+                Measurement::GrowthPercent(percent) => percent.is_finite(),
+                _ => true,
+            });
+            checks.push(LimitCheck {
+                budget: budget.description.clone(),
+                limit,
+                actual,
+                status,
+            });
+        }
+    }
+
+    Ok(CheckReport { checks })
+}
@@ -0,0 +1,412 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+
+#[cfg(feature = "emit_csv")]
+use csv;
+#[cfg(feature = "emit_csv")]
+use serde_derive::Serialize;
+
+#[cfg(feature = "emit_json")]
+use crate::formats::json;
+#[cfg(feature = "emit_text")]
+use crate::formats::table::{Align, Table};
+use twiggy_ir as ir;
+use twiggy_opt as opt;
+use twiggy_traits as traits;
+
+#[derive(Debug)]
+struct Crates {
+    crates: Vec<CrateEntry>,
+    // Only the text and CSV output have a row for the total.
+    #[cfg_attr(
+        not(any(feature = "emit_text", feature = "emit_csv")),
+        allow(dead_code)
+    )]
+    total: CrateEntry,
+}
+
+/// A crate, module or type, or a summary of several of them, and the sizes of
+/// the items in it.
+#[derive(Debug)]
+struct CrateEntry {
+    name: String,
+    path: String,
+    shallow: u64,
+    retained: u64,
+    children: Vec<CrateEntry>,
+}
+
+/// A node in the tree of namespaces, with all of the items inside it.
+#[derive(Default)]
+struct Namespace {
+    ids: BTreeSet<ir::Id>,
+    children: BTreeMap<String, Namespace>,
+}
+
+impl traits::Emit for Crates {
+    #[cfg(feature = "emit_text")]
+    fn emit_text(&self, items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+        fn add_rows(table: &mut Table, items: &ir::Items, entry: &CrateEntry, depth: u32) {
+            let mut label = String::with_capacity(depth as usize * 4 + entry.name.len());
+            for _ in 1..depth {
+                label.push_str("    ");
+            }
+            if depth > 0 {
+                label.push_str("  ⤷ ");
+            }
+            label.push_str(&entry.name);
+
+            table.add_row(vec![
+                entry.shallow.to_string(),
+                format!("{:.2}%", super::percent(entry.shallow, items.size())),
+                entry.retained.to_string(),
+                format!("{:.2}%", super::percent(entry.retained, items.size())),
+                label,
+            ]);
+            for child in &entry.children {
+                add_rows(table, items, child, depth + 1);
+            }
+        }
+
+        let mut table = Table::with_header(vec![
+            (Align::Right, "Shallow Bytes".to_string()),
+            (Align::Right, "Shallow %".to_string()),
+            (Align::Right, "Retained Bytes".to_string()),
+            (Align::Right, "Retained %".to_string()),
+            (Align::Left, "Crate".to_string()),
+        ]);
+        for entry in self.crates.iter().chain(Some(&self.total)) {
+            add_rows(&mut table, items, entry, 0);
+        }
+
+        write!(dest, "{}", &table)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_json")]
+    fn emit_json(&self, items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+        fn add_entry(
+            items: &ir::Items,
+            entry: &CrateEntry,
+            obj: &mut json::Object,
+        ) -> anyhow::Result<()> {
+            obj.field("name", entry.path.as_str())?;
+            obj.field("shallow_size", entry.shallow)?;
+            obj.field(
+                "shallow_size_percent",
+                super::percent(entry.shallow, items.size()),
+            )?;
+            obj.field("retained_size", entry.retained)?;
+            obj.field(
+                "retained_size_percent",
+                super::percent(entry.retained, items.size()),
+            )?;
+            if !entry.children.is_empty() {
+                let mut arr = obj.array("children")?;
+                for child in &entry.children {
+                    let mut obj = arr.object()?;
+                    add_entry(items, child, &mut obj)?;
+                }
+            }
+            Ok(())
+        }
+
+        let mut arr = json::array(dest)?;
+        for entry in &self.crates {
+            let mut obj = arr.object()?;
+            add_entry(items, entry, &mut obj)?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_csv")]
+    fn emit_csv(&self, items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+        #[derive(Serialize, Debug)]
+        #[serde(rename_all = "PascalCase")]
+        struct CsvRecord<'a> {
+            name: &'a str,
+            depth: u32,
+            shallow_size: u64,
+            shallow_size_percent: f64,
+            retained_size: u64,
+            retained_size_percent: f64,
+        }
+
+        fn add_records(
+            wtr: &mut csv::Writer<&mut dyn io::Write>,
+            items: &ir::Items,
+            entry: &CrateEntry,
+            depth: u32,
+        ) -> anyhow::Result<()> {
+            wtr.serialize(CsvRecord {
+                name: &entry.path,
+                depth,
+                shallow_size: entry.shallow,
+                shallow_size_percent: super::percent(entry.shallow, items.size()),
+                retained_size: entry.retained,
+                retained_size_percent: super::percent(entry.retained, items.size()),
+            })?;
+            wtr.flush()?;
+            for child in &entry.children {
+                add_records(wtr, items, child, depth + 1)?;
+            }
+            Ok(())
+        }
+
+        let mut wtr = csv::Writer::from_writer(dest);
+        for entry in self.crates.iter().chain(Some(&self.total)) {
+            add_records(&mut wtr, items, entry, 0)?;
+        }
+        Ok(())
+    }
+}
+
+/// Split a top-level `s` at each occurrence of `sep` that isn't nested inside
+/// brackets.
+fn split_top_level<'a>(s: &'a str, sep: &str) -> Vec<&'a str> {
+    let mut parts = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    let mut prev = None;
+    for (i, ch) in s.char_indices() {
+        if depth == 0 && i >= start && s[i..].starts_with(sep) {
+            parts.push(&s[start..i]);
+            start = i + sep.len();
+        } else {
+            match ch {
+                '<' | '(' | '[' | '{' => depth += 1,
+                '>' if prev == Some('-') => {}
+                '>' | ')' | ']' | '}' => depth -= 1,
+                _ => {}
+            }
+        }
+        prev = Some(ch);
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+/// Split a path like `alloc::vec::Vec<u8>` into its segments, without their
+/// generic arguments.
+fn path_segments(path: &str) -> Vec<String> {
+    split_top_level(path, "::")
+        .into_iter()
+        .map(|segment| segment.split('<').next().unwrap().trim())
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| segment.to_string())
+        .collect()
+}
+
+/// Get the namespace that a demangled function name belongs to, as the path of
+/// its crate followed by the modules and types inside it. Methods of trait
+/// impls belong to the implementing type, or to the trait if that type is not
+/// a path, as with `<&T as core::fmt::Debug>::fmt` or `<u32 as Trait>::f`.
+/// Returns an empty path for functions that aren't in any namespace.
+fn namespace(name: &str) -> Vec<String> {
+    // Drop the hash at the end of legacy Rust symbols.
+    let mut name = name;
+    if let Some(idx) = name.rfind("::h") {
+        let hash = &name[idx + 3..];
+        if hash.len() == 16 && hash.chars().all(|ch| ch.is_ascii_hexdigit()) {
+            name = &name[..idx];
+        }
+    }
+
+    // Drop the parameter list and return type of C++ functions.
+    let name = name.replace("(anonymous namespace)", "{anonymous namespace}");
+    let name = split_top_level(&name, "(")[0];
+    let name = split_top_level(name, " ").pop().unwrap();
+
+    if let Some(qualified) = name.strip_prefix('<') {
+        // Find the `>` that closes the qualified path.
+        let mut depth = 1;
+        let mut prev = None;
+        let close = qualified.char_indices().find_map(|(i, ch)| {
+            match ch {
+                '<' => depth += 1,
+                '>' if prev == Some('-') => {}
+                '>' => depth -= 1,
+                _ => {}
+            }
+            prev = Some(ch);
+            if depth == 0 {
+                Some(i)
+            } else {
+                None
+            }
+        });
+        let qualified = match close {
+            Some(close) => &qualified[..close],
+            None => return vec![],
+        };
+
+        let mut parts = split_top_level(qualified, " as ").into_iter();
+        let mut ty = parts.next().unwrap_or_default().trim();
+        let trait_ = parts.next();
+        loop {
+            let stripped = ty
+                .trim_start_matches('&')
+                .trim_start_matches("mut ")
+                .trim_start_matches("*const ")
+                .trim_start_matches("*mut ")
+                .trim_start_matches("dyn ");
+            let stripped = match stripped.strip_prefix('\'') {
+                Some(lifetime) => lifetime.split_once(' ').map_or("", |(_, ty)| ty),
+                None => stripped,
+            };
+            if stripped == ty {
+                break;
+            }
+            ty = stripped;
+        }
+
+        let ty = path_segments(ty);
+        if ty.len() > 1 {
+            return ty;
+        }
+        return trait_.map(path_segments).unwrap_or_default();
+    }
+
+    let mut path = path_segments(name);
+    path.pop();
+    path
+}
+
+/// Sum up the sizes of the items in the given namespace, and of its children,
+/// sorting and truncating them according to the options. Also returns the
+/// items in the namespace.
+fn process_namespace(
+    items: &ir::Items,
+    opts: &opt::Crates,
+    name: String,
+    path: String,
+    namespace: Namespace,
+) -> (CrateEntry, BTreeSet<ir::Id>) {
+    let children = process_children(items, opts, &path, namespace.children);
+    let entry = CrateEntry {
+        name,
+        path,
+        shallow: super::total_size(namespace.ids.iter().map(|&id| items[id].size())),
+        retained: super::retained_size_of(items, &namespace.ids),
+        children,
+    };
+    (entry, namespace.ids)
+}
+
+/// Process the given child namespaces of the namespace at `parent`, and keep
+/// the largest of them, summarizing the rest in one entry.
+fn process_children(
+    items: &ir::Items,
+    opts: &opt::Crates,
+    parent: &str,
+    children: BTreeMap<String, Namespace>,
+) -> Vec<CrateEntry> {
+    let mut children: Vec<(CrateEntry, BTreeSet<ir::Id>)> = children
+        .into_iter()
+        .map(|(name, namespace)| {
+            let path = if parent.is_empty() {
+                name.clone()
+            } else {
+                format!("{}::{}", parent, name)
+            };
+            process_namespace(items, opts, name, path, namespace)
+        })
+        .collect();
+
+    let sort_key = |entry: &CrateEntry| {
+        if opts.retained() {
+            (entry.retained, entry.shallow)
+        } else {
+            (entry.shallow, entry.retained)
+        }
+    };
+    children.sort_by(|(a, _), (b, _)| {
+        sort_key(b)
+            .cmp(&sort_key(a))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    let max_items = opts.max_items() as usize;
+    let remaining: BTreeSet<ir::Id> = children
+        .iter()
+        .skip(max_items)
+        .flat_map(|(_, ids)| ids)
+        .copied()
+        .collect();
+    let remaining_count = children.len().saturating_sub(max_items);
+    children.truncate(max_items);
+
+    let mut entries: Vec<CrateEntry> = children.into_iter().map(|(entry, _)| entry).collect();
+    if remaining_count > 0 {
+        entries.push(summary(
+            items,
+            format!("... and {} more.", remaining_count),
+            &remaining,
+        ));
+    }
+    entries
+}
+
+/// Get the path of the namespace that an item belongs to, starting with its
+/// crate. Items that aren't functions in a namespace belong to a namespace
+/// named after their kind.
+fn path(item: &ir::Item) -> Vec<String> {
+    let path = match item.kind() {
+        ir::ItemKind::Code(_) => namespace(item.name()),
+        _ => vec![],
+    };
+    if path.is_empty() {
+        return vec![format!("[{}]", item.kind().name())];
+    }
+    path
+}
+
+/// Get the name of the crate that an item belongs to.
+pub(super) fn crate_of(item: &ir::Item) -> String {
+    path(item).swap_remove(0)
+}
+
+/// Summarize the given items in one entry, without children.
+fn summary(items: &ir::Items, name: String, ids: &BTreeSet<ir::Id>) -> CrateEntry {
+    CrateEntry {
+        path: name.clone(),
+        name,
+        shallow: super::total_size(ids.iter().map(|&id| items[id].size())),
+        retained: super::retained_size_of(items, ids),
+        children: vec![],
+    }
+}
+
+/// Roll the sizes of functions up into the crates, modules and types they
+/// belong to.
+pub fn crates(items: &mut ir::Items, opts: &opt::Crates) -> anyhow::Result<Box<dyn traits::Emit>> {
+    items.compute_retained_sizes();
+    let items = &*items;
+
+    // Items that aren't functions in a namespace are grouped by their kind.
+    let mut root = Namespace::default();
+    let mut all = BTreeSet::new();
+    for item in items.iter() {
+        if item.id() == items.meta_root() || !super::matches_kinds(item, opts.kinds()) {
+            continue;
+        }
+        let path = path(item);
+
+        all.insert(item.id());
+        let mut namespace = &mut root;
+        for segment in path.into_iter().take(opts.max_depth() as usize) {
+            namespace = namespace.children.entry(segment).or_default();
+            namespace.ids.insert(item.id());
+        }
+    }
+
+    let total = summary(
+        items,
+        format!("Σ [{} Total Crates]", root.children.len()),
+        &all,
+    );
+    let crates = process_children(items, opts, "", root.children);
+
+    Ok(Box::new(Crates { crates, total }) as Box<_>)
+}
@@ -0,0 +1,555 @@
+//! Diffing the dominator trees of two versions of a binary, by aligning them
+//! on matched items, to show which subtrees' retained sizes changed.
+
+use std::cmp;
+use std::collections::HashMap;
+use std::io;
+use std::mem;
+
+#[cfg(feature = "emit_csv")]
+use serde_derive::Serialize;
+
+#[cfg(feature = "emit_json")]
+use crate::formats::json;
+#[cfg(feature = "emit_text")]
+use crate::formats::table::{Align, Table};
+use twiggy_ir as ir;
+use twiggy_traits as traits;
+
+use super::matching::Match;
+use crate::analyses::{size_delta, total_size};
+
+/// The aligned dominator trees of the old and new versions.
+#[derive(Debug)]
+pub(super) struct DominatorDiff {
+    nodes: Vec<Node>,
+    /// How many rows were left out to keep within the maximum.
+    remaining: usize,
+    total: Node,
+}
+
+/// An item in the aligned dominator trees, or a summary row.
+#[derive(Debug)]
+struct Node {
+    name: String,
+    old_retained: Option<u64>,
+    new_retained: Option<u64>,
+    /// The change in the item's own size. Summary rows and items that moved
+    /// away have none.
+    delta: Option<i64>,
+    /// Where the item moved to or from in the dominator tree, if it did.
+    moved: Option<String>,
+    children: Vec<Node>,
+    /// Did nothing in this item's subtree change?
+    unchanged: bool,
+    /// The number of rows that this node and its subtree take up, before
+    /// truncation.
+    rows: usize,
+}
+
+impl Drop for Node {
+    // Drop the children with an explicit stack rather than by recursion, so
+    // that very deep trees can't overflow the call stack.
+    fn drop(&mut self) {
+        let mut stack = mem::take(&mut self.children);
+        while let Some(mut node) = stack.pop() {
+            stack.append(&mut node.children);
+        }
+    }
+}
+
+impl Node {
+    fn summary(name: String, old_retained: u64, new_retained: u64) -> Node {
+        Node {
+            name,
+            old_retained: Some(old_retained),
+            new_retained: Some(new_retained),
+            delta: None,
+            moved: None,
+            children: vec![],
+            unchanged: old_retained == new_retained,
+            rows: 1,
+        }
+    }
+
+    /// The change in the retained size of this item's subtree.
+    fn retained_delta(&self) -> i64 {
+        size_delta(
+            self.old_retained.unwrap_or(0),
+            self.new_retained.unwrap_or(0),
+        )
+    }
+
+    /// Keep at most `max` rows of the given nodes, in order, depth first.
+    /// Returns the nodes that were kept, and how many rows were left out.
+    fn truncate(nodes: Vec<Node>, max: usize) -> (Vec<Node>, usize) {
+        // Rebuild the trees with an explicit stack rather than by recursion.
+        // Each level of the stack holds the nodes left to visit at that depth,
+        // the ones that were kept, and their parent.
+        let mut max = max;
+        let mut removed = 0;
+        let mut stack = vec![(nodes.into_iter(), vec![], None::<Node>)];
+        loop {
+            let (next, _, _) = stack.last_mut().unwrap();
+            match next.next() {
+                Some(node) if max == 0 => removed += node.rows,
+                Some(mut node) => {
+                    max -= 1;
+                    let children = mem::take(&mut node.children);
+                    stack.push((children.into_iter(), vec![], Some(node)));
+                }
+                None => {
+                    let (_, kept, parent) = stack.pop().unwrap();
+                    match parent {
+                        Some(mut parent) => {
+                            parent.children = kept;
+                            stack.last_mut().unwrap().1.push(parent);
+                        }
+                        None => return (kept, removed),
+                    }
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "emit_text")]
+    fn label(&self) -> String {
+        match self.moved {
+            Some(ref moved) => format!("{} ({})", self.name, moved),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// The two dominator trees, and the matches that align them. Both versions
+/// must have their retained sizes computed.
+struct Trees<'a> {
+    old_items: &'a ir::Items,
+    new_items: &'a ir::Items,
+    /// Every match, and the meta roots' at the end.
+    matches: Vec<Match>,
+    old_index: HashMap<ir::Id, usize>,
+    new_index: HashMap<ir::Id, usize>,
+}
+
+impl<'a> Trees<'a> {
+    fn new(old_items: &'a ir::Items, new_items: &'a ir::Items, matches: &[Match]) -> Trees<'a> {
+        let mut matches = matches.to_vec();
+        matches.push((Some(old_items.meta_root()), Some(new_items.meta_root())));
+        let mut old_index = HashMap::new();
+        let mut new_index = HashMap::new();
+        for (i, &(old, new)) in matches.iter().enumerate() {
+            if let Some(old) = old {
+                old_index.insert(old, i);
+            }
+            if let Some(new) = new {
+                new_index.insert(new, i);
+            }
+        }
+        Trees {
+            old_items,
+            new_items,
+            matches,
+            old_index,
+            new_index,
+        }
+    }
+
+    fn meta_root(&self) -> usize {
+        self.matches.len() - 1
+    }
+
+    /// The name of the matched items, as they are named in the new version if
+    /// they are in it.
+    fn name(&self, index: usize) -> String {
+        match self.matches[index] {
+            (_, Some(new)) => self.new_items.display_name(new).into_owned(),
+            (Some(old), None) => self.old_items.display_name(old).into_owned(),
+            (None, None) => unreachable!("every match has an old or a new item"),
+        }
+    }
+
+    /// The matched items that immediately dominate the given old item.
+    fn old_parent(&self, old: ir::Id) -> Option<usize> {
+        self.old_items
+            .immediate_dominator(old)
+            .map(|parent| self.old_index[&parent])
+    }
+
+    /// The matched items that immediately dominate the given new item.
+    fn new_parent(&self, new: ir::Id) -> Option<usize> {
+        self.new_items
+            .immediate_dominator(new)
+            .map(|parent| self.new_index[&parent])
+    }
+
+    /// Describe a move in the dominator tree from or to the given parent.
+    fn describe_parent(&self, parent: Option<usize>) -> String {
+        match parent {
+            None => "unreachable".to_string(),
+            Some(parent) if parent == self.meta_root() => "a root".to_string(),
+            Some(parent) => format!("`{}`", self.name(parent)),
+        }
+    }
+
+    /// The children of the given matched items in the aligned trees: the
+    /// items it dominates in either version, each under its new parent if it
+    /// is in the new version. An item that moved is also listed under its old
+    /// parent, without its subtree.
+    fn children(&self, index: usize) -> Vec<Child> {
+        let (old, new) = self.matches[index];
+
+        let mut children = vec![];
+        if let Some(new) = new {
+            for child in self.new_items.immediately_dominated(new) {
+                children.push(Child::Subtree(self.new_index[&child]));
+            }
+        }
+        if let Some(old) = old {
+            for child in self.old_items.immediately_dominated(old) {
+                let child_index = self.old_index[&child];
+                match self.matches[child_index].1 {
+                    None => children.push(Child::Subtree(child_index)),
+                    Some(child_new) if self.new_parent(child_new) == Some(index) => {}
+                    Some(child_new) => children.push(Child::Moved(Node {
+                        name: self.name(child_index),
+                        old_retained: Some(self.old_items.retained_size(child)),
+                        new_retained: None,
+                        delta: None,
+                        moved: Some(format!(
+                            "moved to {}",
+                            self.describe_parent(self.new_parent(child_new))
+                        )),
+                        children: vec![],
+                        unchanged: false,
+                        rows: 1,
+                    })),
+                }
+            }
+        }
+        children
+    }
+
+    /// Build the node for the given matched items, from its finished children.
+    fn finish_node(&self, index: usize, children: Vec<Node>) -> Node {
+        let (old, new) = self.matches[index];
+        let all_unchanged = children.iter().all(|child| child.unchanged);
+
+        // Collapse the subtrees that didn't change into one row.
+        let (unchanged, mut children): (Vec<Node>, Vec<Node>) =
+            children.into_iter().partition(|child| child.unchanged);
+        children.sort_by(|a, b| {
+            cmp::Reverse(a.retained_delta().abs())
+                .cmp(&cmp::Reverse(b.retained_delta().abs()))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        if !unchanged.is_empty() {
+            let size = total_size(unchanged.iter().map(|node| node.new_retained.unwrap_or(0)));
+            children.push(Node::summary(
+                format!("[{} Unchanged Subtrees]", unchanged.len()),
+                size,
+                size,
+            ));
+        }
+
+        let moved = match (old, new) {
+            (Some(old), Some(new)) if self.old_parent(old) != self.new_parent(new) => Some(
+                format!("moved from {}", self.describe_parent(self.old_parent(old))),
+            ),
+            _ => None,
+        };
+        let old_size = old.map_or(0, |old| self.old_items[old].size());
+        let new_size = new.map_or(0, |new| self.new_items[new].size());
+        let old_retained = old.map(|old| self.old_items.retained_size(old));
+        let new_retained = new.map(|new| self.new_items.retained_size(new));
+        let delta = size_delta(old_size, new_size);
+        Node {
+            name: self.name(index),
+            old_retained,
+            new_retained,
+            delta: Some(delta),
+            unchanged: all_unchanged
+                && old_retained == new_retained
+                && delta == 0
+                && moved.is_none(),
+            moved,
+            rows: 1 + children.iter().map(|child| child.rows).sum::<usize>(),
+            children,
+        }
+    }
+
+    /// Build the aligned subtree of the given matched items. The subtree is
+    /// built with an explicit stack rather than by recursion, since dominator
+    /// trees can be far deeper than the call stack. Each level of the stack
+    /// holds an item, its children left to build, and the ones already built.
+    fn node(&self, index: usize) -> Node {
+        let mut stack = vec![(index, self.children(index).into_iter(), vec![])];
+        loop {
+            let (_, next, built) = stack.last_mut().unwrap();
+            match next.next() {
+                Some(Child::Moved(node)) => built.push(node),
+                Some(Child::Subtree(child)) => {
+                    stack.push((child, self.children(child).into_iter(), vec![]));
+                }
+                None => {
+                    let (index, _, built) = stack.pop().unwrap();
+                    let node = self.finish_node(index, built);
+                    match stack.last_mut() {
+                        Some((_, _, built)) => built.push(node),
+                        None => return node,
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A child in the aligned trees: the subtree of some matched items, yet to be
+/// built, or an item listed under its old parent after it moved.
+enum Child {
+    Subtree(usize),
+    Moved(Node),
+}
+
+/// Align the dominator trees of the old and new versions, starting from the
+/// given matched items, or from the roots if none are given, and show at most
+/// `max_rows` rows of them.
+pub(super) fn diff(
+    old_items: &ir::Items,
+    new_items: &ir::Items,
+    matches: &[Match],
+    roots: Option<&[Match]>,
+    max_rows: usize,
+) -> DominatorDiff {
+    let trees = Trees::new(old_items, new_items, matches);
+
+    let (nodes, total) = match roots {
+        Some(roots) => {
+            let nodes: Vec<Node> = roots
+                .iter()
+                .map(|&(old, new)| {
+                    let index = match (old, new) {
+                        (_, Some(new)) => trees.new_index[&new],
+                        (Some(old), None) => trees.old_index[&old],
+                        (None, None) => unreachable!("every match has an old or a new item"),
+                    };
+                    trees.node(index)
+                })
+                .collect();
+            let (old, new) = nodes.iter().fold((0u64, 0u64), |(old, new), node| {
+                (
+                    old.saturating_add(node.old_retained.unwrap_or(0)),
+                    new.saturating_add(node.new_retained.unwrap_or(0)),
+                )
+            });
+            (nodes, (old, new))
+        }
+        None => (
+            mem::take(&mut trees.node(trees.meta_root()).children),
+            (old_items.size(), new_items.size()),
+        ),
+    };
+
+    let rows: usize = nodes.iter().map(|node| node.rows).sum();
+    let (nodes, remaining) = Node::truncate(nodes, max_rows);
+    let total = Node::summary(format!("Σ [{} Total Rows]", rows), total.0, total.1);
+    DominatorDiff {
+        nodes,
+        remaining,
+        total,
+    }
+}
+
+impl traits::Emit for DominatorDiff {
+    #[cfg(feature = "emit_text")]
+    fn emit_text(&self, _items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+        // Add rows for the node and its subtree, in pre order with an explicit
+        // stack rather than by recursion, since the trees can be far deeper
+        // than the call stack.
+        fn add_rows(table: &mut Table, node: &Node) {
+            let mut stack = vec![(node, 0)];
+            while let Some((node, depth)) = stack.pop() {
+                let mut label = String::new();
+                for _ in 1..depth {
+                    label.push_str("    ");
+                }
+                if depth > 0 {
+                    label.push_str("  ⤷ ");
+                }
+                label.push_str(&node.label());
+
+                let or_blank = |size: Option<u64>| size.map_or_else(String::new, |s| s.to_string());
+                table.add_row(vec![
+                    format!("{:+}", node.retained_delta()),
+                    or_blank(node.old_retained),
+                    or_blank(node.new_retained),
+                    node.delta
+                        .map_or_else(String::new, |delta| format!("{:+}", delta)),
+                    label,
+                ]);
+                let children = node.children.iter().rev();
+                stack.extend(children.map(|child| (child, depth + 1)));
+            }
+        }
+
+        let mut table = Table::with_header(vec![
+            (Align::Right, "Retained Delta".to_string()),
+            (Align::Right, "Old Retained".to_string()),
+            (Align::Right, "New Retained".to_string()),
+            (Align::Right, "Delta Bytes".to_string()),
+            (Align::Left, "Dominator Tree".to_string()),
+        ]);
+        for node in &self.nodes {
+            add_rows(&mut table, node);
+        }
+        if self.remaining > 0 {
+            table.add_row(vec![
+                String::new(),
+                String::new(),
+                String::new(),
+                String::new(),
+                format!("... and {} more.", self.remaining),
+            ]);
+        }
+        add_rows(&mut table, &self.total);
+
+        write!(dest, "{}", &table)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_json")]
+    fn emit_json(&self, _items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+        fn add_fields(node: &Node, obj: &mut json::Object) -> anyhow::Result<()> {
+            obj.field("name", node.name.as_str())?;
+            obj.field("retained_delta_bytes", node.retained_delta())?;
+            if let Some(old_retained) = node.old_retained {
+                obj.field("old_retained_size", old_retained)?;
+            }
+            if let Some(new_retained) = node.new_retained {
+                obj.field("new_retained_size", new_retained)?;
+            }
+            if let Some(delta) = node.delta {
+                obj.field("delta_bytes", delta)?;
+            }
+            if let Some(ref moved) = node.moved {
+                obj.field("moved", moved.as_str())?;
+            }
+            Ok(())
+        }
+
+        // Add the node's fields to the object, along with its children. Those
+        // are written with an explicit stack rather than by recursion, since
+        // the trees can be far deeper than the call stack. Each level of the
+        // stack holds the children left to write at that depth, and whether
+        // any have been written yet.
+        fn add_node(node: &Node, obj: &mut json::Object) -> anyhow::Result<()> {
+            add_fields(node, obj)?;
+            if node.children.is_empty() {
+                return Ok(());
+            }
+
+            let w = obj.raw("children")?;
+            write!(w, "[")?;
+            let mut stack = vec![(node.children.iter(), false)];
+            while let Some((next, any_written)) = stack.last_mut() {
+                match next.next() {
+                    Some(child) => {
+                        if mem::replace(any_written, true) {
+                            write!(w, ",")?;
+                        }
+                        let mut obj = json::object(&mut *w)?;
+                        add_fields(child, &mut obj)?;
+                        if !child.children.is_empty() {
+                            write!(obj.raw("children")?, "[")?;
+                            obj.leave_open();
+                            stack.push((child.children.iter(), false));
+                        }
+                    }
+                    None => {
+                        stack.pop();
+                        write!(w, "]")?;
+                        if !stack.is_empty() {
+                            write!(w, "}}")?;
+                        }
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        let mut obj = json::object(dest)?;
+        {
+            let mut arr = obj.array("items")?;
+            for node in &self.nodes {
+                add_node(node, &mut arr.object()?)?;
+            }
+        }
+        let mut summary = obj.array("summary")?;
+        if self.remaining > 0 {
+            let mut remaining = summary.object()?;
+            remaining.field("name", format!("... and {} more.", self.remaining).as_str())?;
+        }
+        add_node(&self.total, &mut summary.object()?)?;
+
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_csv")]
+    fn emit_csv(&self, _items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+        #[derive(Serialize, Debug)]
+        #[serde(rename_all = "PascalCase")]
+        struct CsvRecord<'a> {
+            name: &'a str,
+            depth: usize,
+            retained_delta_bytes: Option<String>,
+            old_retained_size: Option<u64>,
+            new_retained_size: Option<u64>,
+            delta_bytes: Option<String>,
+            moved: Option<&'a str>,
+        }
+
+        // Add records for the node and its subtree, in pre order with an
+        // explicit stack rather than by recursion, since the trees can be far
+        // deeper than the call stack.
+        fn add_records(
+            wtr: &mut csv::Writer<&mut dyn io::Write>,
+            node: &Node,
+        ) -> anyhow::Result<()> {
+            let mut stack = vec![(node, 0)];
+            while let Some((node, depth)) = stack.pop() {
+                wtr.serialize(CsvRecord {
+                    name: &node.name,
+                    depth,
+                    retained_delta_bytes: Some(format!("{:+}", node.retained_delta())),
+                    old_retained_size: node.old_retained,
+                    new_retained_size: node.new_retained,
+                    delta_bytes: node.delta.map(|delta| format!("{:+}", delta)),
+                    moved: node.moved.as_deref(),
+                })?;
+                wtr.flush()?;
+                let children = node.children.iter().rev();
+                stack.extend(children.map(|child| (child, depth + 1)));
+            }
+            Ok(())
+        }
+
+        let mut wtr = csv::Writer::from_writer(dest);
+        for node in &self.nodes {
+            add_records(&mut wtr, node)?;
+        }
+        if self.remaining > 0 {
+            wtr.serialize(CsvRecord {
+                name: &format!("... and {} more.", self.remaining),
+                depth: 0,
+                retained_delta_bytes: None,
+                old_retained_size: None,
+                new_retained_size: None,
+                delta_bytes: None,
+                moved: None,
+            })?;
+            wtr.flush()?;
+        }
+        add_records(&mut wtr, &self.total)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,215 @@
+//! Explaining why an item appeared in the new version of a binary, or why its
+//! retained size grew, by comparing its callers, retaining paths and
+//! dominators in the old and new versions.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use twiggy_ir as ir;
+
+/// The most newly retained items to name in an explanation.
+const MAX_NEWLY_RETAINED: usize = 5;
+
+/// Why an item appeared or grew. Every part is empty if nothing about the
+/// item's place in the graph changed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(super) struct Explanation {
+    /// The items that reference this item in the new version but did not
+    /// reference it in the old one.
+    pub(super) new_callers: Vec<String>,
+    /// The shortest path from a root to this item in the new version, if it
+    /// takes an edge that the old version did not have.
+    pub(super) new_path: Option<Vec<String>>,
+    /// This item's old and new immediate dominators, if they changed.
+    pub(super) dominator_change: Option<(String, String)>,
+    /// The largest items that this item retains in the new version but did
+    /// not retain in the old one, with their sizes.
+    pub(super) newly_retained: Vec<(String, u64)>,
+    /// How many more items this item newly retains.
+    pub(super) more_newly_retained: usize,
+}
+
+impl Explanation {
+    /// Describe this explanation in sentences, one for each part of it.
+    #[cfg(any(feature = "emit_text", feature = "emit_csv"))]
+    pub(super) fn lines(&self) -> Vec<String> {
+        let quote = |names: &[String]| {
+            names
+                .iter()
+                .map(|name| format!("`{}`", name))
+                .collect::<Vec<_>>()
+        };
+
+        let mut lines = vec![];
+        if !self.new_callers.is_empty() {
+            lines.push(format!(
+                "newly referenced by {}",
+                quote(&self.new_callers).join(", ")
+            ));
+        }
+        match self.new_path {
+            Some(ref path) if path.len() == 1 => lines.push("now a root".to_string()),
+            Some(ref path) => lines.push(format!(
+                "now reachable because {} was added",
+                quote(path).join(" → ")
+            )),
+            None => {}
+        }
+        if let Some((ref old, ref new)) = self.dominator_change {
+            lines.push(format!(
+                "immediate dominator changed from `{}` to `{}`",
+                old, new
+            ));
+        }
+        if !self.newly_retained.is_empty() {
+            let mut retained: Vec<String> = self
+                .newly_retained
+                .iter()
+                .map(|(name, size)| format!("`{}` ({} bytes)", name, size))
+                .collect();
+            if self.more_newly_retained > 0 {
+                retained.push(format!("{} more", self.more_newly_retained));
+            }
+            lines.push(format!("newly retains {}", retained.join(", ")));
+        }
+        lines
+    }
+}
+
+/// The two versions of the graph, and which old item each new item was
+/// matched with. Both must have their retained sizes computed.
+pub(super) struct Versions<'a> {
+    old_items: &'a ir::Items,
+    new_items: &'a ir::Items,
+    old_ids: HashMap<ir::Id, ir::Id>,
+}
+
+impl<'a> Versions<'a> {
+    pub(super) fn new(
+        old_items: &'a ir::Items,
+        new_items: &'a ir::Items,
+        matches: &[super::matching::Match],
+    ) -> Versions<'a> {
+        let mut old_ids: HashMap<ir::Id, ir::Id> = matches
+            .iter()
+            .filter_map(|&(old, new)| Some((new?, old?)))
+            .collect();
+        old_ids.insert(new_items.meta_root(), old_items.meta_root());
+        Versions {
+            old_items,
+            new_items,
+            old_ids,
+        }
+    }
+
+    /// Did the old version have an edge between the items that the two ends of
+    /// the given new edge were matched with?
+    fn had_edge(&self, from: ir::Id, to: ir::Id) -> bool {
+        match (self.old_ids.get(&from), self.old_ids.get(&to)) {
+            (Some(&from), Some(&to)) => self.old_items.neighbors(from).any(|id| id == to),
+            _ => false,
+        }
+    }
+
+    /// Does the given old item dominate the other given old item?
+    fn old_dominates(&self, dominator: ir::Id, mut id: ir::Id) -> bool {
+        while let Some(idom) = self.old_items.immediate_dominator(id) {
+            if idom == dominator {
+                return true;
+            }
+            id = idom;
+        }
+        false
+    }
+
+    /// Find the shortest path from a root to the given new item, root first,
+    /// if any root reaches it.
+    fn shortest_path(&self, id: ir::Id) -> Option<Vec<ir::Id>> {
+        let items = self.new_items;
+        let meta_root = items.meta_root();
+
+        // Search breadth first through the item's callers, remembering which
+        // callee each caller was first reached from.
+        let mut callees = BTreeMap::new();
+        let mut queue = VecDeque::from(vec![id]);
+        while let Some(node) = queue.pop_front() {
+            if items.predecessors(node).any(|caller| caller == meta_root) {
+                let mut path = vec![node];
+                let mut node = node;
+                while let Some(&callee) = callees.get(&node) {
+                    path.push(callee);
+                    node = callee;
+                }
+                return Some(path);
+            }
+            for caller in items.predecessors(node) {
+                if caller == id || callees.contains_key(&caller) {
+                    continue;
+                }
+                callees.insert(caller, node);
+                queue.push_back(caller);
+            }
+        }
+        None
+    }
+
+    /// Explain why the given new item, matched with the given old item if it
+    /// was not added, appeared or grew.
+    pub(super) fn explain(&self, old: Option<ir::Id>, new: ir::Id) -> Explanation {
+        let items = self.new_items;
+        let meta_root = items.meta_root();
+        let name = |id: ir::Id| items.display_name(id).into_owned();
+
+        let new_callers = items
+            .predecessors(new)
+            .filter(|&caller| caller != meta_root && !self.had_edge(caller, new))
+            .map(name)
+            .collect();
+
+        let new_path = self.shortest_path(new).filter(|path| {
+            !self.had_edge(meta_root, path[0])
+                || path.windows(2).any(|edge| !self.had_edge(edge[0], edge[1]))
+        });
+        let new_path = new_path.map(|path| path.into_iter().map(name).collect());
+
+        let dominator_change = old.and_then(|old| {
+            let old_idom = self.old_items.immediate_dominator(old)?;
+            let new_idom = items.immediate_dominator(new)?;
+            if self.old_ids.get(&new_idom) == Some(&old_idom) {
+                return None;
+            }
+            Some((
+                self.old_items.display_name(old_idom).into_owned(),
+                name(new_idom),
+            ))
+        });
+
+        // Walk the item's subtree of the new dominator tree for the items that
+        // it did not dominate in the old one.
+        let mut newly_retained = vec![];
+        let mut stack: Vec<ir::Id> = items.immediately_dominated(new).collect();
+        while let Some(id) = stack.pop() {
+            let retained_before = match (old, self.old_ids.get(&id)) {
+                (Some(old), Some(&old_id)) => self.old_dominates(old, old_id),
+                _ => false,
+            };
+            if !retained_before {
+                newly_retained.push((items[id].size(), id));
+            }
+            stack.extend(items.immediately_dominated(id));
+        }
+        newly_retained.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        let more_newly_retained = newly_retained.len().saturating_sub(MAX_NEWLY_RETAINED);
+        newly_retained.truncate(MAX_NEWLY_RETAINED);
+
+        Explanation {
+            new_callers,
+            new_path,
+            dominator_change,
+            newly_retained: newly_retained
+                .into_iter()
+                .map(|(size, id)| (name(id), size))
+                .collect(),
+            more_newly_retained,
+        }
+    }
+}
@@ -0,0 +1,202 @@
+//! Matching the items of the old version of a binary with the items of the new
+//! one that they became.
+//!
+//! Items are matched in rounds, and each round only looks at the items that
+//! earlier rounds left unmatched:
+//!
+//! 1. Items with exactly the same name.
+//! 2. Items whose names are the same once the parts that change from build to
+//!    build are normalized away: the hashes of legacy Rust symbols, the
+//!    indices of closures and shims, LLVM's numbered clone suffixes, and the
+//!    indices of wasm entries like `type[3]`.
+//! 3. Items of the same kind and size that reference the same items, when
+//!    that is true of only one old and one new item. This finds items whose
+//!    names are missing, like `code[7]`, or that were renamed.
+//! 4. Items of the same kind that reference the same items, whatever their
+//!    size, when that is true of only one old and one new item.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use twiggy_ir as ir;
+
+/// An old item and the new item it became. Items that were removed have no
+/// new item, and items that were added have no old item.
+pub(crate) type Match = (Option<ir::Id>, Option<ir::Id>);
+
+/// Normalize away the parts of an item's name that change from build to build
+/// without the item itself changing.
+pub(super) fn normalize(name: &str) -> Cow<'_, str> {
+    static PATTERNS: OnceLock<Vec<(Regex, &str)>> = OnceLock::new();
+    let patterns = PATTERNS.get_or_init(|| {
+        vec![
+            // The hash at the end of legacy Rust symbols.
+            (Regex::new(r"::h[0-9a-f]{16}\b").unwrap(), ""),
+            // Closure, shim and C++ lambda indices, as in `{closure#2}`.
+            (Regex::new(r"#\d+\}").unwrap(), "}"),
+            // Suffixes of functions that LLVM cloned or made local.
+            (Regex::new(r"(\.llvm\.\d+|\.\d+)+$").unwrap(), ""),
+            // The indices of wasm entries, as in `type[3]: (i32) -> nil`.
+            (Regex::new(r"^([a-z]+)\[\d+\]").unwrap(), "$1[]"),
+        ]
+    });
+
+    let mut name = Cow::Borrowed(name);
+    for (pattern, replacement) in patterns {
+        if let Cow::Owned(replaced) = pattern.replace_all(&name, *replacement) {
+            name = Cow::Owned(replaced);
+        }
+    }
+    name
+}
+
+/// Is this an item's whole name only its kind and index, as in `code[7]`, so
+/// that it says nothing about which item it is once normalized?
+pub(super) fn is_placeholder(normalized: &str) -> bool {
+    normalized.ends_with("[]") && !normalized[..normalized.len() - 2].contains(['[', ' '])
+}
+
+/// One side of the diff: the items of one version of the binary, and which of
+/// them are still unmatched.
+struct Side<'a> {
+    items: &'a ir::Items,
+    ids: Vec<ir::Id>,
+    matched: Vec<bool>,
+}
+
+impl<'a> Side<'a> {
+    fn new(items: &'a ir::Items, ids: Vec<ir::Id>) -> Side<'a> {
+        let matched = vec![false; ids.len()];
+        Side {
+            items,
+            ids,
+            matched,
+        }
+    }
+
+    /// Group the indices of the unmatched items by the given key, skipping the
+    /// items that have none.
+    fn unmatched_by<K, F>(&self, mut key: F) -> BTreeMap<K, Vec<usize>>
+    where
+        K: Ord,
+        F: FnMut(ir::Id) -> Option<K>,
+    {
+        let mut groups: BTreeMap<K, Vec<usize>> = BTreeMap::new();
+        for (i, &id) in self.ids.iter().enumerate() {
+            if self.matched[i] {
+                continue;
+            }
+            if let Some(key) = key(id) {
+                groups.entry(key).or_default().push(i);
+            }
+        }
+        groups
+    }
+
+    /// The shape of an item: its kind, and the normalized names of the items
+    /// it references.
+    fn shape(&self, id: ir::Id) -> (&'static str, Vec<String>) {
+        let mut neighbors: Vec<String> = self
+            .items
+            .neighbors(id)
+            .map(|neighbor| normalize(self.items[neighbor].name()).into_owned())
+            .collect();
+        neighbors.sort();
+        (self.items[id].kind().name(), neighbors)
+    }
+}
+
+/// Pair up the unmatched items of both sides that have the same key, in the
+/// order they come in. With `unique`, only keys that exactly one old and one
+/// new item have are paired up.
+fn match_by<K, F>(
+    old: &mut Side<'_>,
+    new: &mut Side<'_>,
+    matches: &mut Vec<Match>,
+    unique: bool,
+    key: F,
+) where
+    K: Ord,
+    F: Fn(&Side<'_>, ir::Id) -> Option<K>,
+{
+    let old_groups = old.unmatched_by(|id| key(old, id));
+    let mut new_groups = new.unmatched_by(|id| key(new, id));
+    for (key, old_indices) in old_groups {
+        let new_indices = match new_groups.remove(&key) {
+            Some(new_indices) => new_indices,
+            None => continue,
+        };
+        if unique && (old_indices.len() != 1 || new_indices.len() != 1) {
+            continue;
+        }
+        for (o, n) in old_indices.into_iter().zip(new_indices) {
+            old.matched[o] = true;
+            new.matched[n] = true;
+            matches.push((Some(old.ids[o]), Some(new.ids[n])));
+        }
+    }
+}
+
+/// Match the given old items with the given new items. With `exact_names`,
+/// only items with exactly the same name are matched.
+pub(crate) fn match_items(
+    old_items: &ir::Items,
+    old_ids: Vec<ir::Id>,
+    new_items: &ir::Items,
+    new_ids: Vec<ir::Id>,
+    exact_names: bool,
+) -> Vec<Match> {
+    let mut old = Side::new(old_items, old_ids);
+    let mut new = Side::new(new_items, new_ids);
+    let mut matches = vec![];
+
+    // Items that share a name are told apart by the suffix of their display
+    // names, so the first round matches display names.
+    match_by(&mut old, &mut new, &mut matches, false, |side, id| {
+        Some(side.items.display_name(id).into_owned())
+    });
+
+    if !exact_names {
+        let normalized_name = |side: &Side<'_>, id: ir::Id| {
+            let name = normalize(side.items[id].name()).into_owned();
+            if is_placeholder(&name) {
+                None
+            } else {
+                Some((side.items[id].kind().name(), name))
+            }
+        };
+        match_by(&mut old, &mut new, &mut matches, false, normalized_name);
+
+        match_by(&mut old, &mut new, &mut matches, true, |side, id| {
+            let (kind, neighbors) = side.shape(id);
+            let placeholder = is_placeholder(&normalize(side.items[id].name()));
+            if neighbors.is_empty() && !placeholder {
+                return None;
+            }
+            Some((kind, side.items[id].size(), neighbors))
+        });
+
+        match_by(&mut old, &mut new, &mut matches, true, |side, id| {
+            let (kind, neighbors) = side.shape(id);
+            if neighbors.is_empty() {
+                return None;
+            }
+            Some((kind, neighbors))
+        });
+    }
+
+    for (i, &id) in old.ids.iter().enumerate() {
+        if !old.matched[i] {
+            matches.push((Some(id), None));
+        }
+    }
+    for (i, &id) in new.ids.iter().enumerate() {
+        if !new.matched[i] {
+            matches.push((None, Some(id)));
+        }
+    }
+    matches
+}
@@ -0,0 +1,713 @@
+mod dominators;
+mod explain;
+pub(super) mod matching;
+
+use crate::analyses::categories;
+#[cfg(feature = "emit_json")]
+use crate::formats::json;
+#[cfg(feature = "emit_text")]
+use crate::formats::table::{Align, Table};
+use anyhow::anyhow;
+#[cfg(feature = "emit_csv")]
+use csv;
+use regex;
+#[cfg(feature = "emit_csv")]
+use serde::{self, ser::SerializeStruct};
+use std::cmp;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::io;
+use twiggy_ir as ir;
+use twiggy_opt as opt;
+use twiggy_traits as traits;
+
+#[derive(Debug)]
+struct Diff {
+    deltas: Vec<DiffEntry>,
+    #[cfg_attr(not(feature = "emit_text"), allow(dead_code))]
+    retained: bool,
+    /// Whether the items were grouped, with `--group-by`.
+    #[cfg_attr(not(feature = "emit_csv"), allow(dead_code))]
+    grouped: bool,
+    /// Whether the items were explained, with `--explain`.
+    #[cfg_attr(not(feature = "emit_csv"), allow(dead_code))]
+    explained: bool,
+}
+
+/// Classify the change from the given old size to the given new size. An
+/// item that kept its size only shows up in a diff if it was renamed.
+fn classify(old_size: Option<u64>, new_size: Option<u64>) -> traits::Change {
+    match (old_size, new_size) {
+        (None, _) => traits::Change::Added,
+        (_, None) => traits::Change::Removed,
+        (Some(old), Some(new)) if new > old => traits::Change::Grown,
+        (Some(old), Some(new)) if new < old => traits::Change::Shrunk,
+        _ => traits::Change::Renamed,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct DiffEntry {
+    name: String,
+    /// The item's old name, if it was renamed.
+    old_name: Option<String>,
+    old_size: Option<u64>,
+    new_size: Option<u64>,
+    old_retained: Option<u64>,
+    new_retained: Option<u64>,
+    delta: i64,
+    /// How the item changed. Summary rows have none.
+    change: Option<traits::Change>,
+    /// The old and new item of this entry. Groups and summary rows have
+    /// neither.
+    ids: matching::Match,
+    /// Why the item appeared or grew, with `--explain`.
+    explanation: Option<explain::Explanation>,
+    /// The changed items in this group, with `--expand`.
+    members: Vec<DiffEntry>,
+}
+
+impl DiffEntry {
+    /// An entry for an item or group that was only in the old version if
+    /// it has no new size, or only in the new version if it has no old size.
+    fn new(name: String, old_size: Option<u64>, new_size: Option<u64>) -> DiffEntry {
+        DiffEntry {
+            name,
+            old_name: None,
+            old_size,
+            new_size,
+            old_retained: None,
+            new_retained: None,
+            delta: super::size_delta(old_size.unwrap_or(0), new_size.unwrap_or(0)),
+            change: Some(classify(old_size, new_size)),
+            ids: (None, None),
+            explanation: None,
+            members: vec![],
+        }
+    }
+
+    /// An entry summarizing several others.
+    fn summary(name: String, old_size: u64, new_size: u64) -> DiffEntry {
+        DiffEntry {
+            change: None,
+            ..DiffEntry::new(name, Some(old_size), Some(new_size))
+        }
+    }
+
+    /// The name to show for this entry, with the item's old name if it was
+    /// renamed.
+    #[cfg(any(feature = "emit_text", feature = "emit_csv"))]
+    fn label(&self) -> String {
+        match self.old_name {
+            Some(ref old_name) => format!("{} → {}", old_name, self.name),
+            None => self.name.clone(),
+        }
+    }
+
+    /// The change in size as a percentage of the old size, if there was one.
+    fn delta_percent(&self) -> Option<f64> {
+        match self.old_size {
+            Some(old_size) if old_size > 0 => Some(self.delta as f64 / old_size as f64 * 100.0),
+            _ => None,
+        }
+    }
+
+    /// The change in retained size, if retained sizes were computed.
+    fn retained_delta(&self) -> Option<i64> {
+        if self.old_retained.is_none() && self.new_retained.is_none() {
+            return None;
+        }
+        Some(super::size_delta(
+            self.old_retained.unwrap_or(0),
+            self.new_retained.unwrap_or(0),
+        ))
+    }
+
+    /// Did this entry change in the given way? Renamed items count as renamed
+    /// whether or not their size changed too.
+    fn changed_by(&self, change: traits::Change) -> bool {
+        self.change == Some(change)
+            || (change == traits::Change::Renamed && self.old_name.is_some())
+    }
+}
+
+impl PartialOrd for DiffEntry {
+    fn partial_cmp(&self, rhs: &DiffEntry) -> Option<cmp::Ordering> {
+        Some(self.cmp(rhs))
+    }
+}
+
+impl Ord for DiffEntry {
+    fn cmp(&self, rhs: &DiffEntry) -> cmp::Ordering {
+        rhs.delta
+            .abs()
+            .cmp(&self.delta.abs())
+            .then(self.name.cmp(&rhs.name))
+            .then(self.old_name.cmp(&rhs.old_name))
+    }
+}
+
+/// A row of CSV output: an entry, and the group it was expanded from, if any.
+/// The `Group` column is only written when the items were grouped, and the
+/// `Explanation` column when they were explained.
+#[cfg(feature = "emit_csv")]
+struct CsvRecord<'a> {
+    grouped: bool,
+    explained: bool,
+    group: Option<&'a str>,
+    entry: &'a DiffEntry,
+}
+
+#[cfg(feature = "emit_csv")]
+impl serde::Serialize for CsvRecord<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let entry = self.entry;
+        let len = 9 + self.grouped as usize + self.explained as usize;
+        let mut state = serializer.serialize_struct("DiffEntry", len)?;
+        state.serialize_field("DeltaBytes", &format!("{:+}", entry.delta))?;
+        state.serialize_field("DeltaPercent", &entry.delta_percent())?;
+        state.serialize_field("OldSize", &entry.old_size)?;
+        state.serialize_field("NewSize", &entry.new_size)?;
+        state.serialize_field(
+            "RetainedDeltaBytes",
+            &entry.retained_delta().map(|delta| format!("{:+}", delta)),
+        )?;
+        state.serialize_field("OldRetainedSize", &entry.old_retained)?;
+        state.serialize_field("NewRetainedSize", &entry.new_retained)?;
+        state.serialize_field("Change", &entry.change.map(traits::Change::name))?;
+        if self.grouped {
+            state.serialize_field("Group", &self.group)?;
+        }
+        state.serialize_field("Item", &entry.label())?;
+        if self.explained {
+            state.serialize_field(
+                "Explanation",
+                &entry
+                    .explanation
+                    .as_ref()
+                    .map(|explanation| explanation.lines().join("; ")),
+            )?;
+        }
+        state.end()
+    }
+}
+
+impl traits::Emit for Diff {
+    #[cfg(feature = "emit_text")]
+    fn emit_text(&self, _items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+        fn or_blank<T: ToString>(value: Option<T>) -> String {
+            value.map_or_else(String::new, |value| value.to_string())
+        }
+
+        let mut header = vec![
+            (Align::Right, "Delta Bytes".into()),
+            (Align::Right, "Delta %".into()),
+            (Align::Right, "Old Bytes".into()),
+            (Align::Right, "New Bytes".into()),
+        ];
+        if self.retained {
+            header.extend(vec![
+                (Align::Right, "Retained Delta".into()),
+                (Align::Right, "Old Retained".into()),
+                (Align::Right, "New Retained".into()),
+            ]);
+        }
+        header.push((Align::Left, "Change".into()));
+        header.push((Align::Left, "Item".to_string()));
+        let mut table = Table::with_header(header);
+
+        let to_row = |entry: &DiffEntry, indent: &str| {
+            let mut row = vec![
+                format!("{:+}", entry.delta),
+                or_blank(entry.delta_percent().map(|p| format!("{:+.2}%", p))),
+                or_blank(entry.old_size),
+                or_blank(entry.new_size),
+            ];
+            if self.retained {
+                row.extend(vec![
+                    or_blank(entry.retained_delta().map(|delta| format!("{:+}", delta))),
+                    or_blank(entry.old_retained),
+                    or_blank(entry.new_retained),
+                ]);
+            }
+            row.push(or_blank(entry.change.map(traits::Change::name)));
+            row.push(format!("{}{}", indent, entry.label()));
+            row
+        };
+
+        for entry in &self.deltas {
+            let row = to_row(entry, "");
+            let columns = row.len();
+            table.add_row(row);
+
+            // Follow the entry with its explanation, if it has one.
+            if let Some(ref explanation) = entry.explanation {
+                for line in explanation.lines() {
+                    let mut row = vec![String::new(); columns - 1];
+                    row.push(format!("  {}", line));
+                    table.add_row(row);
+                }
+            }
+
+            // Follow a group with the items in it, if it was expanded.
+            for member in &entry.members {
+                table.add_row(to_row(member, "  "));
+            }
+        }
+
+        write!(dest, "{}", &table)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_json")]
+    fn emit_json(&self, _items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+        let mut arr = json::array(dest)?;
+
+        for entry in &self.deltas {
+            emit_json_entry(entry, &mut arr.object()?)?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_csv")]
+    fn emit_csv(&self, _items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+        let mut wtr = csv::Writer::from_writer(dest);
+
+        for entry in &self.deltas {
+            wtr.serialize(CsvRecord {
+                grouped: self.grouped,
+                explained: self.explained,
+                group: None,
+                entry,
+            })?;
+            for member in &entry.members {
+                wtr.serialize(CsvRecord {
+                    grouped: self.grouped,
+                    explained: self.explained,
+                    group: Some(&entry.name),
+                    entry: member,
+                })?;
+            }
+            wtr.flush()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Write out an entry, and the items in it if it is an expanded group.
+#[cfg(feature = "emit_json")]
+fn emit_json_entry(entry: &DiffEntry, obj: &mut json::Object<'_>) -> anyhow::Result<()> {
+    obj.field("delta_bytes", entry.delta as f64)?;
+    if let Some(delta_percent) = entry.delta_percent() {
+        obj.field("delta_percent", delta_percent)?;
+    }
+    if let Some(old_size) = entry.old_size {
+        obj.field("old_size", old_size)?;
+    }
+    if let Some(new_size) = entry.new_size {
+        obj.field("new_size", new_size)?;
+    }
+    if let Some(retained_delta) = entry.retained_delta() {
+        obj.field("retained_delta_bytes", retained_delta)?;
+    }
+    if let Some(old_retained) = entry.old_retained {
+        obj.field("old_retained_size", old_retained)?;
+    }
+    if let Some(new_retained) = entry.new_retained {
+        obj.field("new_retained_size", new_retained)?;
+    }
+    if let Some(change) = entry.change {
+        obj.field("change", change.name())?;
+    }
+    obj.field("name", entry.name.as_str())?;
+    if let Some(ref old_name) = entry.old_name {
+        obj.field("old_name", old_name.as_str())?;
+    }
+    if let Some(ref explanation) = entry.explanation {
+        emit_json_explanation(explanation, &mut obj.object("explanation")?)?;
+    }
+    if !entry.members.is_empty() {
+        let mut members = obj.array("members")?;
+        for member in &entry.members {
+            emit_json_entry(member, &mut members.object()?)?;
+        }
+    }
+    Ok(())
+}
+
+/// Write out the parts of an explanation that it has.
+#[cfg(feature = "emit_json")]
+fn emit_json_explanation(
+    explanation: &explain::Explanation,
+    obj: &mut json::Object<'_>,
+) -> anyhow::Result<()> {
+    if !explanation.new_callers.is_empty() {
+        let mut callers = obj.array("new_callers")?;
+        for caller in &explanation.new_callers {
+            callers.elem(caller.as_str())?;
+        }
+    }
+    if let Some(ref path) = explanation.new_path {
+        let mut arr = obj.array("new_retaining_path")?;
+        for name in path {
+            arr.elem(name.as_str())?;
+        }
+    }
+    if let Some((ref old, ref new)) = explanation.dominator_change {
+        obj.field("old_immediate_dominator", old.as_str())?;
+        obj.field("new_immediate_dominator", new.as_str())?;
+    }
+    if !explanation.newly_retained.is_empty() {
+        let mut arr = obj.array("newly_retained")?;
+        for (name, size) in &explanation.newly_retained {
+            let mut item = arr.object()?;
+            item.field("name", name.as_str())?;
+            item.field("shallow_size", *size)?;
+        }
+    }
+    if explanation.more_newly_retained > 0 {
+        obj.field(
+            "more_newly_retained",
+            explanation.more_newly_retained as u64,
+        )?;
+    }
+    Ok(())
+}
+
+/// Compute the diff between two sets of items.
+pub fn diff(
+    old_items: &mut ir::Items,
+    new_items: &mut ir::Items,
+    opts: &opt::Diff,
+) -> anyhow::Result<Box<dyn traits::Emit>> {
+    let group_by = opts.group_by();
+    categories::check_classified(old_items, opts.categories(), group_by)?;
+    let max_items = opts.max_items() as usize;
+    if opts.explain() && group_by.is_some() {
+        return Err(anyhow!(
+            "`--explain` can't be used together with `--group-by`"
+        ));
+    }
+    if opts.expand() && group_by.is_none() {
+        return Err(anyhow!(
+            "`--expand` can only be used together with `--group-by`"
+        ));
+    }
+    if opts.dominators() {
+        let conflicting = [
+            (group_by.is_some(), "--group-by"),
+            (opts.explain(), "--explain"),
+            (!opts.kinds().is_empty(), "--kind"),
+            (!opts.categories().is_empty(), "--category"),
+            (!opts.changes().is_empty(), "--change"),
+        ];
+        if let Some((_, option)) = conflicting.iter().find(|(given, _)| *given) {
+            return Err(anyhow!(
+                "`--dominators` can't be used together with `{}`",
+                option
+            ));
+        }
+    }
+    if opts.retained() || opts.explain() || opts.dominators() {
+        old_items.compute_retained_sizes();
+        new_items.compute_retained_sizes();
+    }
+    let (old_items, new_items) = (&*old_items, &*new_items);
+
+    // Match each old item with the new item it became, if any. All items are
+    // matched, so that which items match doesn't depend on what is shown.
+    let all_ids = |items: &ir::Items| {
+        items
+            .iter()
+            .map(|item| item.id())
+            .filter(|&id| id != items.meta_root())
+            .collect()
+    };
+    let all_matches = matching::match_items(
+        old_items,
+        all_ids(old_items),
+        new_items,
+        all_ids(new_items),
+        opts.exact_names(),
+    );
+
+    // Only consider the items of the given kinds and categories. An item that
+    // is only considered in one version was added or removed as far as the
+    // diff is concerned.
+    let is_considered = |items: &ir::Items, id: ir::Id| {
+        super::matches_kinds(&items[id], opts.kinds())
+            && categories::in_categories(items, id, opts.categories())
+    };
+    let matches: Vec<matching::Match> = all_matches
+        .iter()
+        .map(|&(old, new)| {
+            (
+                old.filter(|&id| is_considered(old_items, id)),
+                new.filter(|&id| is_considered(new_items, id)),
+            )
+        })
+        .filter(|&ids| ids != (None, None))
+        .collect();
+
+    // If arguments were given to the command, we should filter out items that
+    // do not match any of the given names or expressions. An item matches if
+    // either its old or its new version does, and an item's plain name matches
+    // all of the items sharing it.
+    let matches: Vec<matching::Match> = if !opts.items().is_empty() {
+        let regexps = if opts.using_regexps() {
+            Some(regex::RegexSet::new(opts.items())?)
+        } else {
+            None
+        };
+        let item_names = opts.items().iter().collect::<HashSet<_>>();
+        let is_match = |items: &ir::Items, id: ir::Id| {
+            let name = items.display_name(id);
+            match regexps {
+                Some(ref regexps) => regexps.is_match(&name),
+                None => {
+                    item_names.contains(&name.to_string())
+                        || item_names.contains(&items[id].name().to_string())
+                }
+            }
+        };
+        matches
+            .into_iter()
+            .filter(|&(old, new)| {
+                old.is_some_and(|id| is_match(old_items, id))
+                    || new.is_some_and(|id| is_match(new_items, id))
+            })
+            .collect()
+    } else {
+        matches
+    };
+
+    // Show the aligned dominator trees, starting from the given items if there
+    // are any.
+    if opts.dominators() {
+        let roots = if opts.items().is_empty() {
+            None
+        } else {
+            Some(&matches[..])
+        };
+        let diff = dominators::diff(old_items, new_items, &all_matches, roots, max_items);
+        return Ok(Box::new(diff) as Box<_>);
+    }
+
+    let size = |items: &ir::Items, id: ir::Id| items[id].size();
+    let retained = |items: &ir::Items, id: ir::Id| items.retained_size(id);
+
+    // Create a `DiffEntry` for each changed item, and keep the entries that
+    // changed in the ways asked for.
+    let mut item_deltas: Vec<DiffEntry> = matches
+        .iter()
+        .filter_map(|&(old, new)| {
+            let mut entry = match (old, new) {
+                (Some(old), Some(new)) => {
+                    let old_name = old_items.display_name(old);
+                    let name = new_items.display_name(new);
+                    let mut entry = DiffEntry::new(
+                        name.to_string(),
+                        Some(size(old_items, old)),
+                        Some(size(new_items, new)),
+                    );
+                    // Only report items that changed size, or that were
+                    // renamed rather than just rebuilt.
+                    let (old_normalized, normalized) =
+                        (matching::normalize(&old_name), matching::normalize(&name));
+                    let renamed = old_normalized != normalized
+                        && !(matching::is_placeholder(&old_normalized)
+                            && matching::is_placeholder(&normalized));
+                    if entry.delta == 0 && !renamed {
+                        return None;
+                    }
+                    if old_normalized != normalized {
+                        entry.old_name = Some(old_name.into_owned());
+                    }
+                    entry
+                }
+                (Some(old), None) => DiffEntry::new(
+                    old_items.display_name(old).into_owned(),
+                    Some(size(old_items, old)),
+                    None,
+                ),
+                (None, Some(new)) => DiffEntry::new(
+                    new_items.display_name(new).into_owned(),
+                    None,
+                    Some(size(new_items, new)),
+                ),
+                (None, None) => return None,
+            };
+            if entry.delta == 0 && entry.old_name.is_none() {
+                return None;
+            }
+            if opts.retained() {
+                entry.old_retained = old.map(|id| retained(old_items, id));
+                entry.new_retained = new.map(|id| retained(new_items, id));
+            }
+            entry.ids = (old, new);
+            Some(entry)
+        })
+        .collect();
+    if !opts.changes().is_empty() {
+        item_deltas.retain(|entry| {
+            opts.changes()
+                .iter()
+                .any(|&change| entry.changed_by(change))
+        });
+    }
+
+    // Sum the old and new sizes of the given entries.
+    let sum_sizes = |entries: &[DiffEntry]| {
+        entries.iter().fold((0u64, 0u64), |(old, new), entry| {
+            (
+                old.saturating_add(entry.old_size.unwrap_or(0)),
+                new.saturating_add(entry.new_size.unwrap_or(0)),
+            )
+        })
+    };
+
+    // When grouping, create a `DiffEntry` for each changed group instead. The
+    // old size of each item counts against its old group and the new size
+    // towards its new one. Then, keep the groups that changed in the ways
+    // asked for, and sort the collection.
+    let mut deltas: Vec<DiffEntry> = match group_by {
+        Some(group_by) => {
+            let mut groups: BTreeMap<String, (BTreeSet<ir::Id>, BTreeSet<ir::Id>)> =
+                BTreeMap::new();
+            for &(old, new) in &matches {
+                if let Some(old) = old {
+                    let group = super::groups::group_of(old_items, old, group_by);
+                    groups.entry(group).or_default().0.insert(old);
+                }
+                if let Some(new) = new {
+                    let group = super::groups::group_of(new_items, new, group_by);
+                    groups.entry(group).or_default().1.insert(new);
+                }
+            }
+            // The total size of a set of items, or its retained size, or none
+            // if the set is empty.
+            let sum = |items: &ir::Items, ids: &BTreeSet<ir::Id>| {
+                if ids.is_empty() {
+                    None
+                } else {
+                    Some(super::total_size(ids.iter().map(|&id| size(items, id))))
+                }
+            };
+            let sum_retained = |items: &ir::Items, ids: &BTreeSet<ir::Id>| {
+                if ids.is_empty() {
+                    None
+                } else {
+                    Some(super::retained_size_of(items, ids))
+                }
+            };
+            let mut groups: Vec<DiffEntry> = groups
+                .into_iter()
+                .map(|(name, (old_ids, new_ids))| {
+                    let mut entry =
+                        DiffEntry::new(name, sum(old_items, &old_ids), sum(new_items, &new_ids));
+                    if opts.retained() {
+                        entry.old_retained = sum_retained(old_items, &old_ids);
+                        entry.new_retained = sum_retained(new_items, &new_ids);
+                    }
+                    // An item that moved between groups is a member of both.
+                    if opts.expand() {
+                        let mut members: Vec<DiffEntry> = item_deltas
+                            .iter()
+                            .filter(|member| {
+                                member.ids.0.is_some_and(|id| old_ids.contains(&id))
+                                    || member.ids.1.is_some_and(|id| new_ids.contains(&id))
+                            })
+                            .cloned()
+                            .collect();
+                        members.sort();
+                        let rem_cnt = members.len().saturating_sub(max_items);
+                        if rem_cnt > 0 {
+                            let (rem_old, rem_new) = sum_sizes(&members[members.len() - rem_cnt..]);
+                            members.truncate(max_items);
+                            members.push(DiffEntry::summary(
+                                format!("... and {} more.", rem_cnt),
+                                rem_old,
+                                rem_new,
+                            ));
+                        }
+                        entry.members = members;
+                    }
+                    entry
+                })
+                .filter(|entry| entry.delta != 0)
+                .collect();
+            if !opts.changes().is_empty() {
+                groups.retain(|entry| {
+                    opts.changes()
+                        .iter()
+                        .any(|&change| entry.changed_by(change))
+                });
+            }
+            groups
+        }
+        None => item_deltas,
+    };
+    deltas.sort();
+
+    // Create an entry to summarize the diff rows that will be truncated.
+    let rem_cnt = deltas.len().saturating_sub(max_items);
+    let (rem_old, rem_new) = sum_sizes(&deltas[deltas.len() - rem_cnt..]);
+    let remaining = DiffEntry::summary(format!("... and {} more.", rem_cnt), rem_old, rem_new);
+
+    // Create a `DiffEntry` representing the net change, and total row count.
+    // If specifying arguments were not given, calculate the total net changes,
+    // otherwise find the total values only for items in the the deltas collection.
+    let (total_old, total_new) = if opts.items().is_empty()
+        && opts.kinds().is_empty()
+        && opts.categories().is_empty()
+        && opts.changes().is_empty()
+    {
+        (old_items.size(), new_items.size())
+    } else {
+        sum_sizes(&deltas)
+    };
+    let total = DiffEntry::summary(
+        format!("Σ [{} Total Rows]", deltas.len()),
+        total_old,
+        total_new,
+    );
+
+    // Now that the 'remaining' and 'total' summary entries have been created,
+    // truncate the vector of deltas before we box up the result, and push
+    // the remaining and total rows to the deltas vector.
+    deltas.truncate(max_items);
+
+    // Explain why each of the items that are shown appeared, or why its
+    // retained size grew.
+    if opts.explain() {
+        let versions = explain::Versions::new(old_items, new_items, &all_matches);
+        for entry in &mut deltas {
+            let (old, new) = match entry.ids {
+                (old, Some(new)) => (old, new),
+                (_, None) => continue,
+            };
+            let grew = old.is_none_or(|old| retained(new_items, new) > retained(old_items, old));
+            if grew {
+                entry.explanation = Some(versions.explain(old, new))
+                    .filter(|explanation| *explanation != explain::Explanation::default());
+            }
+        }
+    }
+    if rem_cnt > 0 {
+        deltas.push(remaining);
+    }
+    deltas.push(total);
+
+    // Return the results so that they can be emitted.
+    let diff = Diff {
+        deltas,
+        retained: opts.retained(),
+        grouped: group_by.is_some(),
+        explained: opts.explain(),
+    };
+    Ok(Box::new(diff) as Box<_>)
+}
@@ -1,18 +1,31 @@
-use std::collections::BTreeMap;
+use std::cmp;
 use std::io;
+#[cfg(feature = "emit_json")]
+use std::mem;
 
+#[cfg(feature = "emit_csv")]
 use csv;
+#[cfg(feature = "emit_csv")]
 use serde_derive::Serialize;
 
 use twiggy_ir as ir;
-use twiggy_opt as opt;
 use twiggy_traits as traits;
 
 use super::UnreachableItemsSummary;
 use crate::analyses::dominators::DominatorTree;
+#[cfg(feature = "emit_json")]
 use crate::formats::json;
+#[cfg(feature = "emit_text")]
 use crate::formats::table::{Align, Table};
 
+/// The items that the given item immediately dominates, largest retained size
+/// first.
+fn sorted_children(items: &ir::Items, id: ir::Id) -> Vec<ir::Id> {
+    let mut children: Vec<_> = items.immediately_dominated(id).collect();
+    children.sort_by_key(|id| cmp::Reverse(items.retained_size(*id)));
+    children
+}
+
 impl traits::Emit for DominatorTree {
     #[cfg(feature = "emit_text")]
     fn emit_text(&self, items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
@@ -23,56 +36,32 @@ impl traits::Emit for DominatorTree {
         ]);
 
         let opts = &self.opts;
-        let mut row = 0 as u32;
-
-        fn recursive_add_rows(
-            table: &mut Table,
-            items: &ir::Items,
-            dominator_tree: &BTreeMap<ir::Id, Vec<ir::Id>>,
-            depth: u32,
-            mut row: &mut u32,
-            opts: &opt::Dominators,
-            id: ir::Id,
-        ) {
-            assert_eq!(id == items.meta_root(), depth == 0);
-
-            if *row > opts.max_rows() || depth > opts.max_depth() {
-                return;
-            }
+        let mut row = 0_u32;
+
+        // Walk each subtree in pre order with an explicit stack rather than by
+        // recursion, since dominator trees can be far deeper than the call
+        // stack. Every row after a subtree's first counts towards the maximum,
+        // even if it is too deep to show.
+        for &start in &self.items {
+            let start_depth = if start == items.meta_root() { 0 } else { 1 };
+            let mut stack = vec![(start, start_depth)];
+            while let Some((id, depth)) = stack.pop() {
+                assert_eq!(id == items.meta_root(), depth == 0);
+                if depth > start_depth {
+                    row += 1;
+                }
 
-            if depth > 0 {
-                add_text_item(items, depth, id, table);
-            }
+                if row > opts.max_rows() || depth > opts.max_depth() {
+                    continue;
+                }
 
-            if let Some(children) = dominator_tree.get(&id) {
-                let mut children = children.to_vec();
-                children.sort_by(|a, b| items.retained_size(*b).cmp(&items.retained_size(*a)));
-                for child in children {
-                    *row += 1;
-                    recursive_add_rows(
-                        table,
-                        items,
-                        dominator_tree,
-                        depth + 1,
-                        &mut row,
-                        &opts,
-                        child,
-                    );
+                if depth > 0 {
+                    add_text_item(items, depth, id, &mut table);
                 }
-            }
-        }
 
-        for id in &self.items {
-            let start_depth = if *id == items.meta_root() { 0 } else { 1 };
-            recursive_add_rows(
-                &mut table,
-                items,
-                &self.tree,
-                start_depth,
-                &mut row,
-                &opts,
-                *id,
-            );
+                let children = sorted_children(items, id).into_iter().rev();
+                stack.extend(children.map(|child| (child, depth + 1)));
+            }
         }
 
         if let Some(UnreachableItemsSummary {
@@ -94,23 +83,48 @@ impl traits::Emit for DominatorTree {
 
     #[cfg(feature = "emit_json")]
     fn emit_json(&self, items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
-        fn recursive_add_children(
+        // Add the item's fields to the object, along with the items it
+        // dominates. Those are written with an explicit stack rather than by
+        // recursion, since dominator trees can be far deeper than the call
+        // stack. Each level of the stack holds the children left to write at
+        // that depth, and whether any have been written yet.
+        fn add_subtree(
             items: &ir::Items,
-            opts: &opt::Dominators,
-            dominator_tree: &BTreeMap<ir::Id, Vec<ir::Id>>,
             id: ir::Id,
             obj: &mut json::Object,
         ) -> anyhow::Result<()> {
             add_json_item(items, id, obj)?;
 
-            if let Some(children) = dominator_tree.get(&id) {
-                let mut children = children.to_vec();
-                children.sort_by(|a, b| items.retained_size(*b).cmp(&items.retained_size(*a)));
+            let children = sorted_children(items, id);
+            if children.is_empty() {
+                return Ok(());
+            }
 
-                let mut arr = obj.array("children")?;
-                for child in children {
-                    let mut obj = arr.object()?;
-                    recursive_add_children(items, opts, dominator_tree, child, &mut obj)?;
+            let w = obj.raw("children")?;
+            write!(w, "[")?;
+            let mut stack = vec![(children.into_iter(), false)];
+            while let Some((next, any_written)) = stack.last_mut() {
+                match next.next() {
+                    Some(child) => {
+                        if mem::replace(any_written, true) {
+                            write!(w, ",")?;
+                        }
+                        let mut obj = json::object(&mut *w)?;
+                        add_json_item(items, child, &mut obj)?;
+                        let children = sorted_children(items, child);
+                        if !children.is_empty() {
+                            write!(obj.raw("children")?, "[")?;
+                            obj.leave_open();
+                            stack.push((children.into_iter(), false));
+                        }
+                    }
+                    None => {
+                        stack.pop();
+                        write!(w, "]")?;
+                        if !stack.is_empty() {
+                            write!(w, "}}")?;
+                        }
+                    }
                 }
             }
 
@@ -123,7 +137,7 @@ impl traits::Emit for DominatorTree {
             let mut arr = obj.array("items")?;
             for curr_id in &self.items {
                 let mut item = arr.object()?;
-                recursive_add_children(items, &self.opts, &self.tree, *curr_id, &mut item)?;
+                add_subtree(items, *curr_id, &mut item)?;
             }
         }
 
@@ -146,26 +160,15 @@ impl traits::Emit for DominatorTree {
 
     #[cfg(feature = "emit_csv")]
     fn emit_csv(&self, items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
-        fn recursive_add_children(
-            items: &ir::Items,
-            opts: &opt::Dominators,
-            dominator_tree: &BTreeMap<ir::Id, Vec<ir::Id>>,
-            id: ir::Id,
-            wtr: &mut csv::Writer<&mut dyn io::Write>,
-        ) -> anyhow::Result<()> {
-            add_csv_item(items, id, wtr)?;
-            if let Some(children) = dominator_tree.get(&id) {
-                let mut children = children.to_vec();
-                children.sort_by(|a, b| items.retained_size(*b).cmp(&items.retained_size(*a)));
-                for child in children {
-                    recursive_add_children(items, opts, dominator_tree, child, wtr)?;
-                }
-            }
-            Ok(())
-        }
-
+        // Walk the tree in pre order with an explicit stack rather than by
+        // recursion, since dominator trees can be far deeper than the call
+        // stack.
         let mut wtr = csv::Writer::from_writer(dest);
-        recursive_add_children(items, &self.opts, &self.tree, items.meta_root(), &mut wtr)?;
+        let mut stack = vec![items.meta_root()];
+        while let Some(id) = stack.pop() {
+            add_csv_item(items, id, &mut wtr)?;
+            stack.extend(sorted_children(items, id).into_iter().rev());
+        }
 
         if let Some(UnreachableItemsSummary {
             count,
@@ -192,19 +195,18 @@ impl traits::Emit for DominatorTree {
 
 #[cfg(feature = "emit_text")]
 fn add_text_item(items: &ir::Items, depth: u32, id: ir::Id, table: &mut Table) {
-    let item = &items[id];
-
     let size = items.retained_size(id);
-    let size_percent = (f64::from(size)) / (f64::from(items.size())) * 100.0;
+    let size_percent = crate::analyses::percent(size, items.size());
 
-    let mut label = String::with_capacity(depth as usize * 4 + item.name().len() + "⤷ ".len());
+    let name = items.display_name(id);
+    let mut label = String::with_capacity(depth as usize * 4 + name.len() + "⤷ ".len());
     for _ in 2..depth {
         label.push_str("    ");
     }
     if depth != 1 {
         label.push_str("  ⤷ ");
     }
-    label.push_str(item.name());
+    label.push_str(&name);
 
     table.add_row(vec![
         size.to_string(),
@@ -217,15 +219,15 @@ fn add_text_item(items: &ir::Items, depth: u32, id: ir::Id, table: &mut Table) {
 fn add_json_item(items: &ir::Items, id: ir::Id, obj: &mut json::Object) -> anyhow::Result<()> {
     let item = &items[id];
 
-    obj.field("name", item.name())?;
+    obj.field("name", items.display_name(id).as_ref())?;
 
     let shallow_size = item.size();
-    let shallow_size_percent = f64::from(shallow_size) / f64::from(items.size()) * 100.0;
+    let shallow_size_percent = crate::analyses::percent(shallow_size, items.size());
     obj.field("shallow_size", shallow_size)?;
     obj.field("shallow_size_percent", shallow_size_percent)?;
 
     let retained_size = items.retained_size(id);
-    let retained_size_percent = f64::from(retained_size) / f64::from(items.size()) * 100.0;
+    let retained_size_percent = crate::analyses::percent(retained_size, items.size());
     obj.field("retained_size", retained_size)?;
     obj.field("retained_size_percent", retained_size_percent)?;
     Ok(())
@@ -237,9 +239,9 @@ fn add_json_item(items: &ir::Items, id: ir::Id, obj: &mut json::Object) -> anyho
 struct CsvRecord {
     pub id: Option<u64>,
     pub name: String,
-    pub shallow_size: u32,
+    pub shallow_size: u64,
     pub shallow_size_percent: f64,
-    pub retained_size: u32,
+    pub retained_size: u64,
     pub retained_size_percent: f64,
     pub immediate_dominator: Option<u64>,
 }
@@ -253,21 +255,17 @@ fn add_csv_item(
     let item = &items[id];
     let (shallow_size, shallow_size_percent) = (
         item.size(),
-        f64::from(item.size()) / f64::from(items.size()) * 100.0,
+        crate::analyses::percent(item.size(), items.size()),
     );
     let (retained_size, retained_size_percent) = (
         items.retained_size(id),
-        f64::from(items.retained_size(id)) / f64::from(items.size()) * 100.0,
+        crate::analyses::percent(items.retained_size(id), items.size()),
     );
-    let idom = if let Some(idom) = items.immediate_dominators().get(&id) {
-        idom.serializable()
-    } else {
-        id.serializable()
-    };
+    let idom = items.immediate_dominator(id).unwrap_or(id).serializable();
 
     let rc = CsvRecord {
         id: Some(item.id().serializable()),
-        name: item.name().to_string(),
+        name: items.display_name(id).into_owned(),
         shallow_size,
         shallow_size_percent,
         retained_size,
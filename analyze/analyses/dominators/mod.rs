@@ -1,4 +1,4 @@
-use std::collections::BTreeMap;
+use std::cmp;
 
 use regex;
 
@@ -6,20 +6,21 @@ use twiggy_ir as ir;
 use twiggy_opt as opt;
 use twiggy_traits as traits;
 
-use crate::analyses::garbage;
+use crate::analyses::{categories, garbage};
 
 mod emit;
 
 struct DominatorTree {
-    tree: BTreeMap<ir::Id, Vec<ir::Id>>,
     items: Vec<ir::Id>,
+    // Only the text output is limited to a maximum depth and number of rows.
+    #[cfg_attr(not(feature = "emit_text"), allow(dead_code))]
     opts: opt::Dominators,
     unreachable_items_summary: Option<UnreachableItemsSummary>,
 }
 
 struct UnreachableItemsSummary {
     count: usize,
-    size: u32,
+    size: u64,
     size_percent: f64,
 }
 
@@ -28,33 +29,51 @@ pub fn dominators(
     items: &mut ir::Items,
     opts: &opt::Dominators,
 ) -> anyhow::Result<Box<dyn traits::Emit>> {
+    categories::check_classified(items, opts.categories(), None)?;
+    super::filter_edges(items, opts.edge_kinds(), opts.excluded_edge_kinds());
     items.compute_dominator_tree();
-    items.compute_dominators();
     items.compute_retained_sizes();
     items.compute_predecessors();
 
     let arguments = opts.items();
-    let dominator_items = if arguments.is_empty() {
+    let kinds = opts.kinds();
+    let categories = opts.categories();
+    let mut dominator_items = if arguments.is_empty() && kinds.is_empty() && categories.is_empty() {
         vec![items.meta_root()]
+    } else if arguments.is_empty() {
+        // Show the subtrees of every item of the requested kinds and
+        // categories.
+        let mut sorted_items: Vec<_> = items
+            .iter()
+            .filter(|item| item.id() != items.meta_root())
+            .map(|item| item.id())
+            .collect();
+        sorted_items.sort_by_key(|&id| cmp::Reverse(items.retained_size(id)));
+        sorted_items
     } else if opts.using_regexps() {
         let regexps = regex::RegexSet::new(arguments)?;
         let mut sorted_items: Vec<_> = items
             .iter()
-            .filter(|item| regexps.is_match(&item.name()))
+            .filter(|item| regexps.is_match(item.name()))
             .map(|item| item.id())
             .collect();
-        sorted_items.sort_by_key(|id| -i64::from(items.retained_size(*id)));
+        sorted_items.sort_by_key(|&id| cmp::Reverse(items.retained_size(id)));
         sorted_items
     } else {
         arguments
             .iter()
-            .filter_map(|name| items.get_item_by_name(name))
+            .flat_map(|name| items.get_items_by_name(name))
             .map(|item| item.id())
             .collect()
     };
+    if !kinds.is_empty() {
+        dominator_items.retain(|&id| super::matches_kinds(&items[id], kinds));
+    }
+    if !categories.is_empty() {
+        dominator_items.retain(|&id| categories::in_categories(items, id, categories));
+    }
 
     let tree = DominatorTree {
-        tree: items.dominator_tree().clone(),
         items: dominator_items,
         opts: opts.clone(),
         unreachable_items_summary: summarize_unreachable_items(items, opts),
@@ -67,14 +86,18 @@ fn summarize_unreachable_items(
     items: &mut ir::Items,
     opts: &opt::Dominators,
 ) -> Option<UnreachableItemsSummary> {
-    let (size, count) = garbage::get_unreachable_items(&items)
+    let (size, count) = garbage::get_unreachable_items(items)
         .map(|item| item.size())
-        .fold((0, 0), |(s, c), curr| (s + curr, c + 1));
-    if opts.items().is_empty() && size > 0 {
+        .fold((0, 0), |(s, c), curr| (u64::saturating_add(s, curr), c + 1));
+    if opts.items().is_empty()
+        && opts.kinds().is_empty()
+        && opts.categories().is_empty()
+        && size > 0
+    {
         Some(UnreachableItemsSummary {
             count,
             size,
-            size_percent: (f64::from(size)) / (f64::from(items.size())) * 100.0,
+            size_percent: super::percent(size, items.size()),
         })
     } else {
         None
@@ -1,8 +1,10 @@
+use std::cmp;
 use std::collections::BTreeSet;
 use std::io;
 
 use petgraph::visit::Walker;
 
+use crate::analyses::{categories, groups};
 use crate::formats::json;
 use crate::formats::table::{Align, Table};
 use twiggy_ir as ir;
@@ -28,21 +30,22 @@ impl traits::Emit for Garbage {
 
         for item in items_iter.clone().take(self.limit) {
             let size = item.size();
-            let size_percent = (f64::from(size)) / (f64::from(items.size())) * 100.0;
+            let size_percent = super::percent(size, items.size());
             table.add_row(vec![
                 size.to_string(),
                 format!("{:.2}%", size_percent),
-                item.name().to_string(),
+                items.display_name(item.id()).into_owned(),
             ]);
         }
 
         match items_iter
             .clone()
             .skip(self.limit)
-            .fold((0, 0), |(size, cnt), item| (size + item.size(), cnt + 1))
-        {
+            .fold((0, 0), |(size, cnt), item| {
+                (u64::saturating_add(size, item.size()), cnt + 1)
+            }) {
             (size, cnt) if cnt > 0 => {
-                let size_percent = f64::from(size) / f64::from(items.size()) * 100.0;
+                let size_percent = super::percent(size, items.size());
                 table.add_row(vec![
                     size.to_string(),
                     format!("{:.2}%", size_percent),
@@ -52,8 +55,8 @@ impl traits::Emit for Garbage {
             _ => {}
         }
 
-        let total_size: u32 = items_iter.map(|item| item.size()).sum();
-        let total_percent = (f64::from(total_size)) / (f64::from(items.size())) * 100.0;
+        let total_size = super::total_size(items_iter.map(|item| item.size()));
+        let total_percent = super::percent(total_size, items.size());
         table.add_row(vec![
             total_size.to_string(),
             format!("{:.2}%", total_percent),
@@ -61,8 +64,9 @@ impl traits::Emit for Garbage {
         ]);
 
         if !self.data_segments.is_empty() {
-            let total_size: u32 = self.data_segments.iter().map(|&id| items[id].size()).sum();
-            let size_percent = f64::from(total_size) / f64::from(items.size()) * 100.0;
+            let total_size =
+                super::total_size(self.data_segments.iter().map(|&id| items[id].size()));
+            let size_percent = super::percent(total_size, items.size());
             table.add_row(vec![
                 total_size.to_string(),
                 format!("{:.2}%", size_percent),
@@ -85,10 +89,10 @@ impl traits::Emit for Garbage {
             let item = &items[id];
 
             let mut obj = arr.object()?;
-            obj.field("name", item.name())?;
+            obj.field("name", items.display_name(id).as_ref())?;
 
             let size = item.size();
-            let size_percent = (f64::from(size)) / (f64::from(items.size())) * 100.0;
+            let size_percent = super::percent(size, items.size());
             obj.field("bytes", size)?;
             obj.field("size_percent", size_percent)?;
         }
@@ -98,10 +102,12 @@ impl traits::Emit for Garbage {
             .iter()
             .skip(self.limit)
             .map(|id| &items[*id])
-            .fold((0, 0), |(size, cnt), item| (size + item.size(), cnt + 1));
+            .fold((0, 0), |(size, cnt), item| {
+                (u64::saturating_add(size, item.size()), cnt + 1)
+            });
         if total_cnt > 0 {
             let name = format!("... and {} more", total_cnt);
-            let total_size_percent = (f64::from(total_size)) / (f64::from(items.size())) * 100.0;
+            let total_size_percent = super::percent(total_size, items.size());
             let mut obj = arr.object()?;
             obj.field("name", name.as_str())?;
             obj.field("bytes", total_size)?;
@@ -111,8 +117,8 @@ impl traits::Emit for Garbage {
         // Scoping the borrow of `arr` so we can get another object in the next block
         {
             let total_name = format!("Σ [{} Total Rows]", self.items.len());
-            let total_size: u32 = self.items.iter().map(|&id| items[id].size()).sum();
-            let total_size_percent = (f64::from(total_size)) / (f64::from(items.size())) * 100.0;
+            let total_size = super::total_size(self.items.iter().map(|&id| items[id].size()));
+            let total_size_percent = super::percent(total_size, items.size());
             let mut obj = arr.object()?;
             obj.field("name", total_name.as_str())?;
             obj.field("bytes", total_size)?;
@@ -124,8 +130,8 @@ impl traits::Emit for Garbage {
                 "{} potential false-positive data segments",
                 self.data_segments.len()
             );
-            let size: u32 = self.data_segments.iter().map(|&id| items[id].size()).sum();
-            let size_percent = f64::from(size) / f64::from(items.size()) * 100.0;
+            let size = super::total_size(self.data_segments.iter().map(|&id| items[id].size()));
+            let size_percent = super::percent(size, items.size());
 
             let mut obj = arr.object()?;
             obj.field("name", name.as_str())?;
@@ -143,9 +149,21 @@ impl traits::Emit for Garbage {
 }
 
 /// Find items that are not transitively referenced by any exports or public functions.
-pub fn garbage(items: &ir::Items, opts: &opt::Garbage) -> anyhow::Result<Box<dyn traits::Emit>> {
-    let mut unreachable_items = get_unreachable_items(&items).collect::<Vec<_>>();
-    unreachable_items.sort_by(|a, b| b.size().cmp(&a.size()));
+pub fn garbage(
+    items: &mut ir::Items,
+    opts: &opt::Garbage,
+) -> anyhow::Result<Box<dyn traits::Emit>> {
+    categories::check_classified(items, opts.categories(), opts.group_by())?;
+    super::filter_edges(items, opts.edge_kinds(), opts.excluded_edge_kinds());
+    if opts.group_by().is_some() {
+        items.compute_retained_sizes();
+    }
+
+    let mut unreachable_items = get_unreachable_items(items)
+        .filter(|item| super::matches_kinds(item, opts.kinds()))
+        .filter(|item| categories::in_categories(items, item.id(), opts.categories()))
+        .collect::<Vec<_>>();
+    unreachable_items.sort_by_key(|item| cmp::Reverse(item.size()));
 
     // Split the items into two categories if necessary
     let (data_segments, items_non_data) = if opts.show_data_segments() {
@@ -168,6 +186,12 @@ pub fn garbage(items: &ir::Items, opts: &opt::Garbage) -> anyhow::Result<Box<dyn
         )
     };
 
+    if let Some(group_by) = opts.group_by() {
+        let max_groups = opts.max_items() as usize;
+        let groups = groups::group(items, items_non_data, group_by, false, max_groups);
+        return Ok(Box::new(groups) as Box<_>);
+    }
+
     let garbage_items = Garbage {
         items: items_non_data,
         data_segments,
@@ -0,0 +1,250 @@
+//! Grouping items by their category, crate, generic function, section or
+//! kind.
+
+use std::cmp;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+
+#[cfg(feature = "emit_csv")]
+use serde::{self, ser::SerializeStruct};
+
+use crate::analyses::{categories, crates};
+#[cfg(feature = "emit_json")]
+use crate::formats::json;
+#[cfg(feature = "emit_text")]
+use crate::formats::table::{Align, Table};
+use twiggy_ir as ir;
+use twiggy_traits as traits;
+
+/// The name of the group of the given kind that an item belongs to.
+pub(crate) fn group_of(items: &ir::Items, id: ir::Id, group_by: traits::GroupBy) -> String {
+    let item = &items[id];
+    match group_by {
+        traits::GroupBy::Category => categories::category(items, id).to_string(),
+        traits::GroupBy::Crate => crates::crate_of(item),
+        // Functions that aren't generic, and other items, are grouped by kind.
+        traits::GroupBy::Generic => match item.monomorphization_of() {
+            Some(generic) => generic.to_string(),
+            None => format!("[{}]", item.kind().name()),
+        },
+        traits::GroupBy::Section => match item.section() {
+            Some(section) => section.to_string(),
+            None => "[no section]".to_string(),
+        },
+        traits::GroupBy::Kind => item.kind().name().to_string(),
+    }
+}
+
+/// The heading of the column of group names, e.g. `Category`.
+#[cfg(any(feature = "emit_text", feature = "emit_csv"))]
+fn heading(group_by: traits::GroupBy) -> &'static str {
+    match group_by {
+        traits::GroupBy::Category => "Category",
+        traits::GroupBy::Crate => "Crate",
+        traits::GroupBy::Generic => "Generic",
+        traits::GroupBy::Section => "Section",
+        traits::GroupBy::Kind => "Kind",
+    }
+}
+
+/// The plural of the heading, e.g. `Categories`.
+fn plural(group_by: traits::GroupBy) -> &'static str {
+    match group_by {
+        traits::GroupBy::Category => "Categories",
+        traits::GroupBy::Crate => "Crates",
+        traits::GroupBy::Generic => "Generics",
+        traits::GroupBy::Section => "Sections",
+        traits::GroupBy::Kind => "Kinds",
+    }
+}
+
+/// The sizes of the items in each group.
+#[derive(Debug)]
+pub(crate) struct Groups {
+    group_by: traits::GroupBy,
+    groups: Vec<GroupEntry>,
+    // Only the text and CSV output have a row for the total.
+    #[cfg_attr(
+        not(any(feature = "emit_text", feature = "emit_csv")),
+        allow(dead_code)
+    )]
+    total: GroupEntry,
+}
+
+/// A group, or a summary of several, and the sizes of its items.
+#[derive(Debug)]
+struct GroupEntry {
+    name: String,
+    count: usize,
+    shallow: u64,
+    retained: u64,
+}
+
+impl GroupEntry {
+    fn new(items: &ir::Items, name: String, ids: &BTreeSet<ir::Id>) -> GroupEntry {
+        GroupEntry {
+            name,
+            count: ids.len(),
+            shallow: super::total_size(ids.iter().map(|&id| items[id].size())),
+            retained: super::retained_size_of(items, ids),
+        }
+    }
+}
+
+/// Group the given items, sorted by their shallow size or by their retained
+/// size, keeping the `max_groups` largest groups and summarizing the rest.
+/// Retained sizes must already be computed.
+pub(crate) fn group(
+    items: &ir::Items,
+    ids: impl IntoIterator<Item = ir::Id>,
+    group_by: traits::GroupBy,
+    retained: bool,
+    max_groups: usize,
+) -> Groups {
+    let mut by_group: BTreeMap<String, BTreeSet<ir::Id>> = BTreeMap::new();
+    for id in ids {
+        by_group
+            .entry(group_of(items, id, group_by))
+            .or_default()
+            .insert(id);
+    }
+
+    let all: BTreeSet<ir::Id> = by_group.values().flatten().copied().collect();
+    let total = GroupEntry::new(
+        items,
+        format!("Σ [{} Total {}]", by_group.len(), plural(group_by)),
+        &all,
+    );
+
+    let mut groups: Vec<(GroupEntry, BTreeSet<ir::Id>)> = by_group
+        .into_iter()
+        .map(|(name, ids)| (GroupEntry::new(items, name, &ids), ids))
+        .collect();
+    groups.sort_by_key(|(entry, _)| {
+        let sizes = if retained {
+            (entry.retained, entry.shallow)
+        } else {
+            (entry.shallow, entry.retained)
+        };
+        cmp::Reverse(sizes)
+    });
+
+    let remaining: BTreeSet<ir::Id> = groups
+        .iter()
+        .skip(max_groups)
+        .flat_map(|(_, ids)| ids)
+        .copied()
+        .collect();
+    let remaining_count = groups.len().saturating_sub(max_groups);
+    groups.truncate(max_groups);
+
+    let mut groups: Vec<GroupEntry> = groups.into_iter().map(|(entry, _)| entry).collect();
+    if remaining_count > 0 {
+        groups.push(GroupEntry::new(
+            items,
+            format!("... and {} more.", remaining_count),
+            &remaining,
+        ));
+    }
+
+    Groups {
+        group_by,
+        groups,
+        total,
+    }
+}
+
+/// A row of CSV output, with the group's name under its heading.
+#[cfg(feature = "emit_csv")]
+struct CsvRecord<'a> {
+    group_by: traits::GroupBy,
+    entry: &'a GroupEntry,
+    total_size: u64,
+}
+
+#[cfg(feature = "emit_csv")]
+impl serde::Serialize for CsvRecord<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let entry = self.entry;
+        let mut state = serializer.serialize_struct("GroupEntry", 6)?;
+        state.serialize_field(heading(self.group_by), &entry.name)?;
+        state.serialize_field("Items", &entry.count)?;
+        state.serialize_field("ShallowSize", &entry.shallow)?;
+        state.serialize_field(
+            "ShallowSizePercent",
+            &super::percent(entry.shallow, self.total_size),
+        )?;
+        state.serialize_field("RetainedSize", &entry.retained)?;
+        state.serialize_field(
+            "RetainedSizePercent",
+            &super::percent(entry.retained, self.total_size),
+        )?;
+        state.end()
+    }
+}
+
+impl traits::Emit for Groups {
+    #[cfg(feature = "emit_text")]
+    fn emit_text(&self, items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+        let mut table = Table::with_header(vec![
+            (Align::Right, "Items".to_string()),
+            (Align::Right, "Shallow Bytes".to_string()),
+            (Align::Right, "Shallow %".to_string()),
+            (Align::Right, "Retained Bytes".to_string()),
+            (Align::Right, "Retained %".to_string()),
+            (Align::Left, heading(self.group_by).to_string()),
+        ]);
+        for entry in self.groups.iter().chain(Some(&self.total)) {
+            table.add_row(vec![
+                entry.count.to_string(),
+                entry.shallow.to_string(),
+                format!("{:.2}%", super::percent(entry.shallow, items.size())),
+                entry.retained.to_string(),
+                format!("{:.2}%", super::percent(entry.retained, items.size())),
+                entry.name.clone(),
+            ]);
+        }
+
+        write!(dest, "{}", &table)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_json")]
+    fn emit_json(&self, items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+        let mut arr = json::array(dest)?;
+        for entry in &self.groups {
+            let mut obj = arr.object()?;
+            obj.field(self.group_by.name(), entry.name.as_str())?;
+            obj.field("items", entry.count as u64)?;
+            obj.field("shallow_size", entry.shallow)?;
+            obj.field(
+                "shallow_size_percent",
+                super::percent(entry.shallow, items.size()),
+            )?;
+            obj.field("retained_size", entry.retained)?;
+            obj.field(
+                "retained_size_percent",
+                super::percent(entry.retained, items.size()),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_csv")]
+    fn emit_csv(&self, items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+        let mut wtr = csv::Writer::from_writer(dest);
+        for entry in self.groups.iter().chain(Some(&self.total)) {
+            wtr.serialize(CsvRecord {
+                group_by: self.group_by,
+                entry,
+                total_size: items.size(),
+            })?;
+            wtr.flush()?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,347 @@
+//! Tracking the sizes of items, groups of items and whole binaries over a
+//! series of builds.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+
+use anyhow::anyhow;
+#[cfg(feature = "emit_csv")]
+use serde_derive::Serialize;
+
+use crate::analyses::categories;
+use crate::analyses::diff::matching;
+#[cfg(feature = "emit_json")]
+use crate::formats::json;
+#[cfg(feature = "emit_text")]
+use crate::formats::table::{Align, Table};
+use twiggy_ir as ir;
+use twiggy_opt as opt;
+use twiggy_traits as traits;
+
+/// The sizes of items, or groups of items, in each build.
+#[derive(Debug)]
+struct History {
+    builds: Vec<String>,
+    series: Vec<Series>,
+    remaining: Option<Series>,
+    total: Series,
+}
+
+/// The size of an item, a group of items or a summary row in each build, or
+/// none for the builds it is not in.
+#[derive(Debug)]
+struct Series {
+    name: String,
+    sizes: Vec<Option<u64>>,
+}
+
+impl Series {
+    /// The index of the first build that this is in.
+    fn appeared(&self) -> Option<usize> {
+        self.sizes.iter().position(Option::is_some)
+    }
+
+    /// How much this grew from the first build to the last.
+    fn growth(&self) -> i64 {
+        let first = self.sizes.first().copied().flatten().unwrap_or(0);
+        let last = self.sizes.last().copied().flatten().unwrap_or(0);
+        super::size_delta(first, last)
+    }
+
+    /// How much this grew from the first build to the last, as a percentage
+    /// of its size in the first build, if it was in it.
+    fn growth_percent(&self) -> Option<f64> {
+        match self.sizes.first().copied().flatten() {
+            Some(first) if first > 0 => Some(self.growth() as f64 / first as f64 * 100.0),
+            _ => None,
+        }
+    }
+
+    /// Did this keep the same size in every build?
+    fn is_unchanged(&self) -> bool {
+        self.sizes.windows(2).all(|pair| pair[0] == pair[1])
+    }
+}
+
+impl traits::Emit for History {
+    #[cfg(feature = "emit_text")]
+    fn emit_text(&self, _items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+        let mut header = vec![
+            (Align::Right, "Growth".to_string()),
+            (Align::Right, "Growth %".to_string()),
+        ];
+        for build in &self.builds {
+            header.push((Align::Right, build.clone()));
+        }
+        header.push((Align::Left, "Appeared".to_string()));
+        header.push((Align::Left, "Item".to_string()));
+        let mut table = Table::with_header(header);
+
+        let summaries = self.remaining.iter().chain(Some(&self.total));
+        for (series, summary) in self
+            .series
+            .iter()
+            .map(|series| (series, false))
+            .chain(summaries.map(|series| (series, true)))
+        {
+            let mut row = vec![
+                format!("{:+}", series.growth()),
+                series
+                    .growth_percent()
+                    .map_or_else(String::new, |p| format!("{:+.2}%", p)),
+            ];
+            for size in &series.sizes {
+                row.push(size.map_or_else(String::new, |size| size.to_string()));
+            }
+            row.push(match series.appeared() {
+                Some(build) if !summary => self.builds[build].clone(),
+                _ => String::new(),
+            });
+            row.push(series.name.clone());
+            table.add_row(row);
+        }
+
+        write!(dest, "{}", &table)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_json")]
+    fn emit_json(&self, _items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+        let add_series =
+            |obj: &mut json::Object, series: &Series, summary: bool| -> anyhow::Result<()> {
+                obj.field("name", series.name.as_str())?;
+                obj.field("growth_bytes", series.growth())?;
+                if let Some(growth_percent) = series.growth_percent() {
+                    obj.field("growth_percent", growth_percent)?;
+                }
+                if let (Some(build), false) = (series.appeared(), summary) {
+                    obj.field("appeared", self.builds[build].as_str())?;
+                }
+                let mut sizes = obj.array("sizes")?;
+                for (build, size) in self.builds.iter().zip(&series.sizes) {
+                    if let Some(size) = *size {
+                        let mut point = sizes.object()?;
+                        point.field("build", build.as_str())?;
+                        point.field("size", size)?;
+                    }
+                }
+                Ok(())
+            };
+
+        let mut obj = json::object(dest)?;
+        {
+            let mut builds = obj.array("builds")?;
+            for build in &self.builds {
+                builds.elem(build.as_str())?;
+            }
+        }
+        {
+            let mut items = obj.array("items")?;
+            for series in &self.series {
+                add_series(&mut items.object()?, series, false)?;
+            }
+        }
+        let mut summary = obj.array("summary")?;
+        for series in self.remaining.iter().chain(Some(&self.total)) {
+            add_series(&mut summary.object()?, series, true)?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_csv")]
+    fn emit_csv(&self, _items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+        #[derive(Serialize, Debug)]
+        #[serde(rename_all = "PascalCase")]
+        struct CsvRecord<'a> {
+            item: &'a str,
+            appeared: Option<&'a str>,
+            growth: String,
+            build: &'a str,
+            size: u64,
+        }
+
+        let mut wtr = csv::Writer::from_writer(dest);
+        let summaries = self.remaining.iter().chain(Some(&self.total));
+        for (series, summary) in self
+            .series
+            .iter()
+            .map(|series| (series, false))
+            .chain(summaries.map(|series| (series, true)))
+        {
+            let appeared = match series.appeared() {
+                Some(build) if !summary => Some(self.builds[build].as_str()),
+                _ => None,
+            };
+            for (build, size) in self.builds.iter().zip(&series.sizes) {
+                if let Some(size) = *size {
+                    wtr.serialize(CsvRecord {
+                        item: &series.name,
+                        appeared,
+                        growth: format!("{:+}", series.growth()),
+                        build,
+                        size,
+                    })?;
+                }
+            }
+            wtr.flush()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Follow each item through the builds, by matching the items of each build
+/// with those of the build before it. Returns the item in each build for each
+/// item that was in any of them.
+fn tracks(builds: &[ir::Items], exact_names: bool) -> Vec<Vec<Option<ir::Id>>> {
+    let all_ids = |items: &ir::Items| -> Vec<ir::Id> {
+        items
+            .iter()
+            .map(|item| item.id())
+            .filter(|&id| id != items.meta_root())
+            .collect()
+    };
+
+    let mut tracks = vec![];
+    let mut current = HashMap::new();
+    for id in all_ids(&builds[0]) {
+        let mut track = vec![None; builds.len()];
+        track[0] = Some(id);
+        current.insert(id, tracks.len());
+        tracks.push(track);
+    }
+
+    // An item that was removed and later added back is followed as a new item
+    // from the build it came back in.
+    for (i, pair) in builds.windows(2).enumerate() {
+        let (old_items, new_items) = (&pair[0], &pair[1]);
+        let matches = matching::match_items(
+            old_items,
+            all_ids(old_items),
+            new_items,
+            all_ids(new_items),
+            exact_names,
+        );
+        let mut next = HashMap::new();
+        for (old, new) in matches {
+            let new = match new {
+                Some(new) => new,
+                None => continue,
+            };
+            let track = match old {
+                Some(old) => current[&old],
+                None => {
+                    tracks.push(vec![None; builds.len()]);
+                    tracks.len() - 1
+                }
+            };
+            tracks[track][i + 1] = Some(new);
+            next.insert(new, track);
+        }
+        current = next;
+    }
+    tracks
+}
+
+/// Show how the sizes of items, or groups of items, changed over the given
+/// builds, which are named by the given labels.
+pub fn history(
+    builds: &[ir::Items],
+    labels: &[String],
+    opts: &opt::History,
+) -> anyhow::Result<Box<dyn traits::Emit>> {
+    if labels.len() != builds.len() {
+        return Err(anyhow!(
+            "expected a label for each of the {} builds, but got {}",
+            builds.len(),
+            labels.len()
+        ));
+    }
+    for items in builds {
+        categories::check_classified(items, &[], opts.group_by())?;
+    }
+    let max_items = opts.max_items() as usize;
+
+    // Sum the sizes of the items in each group in each build, or follow each
+    // item through the builds.
+    let mut series: Vec<Series> = match opts.group_by() {
+        Some(group_by) => {
+            let mut groups: BTreeMap<String, Vec<Option<u64>>> = BTreeMap::new();
+            for (i, items) in builds.iter().enumerate() {
+                for item in items.iter().filter(|item| item.id() != items.meta_root()) {
+                    let group = super::groups::group_of(items, item.id(), group_by);
+                    let sizes = groups
+                        .entry(group)
+                        .or_insert_with(|| vec![None; builds.len()]);
+                    let size = sizes[i].get_or_insert(0);
+                    *size = size.saturating_add(item.size());
+                }
+            }
+            groups
+                .into_iter()
+                .map(|(name, sizes)| Series { name, sizes })
+                .collect()
+        }
+        None => tracks(builds, opts.exact_names())
+            .into_iter()
+            .map(|track| {
+                let (last, id) = track
+                    .iter()
+                    .enumerate()
+                    .rev()
+                    .find_map(|(i, id)| Some((i, (*id)?)))
+                    .expect("every item is in some build");
+                Series {
+                    name: builds[last].display_name(id).into_owned(),
+                    sizes: track
+                        .iter()
+                        .zip(builds)
+                        .map(|(id, items)| id.map(|id| items[id].size()))
+                        .collect(),
+                }
+            })
+            .collect(),
+    };
+
+    // Only show what changed at some point, largest cumulative growers first.
+    series.retain(|series| !series.is_unchanged());
+    series.sort_by(|a, b| {
+        b.growth()
+            .cmp(&a.growth())
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    // Sum the sizes in each build of the given series.
+    let sum_sizes = |series: &[Series]| -> Vec<Option<u64>> {
+        (0..builds.len())
+            .map(|i| {
+                Some(super::total_size(
+                    series.iter().filter_map(|series| series.sizes[i]),
+                ))
+            })
+            .collect()
+    };
+
+    let total = Series {
+        name: format!("Σ [{} Total Rows]", series.len()),
+        sizes: builds.iter().map(|items| Some(items.size())).collect(),
+    };
+    let rem_cnt = series.len().saturating_sub(max_items);
+    let remaining = if rem_cnt > 0 {
+        Some(Series {
+            name: format!("... and {} more.", rem_cnt),
+            sizes: sum_sizes(&series[max_items..]),
+        })
+    } else {
+        None
+    };
+    series.truncate(max_items);
+
+    Ok(Box::new(History {
+        builds: labels.to_vec(),
+        series,
+        remaining,
+        total,
+    }) as Box<_>)
+}
@@ -1,6 +1,80 @@
+pub mod check;
+pub mod crates;
 pub mod diff;
 pub mod dominators;
 pub mod garbage;
+pub mod history;
 pub mod monos;
 pub mod paths;
 pub mod top;
+pub mod whatif;
+
+mod categories;
+mod groups;
+
+use std::collections::BTreeSet;
+
+use twiggy_ir as ir;
+
+/// Drop the edges that aren't of one of the given `kinds`, or that are of one
+/// of the `excluded` kinds. All kinds are kept if `kinds` is empty.
+pub(crate) fn filter_edges(
+    items: &mut ir::Items,
+    kinds: &[ir::EdgeKind],
+    excluded: &[ir::EdgeKind],
+) {
+    if kinds.is_empty() && excluded.is_empty() {
+        return;
+    }
+    items.retain_edges(|_, _, kind| {
+        (kinds.is_empty() || kinds.contains(&kind)) && !excluded.contains(&kind)
+    });
+}
+
+/// Is the given item of one of the given `kinds`? Every item is if `kinds` is
+/// empty.
+pub(crate) fn matches_kinds(item: &ir::Item, kinds: &[ir::ItemKindTag]) -> bool {
+    kinds.is_empty() || kinds.contains(&item.kind().tag())
+}
+
+/// The percentage of `total` that `size` is. Zero if `total` is zero.
+pub(crate) fn percent(size: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        size as f64 / total as f64 * 100.0
+    }
+}
+
+/// The total of the given sizes, saturating at `u64::MAX` rather than
+/// overflowing, since the sizes come from files that may be malformed.
+pub(crate) fn total_size(sizes: impl IntoIterator<Item = u64>) -> u64 {
+    sizes.into_iter().fold(0, u64::saturating_add)
+}
+
+/// The signed change from the `old` size to the `new` size, saturating at the
+/// bounds of an `i64`.
+pub(crate) fn size_delta(old: u64, new: u64) -> i64 {
+    let delta = i128::from(new) - i128::from(old);
+    delta.clamp(i64::MIN.into(), i64::MAX.into()) as i64
+}
+
+/// The number of bytes that the given set of items retains together. This is
+/// the sum of their retained sizes, leaving out items that another item in the
+/// set dominates, since those are already counted.
+pub(crate) fn retained_size_of(items: &ir::Items, ids: &BTreeSet<ir::Id>) -> u64 {
+    let retained = ids
+        .iter()
+        .filter(|&&id| {
+            let mut dominator = items.immediate_dominator(id);
+            while let Some(d) = dominator {
+                if ids.contains(&d) {
+                    return false;
+                }
+                dominator = items.immediate_dominator(d);
+            }
+            true
+        })
+        .map(|&id| items.retained_size(id));
+    total_size(retained)
+}
@@ -16,9 +16,9 @@ impl traits::Emit for Monos {
     #[cfg(feature = "emit_text")]
     fn emit_text(&self, items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
         struct TableRow {
-            bloat: Option<u32>,
+            bloat: Option<u64>,
             bloat_percent: Option<f64>,
-            size: u32,
+            size: u64,
             size_percent: f64,
             name: String,
         }
@@ -27,7 +27,7 @@ impl traits::Emit for Monos {
         // monomorphizations, return a vector of table rows.
         fn process_entry<'a>(
             entry: &'a MonosEntry,
-            total_size: f64,
+            total_size: u64,
         ) -> impl Iterator<Item = TableRow> + 'a {
             let MonosEntry {
                 name,
@@ -36,7 +36,7 @@ impl traits::Emit for Monos {
                 bloat,
             } = entry;
 
-            let get_size_percent = move |x: u32| f64::from(x) / total_size * 100.0;
+            let get_size_percent = move |x: u64| crate::analyses::percent(x, total_size);
 
             iter::once(TableRow {
                 bloat: Some(*bloat),
@@ -45,7 +45,7 @@ impl traits::Emit for Monos {
                 size_percent: get_size_percent(*size),
                 name: name.to_string(),
             })
-            .chain(insts.iter().map(move |(name, size)| TableRow {
+            .chain(insts.iter().map(move |(name, size, _)| TableRow {
                 bloat: None,
                 bloat_percent: None,
                 size: *size,
@@ -71,7 +71,7 @@ impl traits::Emit for Monos {
         } in self
             .monos
             .iter()
-            .flat_map(|mono| process_entry(mono, f64::from(items.size())))
+            .flat_map(|mono| process_entry(mono, items.size()))
         {
             table.add_row(vec![
                 bloat.map(|b| b.to_string()).unwrap_or_default(),
@@ -94,9 +94,9 @@ impl traits::Emit for Monos {
         fn process_entry(
             entry: &MonosEntry,
             obj: &mut json::Object,
-            total_size: f64,
+            total_size: u64,
         ) -> anyhow::Result<()> {
-            let get_size_percent = |size: u32| (f64::from(size)) / total_size * 100.0;
+            let get_size_percent = |size: u64| crate::analyses::percent(size, total_size);
             let MonosEntry {
                 name,
                 insts,
@@ -112,19 +112,22 @@ impl traits::Emit for Monos {
             obj.field("total_size", *size)?;
             obj.field("total_size_percent", get_size_percent(*size))?;
             let mut monos = obj.array("monomorphizations")?;
-            for (name, size, size_percent) in insts
-                .iter()
-                .map(|(name, size)| (name, size, get_size_percent(*size)))
-            {
+            for (name, size, generic_args) in insts {
                 let mut obj = monos.object()?;
                 obj.field("name", name.as_str())?;
                 obj.field("shallow_size", *size)?;
-                obj.field("shallow_size_percent", size_percent)?;
+                obj.field("shallow_size_percent", get_size_percent(*size))?;
+                if !generic_args.is_empty() {
+                    let mut args = obj.array("generic_args")?;
+                    for arg in generic_args {
+                        args.elem(arg.as_str())?;
+                    }
+                }
             }
             Ok(())
         }
 
-        let items_size = f64::from(items.size());
+        let items_size = items.size();
         let mut arr = json::array(dest)?;
         for entry in &self.monos {
             let mut obj = arr.object()?;
@@ -137,17 +140,17 @@ impl traits::Emit for Monos {
     #[cfg(feature = "emit_csv")]
     fn emit_csv(&self, items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
         // Calculate the total size of the collection of items, and define a
-        // helper closure to calculate a percent value for a given u32 size.
-        let items_size = f64::from(items.size());
-        let get_size_percent = |size: u32| (f64::from(size)) / items_size * 100.0;
+        // helper closure to calculate a percent value for a given size.
+        let items_size = items.size();
+        let get_size_percent = |size: u64| crate::analyses::percent(size, items_size);
 
         #[derive(Debug, Default, Serialize)]
         #[serde(rename_all = "PascalCase")]
         struct Record {
             generic: Option<String>,
-            approximate_monomorphization_bloat_bytes: Option<u32>,
+            approximate_monomorphization_bloat_bytes: Option<u64>,
             approximate_monomorphization_bloat_percent: Option<f64>,
-            total_size: Option<u32>,
+            total_size: Option<u64>,
             total_size_percent: Option<f64>,
             monomorphizations: Option<String>,
         }
@@ -157,7 +160,7 @@ impl traits::Emit for Monos {
             let monos = entry
                 .insts
                 .iter()
-                .map(|(name, _)| name.as_str())
+                .map(|(name, _, _)| name.as_str())
                 .collect::<Vec<_>>();
             Record {
                 generic: Some(entry.name.clone()),
@@ -1,9 +1,9 @@
 #[derive(Debug, PartialEq, Eq)]
 pub(super) struct MonosEntry {
     pub name: String,
-    pub insts: Vec<(String, u32)>,
-    pub size: u32,
-    pub bloat: u32,
+    pub insts: Vec<super::Inst>,
+    pub size: u64,
+    pub bloat: u64,
 }
 
 impl PartialOrd for MonosEntry {
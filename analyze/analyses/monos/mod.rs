@@ -8,6 +8,7 @@ use twiggy_traits as traits;
 
 mod emit;
 mod entry;
+mod types;
 
 use self::entry::MonosEntry;
 
@@ -16,38 +17,47 @@ struct Monos {
     monos: Vec<MonosEntry>,
 }
 
+/// An instantiation of a generic function: its name, its size, and the generic
+/// arguments it was instantiated with, when they are known.
+type Inst = (String, u64, Vec<String>);
+
 /// Type alias used to represent a map of generic function names and instantiations.
-type MonosMap<'a> = BTreeMap<&'a str, Vec<(String, u32)>>;
+type MonosMap<'a> = BTreeMap<&'a str, Vec<Inst>>;
 
-/// Collect the monomorphizations of generic functions into a map, then
-/// process the entries and sort the resulting vector.
-fn collect_monomorphizations<'a>(
+/// Find the monomorphizations of the generic functions selected by the
+/// options, paired with the generic function each one instantiates.
+fn monomorphizations<'a>(
     items: &'a ir::Items,
-    opts: &opt::Monos,
-) -> anyhow::Result<MonosMap<'a>> {
+    opts: &'a opt::Monos,
+) -> anyhow::Result<impl Iterator<Item = (&'a str, &'a ir::Item)>> {
     let args_given = !opts.functions().is_empty();
     let using_regexps = opts.using_regexps();
     let regexps = regex::RegexSet::new(opts.functions())?;
 
-    let unsorted_monos: BTreeMap<&'a str, BTreeSet<(String, u32)>> = items
+    Ok(items
         .iter()
-        .filter_map(|item| {
-            if let Some(generic) = item.monomorphization_of() {
-                Some((generic, item))
-            } else {
-                None
-            }
-        })
-        .filter(|(generic, _)| match (args_given, using_regexps) {
+        .filter(move |item| super::matches_kinds(item, opts.kinds()))
+        .filter_map(|item| item.monomorphization_of().map(|generic| (generic, item)))
+        .filter(move |(generic, _)| match (args_given, using_regexps) {
             (true, true) => regexps.is_match(generic),
             (true, false) => opts.functions().iter().any(|name| name == generic),
             (false, _) => true,
-        })
-        .fold(BTreeMap::new(), |mut monos, (generic, inst)| {
-            monos
-                .entry(generic)
-                .or_insert_with(BTreeSet::new)
-                .insert((inst.name().to_string(), inst.size()));
+        }))
+}
+
+/// Collect the monomorphizations of generic functions into a map, then
+/// process the entries and sort the resulting vector.
+fn collect_monomorphizations<'a>(
+    items: &'a ir::Items,
+    opts: &'a opt::Monos,
+) -> anyhow::Result<MonosMap<'a>> {
+    let unsorted_monos: BTreeMap<&'a str, BTreeSet<Inst>> =
+        monomorphizations(items, opts)?.fold(BTreeMap::new(), |mut monos, (generic, inst)| {
+            monos.entry(generic).or_insert_with(BTreeSet::new).insert((
+                items.display_name(inst.id()).into_owned(),
+                inst.size(),
+                inst.generic_args().to_vec(),
+            ));
             monos
         });
 
@@ -55,7 +65,7 @@ fn collect_monomorphizations<'a>(
         .into_iter()
         .map(|(generic, inst_set)| {
             let mut insts = inst_set.into_iter().collect::<Vec<_>>();
-            insts.sort_by(|(a_name, a_size), (b_name, b_size)| {
+            insts.sort_by(|(a_name, a_size, _), (b_name, b_size, _)| {
                 b_size.cmp(a_size).then(a_name.cmp(b_name))
             });
             (generic, insts)
@@ -66,7 +76,7 @@ fn collect_monomorphizations<'a>(
 /// Helper function usedd to summarize a sequence of `MonosEntry` objects.
 /// Returns a tuple representing the number of items summarized, the total
 /// size of the items, and the total approximate potential savings.
-fn summarize_entries<'a>(entries: impl Iterator<Item = &'a MonosEntry>) -> (usize, u32, u32) {
+fn summarize_entries<'a>(entries: impl Iterator<Item = &'a MonosEntry>) -> (usize, u64, u64) {
     entries.fold(
         (0, 0, 0),
         |(total_cnt, total_size, total_savings),
@@ -75,8 +85,8 @@ fn summarize_entries<'a>(entries: impl Iterator<Item = &'a MonosEntry>) -> (usiz
          }| {
             (
                 total_cnt + 1 + insts.len(),
-                total_size + size,
-                total_savings + bloat,
+                u64::saturating_add(total_size, *size),
+                u64::saturating_add(total_savings, *bloat),
             )
         },
     )
@@ -85,19 +95,19 @@ fn summarize_entries<'a>(entries: impl Iterator<Item = &'a MonosEntry>) -> (usiz
 /// Helper function used to summarize a sequence of tuples representing
 /// instantiations of a generic function. Returns a tuple representing the
 /// number of instantiations found, and the total size.
-fn summarize_insts<'a>(entries: impl Iterator<Item = &'a (String, u32)>) -> (u32, u32) {
-    entries.fold((0, 0), |(total_cnt, total_size), (_, size)| {
-        (total_cnt + 1, total_size + size)
+fn summarize_insts<'a>(entries: impl Iterator<Item = &'a Inst>) -> (u32, u64) {
+    entries.fold((0, 0), |(total_cnt, total_size), (_, size, _)| {
+        (total_cnt + 1, u64::saturating_add(total_size, *size))
     })
 }
 
 /// Find the approximate potential savings by calculating the benefits of
 /// removing the largest instantiation, and the benefits of removing an
 /// average instantiation. Returns a tuple containing total size, and bloat.
-fn calculate_total_and_bloat(insts: &[(String, u32)]) -> Option<(u32, u32)> {
-    if let Some(max) = insts.iter().map(|(_, size)| size).max() {
-        let total_size = insts.iter().map(|(_, size)| size).sum::<u32>();
-        let inst_cnt = insts.len() as u32;
+fn calculate_total_and_bloat(insts: &[Inst]) -> Option<(u64, u64)> {
+    if let Some(max) = insts.iter().map(|(_, size, _)| size).max() {
+        let total_size = super::total_size(insts.iter().map(|(_, size, _)| *size));
+        let inst_cnt = insts.len() as u64;
         let size_per_inst = total_size / inst_cnt;
         let avg_savings = size_per_inst * (inst_cnt - 1);
         let removing_largest_savings = total_size - max;
@@ -125,7 +135,7 @@ fn process_monomorphizations(monos_map: MonosMap, opts: &opt::Monos) -> Vec<Mono
                 let (rem_cnt, rem_size) = summarize_insts(insts.iter().skip(max_monos));
                 insts.truncate(max_monos);
                 if rem_cnt > 0 {
-                    insts.push((format!("... and {} more.", rem_cnt), rem_size));
+                    insts.push((format!("... and {} more.", rem_cnt), rem_size, vec![]));
                 }
             };
             (g, insts, t, b)
@@ -185,6 +195,10 @@ fn add_stats(mut monos: Vec<MonosEntry>, opts: &opt::Monos) -> Vec<MonosEntry> {
 
 /// Find bloaty monomorphizations of generic functions.
 pub fn monos(items: &mut ir::Items, opts: &opt::Monos) -> anyhow::Result<Box<dyn traits::Emit>> {
+    if opts.by_type() {
+        return types::monos_by_type(items, opts);
+    }
+
     let monos_map = collect_monomorphizations(items, opts)?;
     let mut monos = process_monomorphizations(monos_map, opts);
     monos = add_stats(monos, opts);
@@ -0,0 +1,236 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+
+#[cfg(feature = "emit_csv")]
+use csv;
+#[cfg(feature = "emit_csv")]
+use serde_derive::Serialize;
+
+#[cfg(feature = "emit_json")]
+use crate::formats::json;
+#[cfg(feature = "emit_text")]
+use crate::formats::table::{Align, Table};
+use twiggy_ir as ir;
+use twiggy_opt as opt;
+use twiggy_traits as traits;
+
+/// The monomorphizations of generic functions, grouped by the generic
+/// arguments they were instantiated with.
+#[derive(Debug)]
+struct MonosByType {
+    types: Vec<TypeEntry>,
+}
+
+/// A generic argument, or a summary row, and the instantiations it caused.
+#[derive(Debug)]
+struct TypeEntry {
+    name: String,
+    count: usize,
+    size: u64,
+    retained: u64,
+    insts: Vec<(String, u64)>,
+}
+
+impl TypeEntry {
+    /// Summarize the given set of instantiations under the given name,
+    /// without listing them.
+    fn summary(name: String, items: &ir::Items, ids: &BTreeSet<ir::Id>) -> TypeEntry {
+        TypeEntry {
+            name,
+            count: ids.len(),
+            size: crate::analyses::total_size(ids.iter().map(|&id| items[id].size())),
+            retained: crate::analyses::retained_size_of(items, ids),
+            insts: vec![],
+        }
+    }
+}
+
+impl traits::Emit for MonosByType {
+    #[cfg(feature = "emit_text")]
+    fn emit_text(&self, items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+        let mut table = Table::with_header(vec![
+            (Align::Right, "Instantiations".into()),
+            (Align::Right, "Bytes".into()),
+            (Align::Right, "%".into()),
+            (Align::Right, "Retained Bytes".into()),
+            (Align::Right, "Retained %".into()),
+            (Align::Left, "Type Argument".to_string()),
+        ]);
+
+        let get_size_percent = |x: u64| crate::analyses::percent(x, items.size());
+        for entry in &self.types {
+            table.add_row(vec![
+                entry.count.to_string(),
+                entry.size.to_string(),
+                format!("{:.2}%", get_size_percent(entry.size)),
+                entry.retained.to_string(),
+                format!("{:.2}%", get_size_percent(entry.retained)),
+                entry.name.clone(),
+            ]);
+            for (name, size) in &entry.insts {
+                table.add_row(vec![
+                    String::new(),
+                    size.to_string(),
+                    format!("{:.2}%", get_size_percent(*size)),
+                    String::new(),
+                    String::new(),
+                    format!("    {}", name),
+                ]);
+            }
+        }
+
+        write!(dest, "{}", &table)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_json")]
+    fn emit_json(&self, items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+        let get_size_percent = |x: u64| crate::analyses::percent(x, items.size());
+        let mut arr = json::array(dest)?;
+        for entry in &self.types {
+            let mut obj = arr.object()?;
+            obj.field("type_argument", entry.name.as_str())?;
+            obj.field("instantiations", entry.count as u64)?;
+            obj.field("total_size", entry.size)?;
+            obj.field("total_size_percent", get_size_percent(entry.size))?;
+            obj.field("retained_size", entry.retained)?;
+            obj.field("retained_size_percent", get_size_percent(entry.retained))?;
+            let mut monos = obj.array("monomorphizations")?;
+            for (name, size) in &entry.insts {
+                let mut obj = monos.object()?;
+                obj.field("name", name.as_str())?;
+                obj.field("shallow_size", *size)?;
+                obj.field("shallow_size_percent", get_size_percent(*size))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_csv")]
+    fn emit_csv(&self, items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+        let get_size_percent = |x: u64| crate::analyses::percent(x, items.size());
+
+        #[derive(Debug, Default, Serialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Record {
+            type_argument: String,
+            instantiations: usize,
+            total_size: u64,
+            total_size_percent: f64,
+            retained_size: u64,
+            retained_size_percent: f64,
+            monomorphizations: String,
+        }
+
+        let mut wtr = csv::Writer::from_writer(dest);
+        for entry in &self.types {
+            let monos = entry
+                .insts
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>();
+            wtr.serialize(Record {
+                type_argument: entry.name.clone(),
+                instantiations: entry.count,
+                total_size: entry.size,
+                total_size_percent: get_size_percent(entry.size),
+                retained_size: entry.retained,
+                retained_size_percent: get_size_percent(entry.retained),
+                monomorphizations: monos.join(", "),
+            })?;
+            wtr.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// Find the generic arguments whose instantiations of generic functions take
+/// up the most space.
+pub(super) fn monos_by_type(
+    items: &mut ir::Items,
+    opts: &opt::Monos,
+) -> anyhow::Result<Box<dyn traits::Emit>> {
+    items.compute_retained_sizes();
+    let items = &*items;
+
+    // Map each generic argument to the instantiations that use it. Lifetimes
+    // don't cause instantiations, so they are left out.
+    let mut by_type: BTreeMap<&str, BTreeSet<ir::Id>> = BTreeMap::new();
+    for (_, inst) in super::monomorphizations(items, opts)? {
+        for arg in inst.generic_args() {
+            if !arg.starts_with('\'') {
+                by_type.entry(arg).or_default().insert(inst.id());
+            }
+        }
+    }
+
+    let mut types: Vec<(&str, BTreeSet<ir::Id>, TypeEntry)> = by_type
+        .into_iter()
+        .map(|(name, ids)| {
+            let entry = TypeEntry::summary(name.to_string(), items, &ids);
+            (name, ids, entry)
+        })
+        .collect();
+    types.sort_by(|(_, _, a), (_, _, b)| {
+        b.retained
+            .cmp(&a.retained)
+            .then(b.size.cmp(&a.size))
+            .then(a.name.cmp(&b.name))
+    });
+
+    let all: BTreeSet<ir::Id> = types.iter().flat_map(|(_, ids, _)| ids).copied().collect();
+    let total_rows = types.len() + types.iter().map(|(_, ids, _)| ids.len()).sum::<usize>();
+
+    let max_generics = opts.max_generics() as usize;
+    let remaining: BTreeSet<ir::Id> = types
+        .iter()
+        .skip(max_generics)
+        .flat_map(|(_, ids, _)| ids)
+        .copied()
+        .collect();
+    let remaining_types = types.len().saturating_sub(max_generics);
+    types.truncate(max_generics);
+
+    let mut entries: Vec<TypeEntry> = types
+        .into_iter()
+        .map(|(_, ids, mut entry)| {
+            if !opts.only_generics() {
+                let mut insts: Vec<(String, u64)> = ids
+                    .iter()
+                    .map(|&id| (items.display_name(id).into_owned(), items[id].size()))
+                    .collect();
+                insts.sort_by(|(a_name, a_size), (b_name, b_size)| {
+                    b_size.cmp(a_size).then(a_name.cmp(b_name))
+                });
+
+                let max_monos = opts.max_monos() as usize;
+                if insts.len() > max_monos {
+                    let rem_cnt = insts.len() - max_monos;
+                    let rem_size = crate::analyses::total_size(
+                        insts[max_monos..].iter().map(|(_, size)| *size),
+                    );
+                    insts.truncate(max_monos);
+                    insts.push((format!("... and {} more.", rem_cnt), rem_size));
+                }
+                entry.insts = insts;
+            }
+            entry
+        })
+        .collect();
+
+    if remaining_types > 0 {
+        entries.push(TypeEntry::summary(
+            format!("... and {} more.", remaining_types),
+            items,
+            &remaining,
+        ));
+    }
+    entries.push(TypeEntry::summary(
+        format!("Σ [{} Total Rows]", total_rows),
+        items,
+        &all,
+    ));
+
+    Ok(Box::new(MonosByType { types: entries }) as Box<_>)
+}
@@ -1,4 +1,7 @@
-use std::collections::BTreeSet;
+use std::cmp;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::iter;
+use std::vec;
 
 use regex;
 
@@ -9,7 +12,11 @@ use twiggy_traits as traits;
 mod paths_emit;
 mod paths_entry;
 
-use self::paths_entry::PathsEntry;
+#[cfg(feature = "emit_json")]
+pub(super) use self::paths_emit::emit_json_helpers;
+#[cfg(feature = "emit_text")]
+pub(super) use self::paths_emit::emit_text_helpers;
+pub(super) use self::paths_entry::PathsEntry;
 
 #[derive(Debug)]
 struct Paths {
@@ -19,6 +26,8 @@ struct Paths {
 
 /// Find all retaining paths for the given items.
 pub fn paths(items: &mut ir::Items, opts: &opt::Paths) -> anyhow::Result<Box<dyn traits::Emit>> {
+    super::filter_edges(items, opts.edge_kinds(), opts.excluded_edge_kinds());
+
     // The predecessor tree only needs to be computed if we are ascending
     // through the retaining paths.
     if !opts.descending() {
@@ -46,7 +55,7 @@ fn get_starting_positions(items: &ir::Items, opts: &opt::Paths) -> anyhow::Resul
             .iter()
             .filter(|item| item.id() != items.meta_root())
             .collect::<Vec<_>>();
-        sorted_items.sort_by(|a, b| b.size().cmp(&a.size()));
+        sorted_items.sort_by_key(|item| cmp::Reverse(item.size()));
         sorted_items.iter().map(|item| item.id()).collect()
     };
 
@@ -56,7 +65,7 @@ fn get_starting_positions(items: &ir::Items, opts: &opt::Paths) -> anyhow::Resul
             .neighbors(items.meta_root())
             .map(|id| &items[id])
             .collect::<Vec<_>>();
-        roots.sort_by(|a, b| b.size().cmp(&a.size()));
+        roots.sort_by_key(|item| cmp::Reverse(item.size()));
         roots.into_iter().map(|item| item.id()).collect()
     };
 
@@ -75,7 +84,7 @@ fn get_starting_positions(items: &ir::Items, opts: &opt::Paths) -> anyhow::Resul
     let get_exact_matches = || -> Vec<ir::Id> {
         opts.functions()
             .iter()
-            .filter_map(|s| items.get_item_by_name(s))
+            .flat_map(|s| items.get_items_by_name(s))
             .map(|item| item.id())
             .collect()
     };
@@ -87,58 +96,168 @@ fn get_starting_positions(items: &ir::Items, opts: &opt::Paths) -> anyhow::Resul
     let args_given = !opts.functions().is_empty();
     let using_regexps = opts.using_regexps();
     let descending = opts.descending();
-    let res = match (args_given, using_regexps, descending) {
+    let mut res = match (args_given, using_regexps, descending) {
         (true, true, _) => get_regexp_matches()?,
         (true, false, _) => get_exact_matches(),
         (false, _, true) => get_functions_default_desc(),
         (false, _, false) => get_functions_default(),
     };
+    res.retain(|&id| super::matches_kinds(&items[id], opts.kinds()));
 
     Ok(res)
 }
 
 /// Create a `PathsEntry` object for the given item.
+///
+/// The entries are built with an explicit stack rather than by recursion, so
+/// that long chains of items can't overflow the call stack. Only the parts of
+/// the tree that can be emitted are built: the emitters go no deeper than the
+/// maximum depth, and no further than the first few paths at each level,
+/// though the CSV output also names all of the children of each entry.
 fn create_entry(
     id: ir::Id,
     items: &ir::Items,
     opts: &opt::Paths,
     seen: &mut BTreeSet<ir::Id>,
 ) -> PathsEntry {
-    // Determine the item's name and size.
-    let item = &items[id];
-    let name = item.name().to_string();
-    let size = item.size();
-
-    // Collect the `ir::Id` values of this entry's children, depending on
-    // whether we are ascending or descending the IR-tree.
-    let children_ids: Vec<ir::Id> = if opts.descending() {
-        items
-            .neighbors(id)
-            .map(|id| id as ir::Id)
-            .filter(|id| !seen.contains(id))
-            .filter(|&id| id != items.meta_root())
-            .collect()
-    } else {
-        items
-            .predecessors(id)
-            .map(|id| id as ir::Id)
-            .filter(|id| !seen.contains(id))
-            .filter(|&id| id != items.meta_root())
-            .collect()
+    struct Frame {
+        id: ir::Id,
+        entry: PathsEntry,
+        children_ids: iter::Enumerate<vec::IntoIter<ir::Id>>,
+    }
+
+    // Determine an item's name and size, and collect the `ir::Id` values of
+    // its children, depending on whether we are ascending or descending the
+    // IR-tree. Items that are already on the current path are skipped.
+    let new_frame = |id: ir::Id, expand: bool, seen: &BTreeSet<ir::Id>| -> Frame {
+        let item = &items[id];
+        let entry = PathsEntry {
+            name: items.display_name(id).into_owned(),
+            size: item.size(),
+            children: vec![],
+        };
+
+        let children_ids: Vec<ir::Id> = if !expand {
+            vec![]
+        } else if opts.descending() {
+            items
+                .neighbors(id)
+                .filter(|id| !seen.contains(id))
+                .filter(|&id| id != items.meta_root())
+                .collect()
+        } else {
+            items
+                .predecessors(id)
+                .filter(|id| !seen.contains(id))
+                .filter(|&id| id != items.meta_root())
+                .collect()
+        };
+
+        Frame {
+            id,
+            entry,
+            children_ids: children_ids.into_iter().enumerate(),
+        }
     };
 
-    // Temporarily add the current item to the set of discovered nodes, and
-    // create an entry for each child. Collect these into a `children` vector.
+    // The current item is in the set of discovered nodes for as long as its
+    // frame is on the stack, while entries are created for its children.
+    let mut stack = vec![new_frame(id, true, seen)];
     seen.insert(id);
-    let children = children_ids
-        .into_iter()
-        .map(|id| create_entry(id, items, opts, seen))
-        .collect();
-    seen.remove(&id);
+    loop {
+        let depth = stack.len() as u32;
+        let frame = stack.last_mut().unwrap();
+        if let Some((i, child)) = frame.children_ids.next() {
+            let expand = depth <= opts.max_depth() && i < opts.max_paths() as usize;
+            let child_frame = new_frame(child, expand, seen);
+            seen.insert(child);
+            stack.push(child_frame);
+            continue;
+        }
+
+        let frame = stack.pop().unwrap();
+        seen.remove(&frame.id);
+        match stack.last_mut() {
+            Some(parent) => parent.entry.children.push(frame.entry),
+            None => return frame.entry,
+        }
+    }
+}
 
-    PathsEntry {
-        name,
-        size,
-        children,
+/// Find the shortest retaining paths of the given item: the shortest path to it
+/// from each of the first `max_paths` roots that reach it, shortest first.
+/// These are built into an entry for the item whose children are its callers
+/// along those paths, in the same way that `paths` shows them, and which goes
+/// no deeper than `max_depth`. Returns `None` if no root reaches the item.
+///
+/// The predecessors of the items must already have been computed.
+pub(super) fn shortest_retaining_paths(
+    items: &ir::Items,
+    id: ir::Id,
+    max_paths: u32,
+    max_depth: u32,
+) -> Option<PathsEntry> {
+    let roots: BTreeSet<ir::Id> = items.neighbors(items.meta_root()).collect();
+
+    // Search breadth first through the item's callers, remembering which
+    // callee each caller was first reached from. That makes the path from
+    // each root found back to the item one of its shortest.
+    let mut discovered = vec![(id, 0)];
+    let mut callees = BTreeMap::new();
+    let mut queue = VecDeque::from(vec![(id, 0)]);
+    let mut ends = vec![];
+    while let Some((node, depth)) = queue.pop_front() {
+        if roots.contains(&node) {
+            ends.push(node);
+            if ends.len() >= max_paths as usize {
+                break;
+            }
+        }
+        for caller in items.predecessors(node) {
+            if caller == items.meta_root() || caller == id || callees.contains_key(&caller) {
+                continue;
+            }
+            callees.insert(caller, node);
+            discovered.push((caller, depth + 1));
+            queue.push_back((caller, depth + 1));
+        }
+    }
+
+    if ends.is_empty() {
+        return None;
+    }
+
+    let mut on_paths: BTreeSet<ir::Id> = iter::once(id).collect();
+    for mut node in ends {
+        while on_paths.insert(node) {
+            node = callees[&node];
+        }
+    }
+
+    // Callers are discovered after their callees, so building the entries in
+    // reverse order finishes each caller's entry before it is added to its
+    // callee's.
+    let new_entry = |node: ir::Id| PathsEntry {
+        name: items.display_name(node).into_owned(),
+        size: items[node].size(),
+        children: vec![],
+    };
+    let mut entries: BTreeMap<ir::Id, PathsEntry> = BTreeMap::new();
+    for &(node, depth) in discovered.iter().rev() {
+        if depth > max_depth || !on_paths.contains(&node) {
+            continue;
+        }
+        let mut entry = entries.remove(&node).unwrap_or_else(|| new_entry(node));
+        entry.children.reverse();
+        if node == id {
+            return Some(entry);
+        }
+        let callee = callees[&node];
+        entries
+            .entry(callee)
+            .or_insert_with(|| new_entry(callee))
+            .children
+            .push(entry);
     }
+    unreachable!("the item itself is always discovered")
 }
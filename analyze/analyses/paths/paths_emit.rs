@@ -96,9 +96,10 @@ impl traits::Emit for Paths {
 }
 
 /// This module contains helper functions and structs used by the `emit_text`
-/// method in Path's implementation of the `traits::Emit` trait.
+/// method in Path's implementation of the `traits::Emit` trait, and by `top`
+/// to emit retaining paths.
 #[cfg(feature = "emit_text")]
-mod emit_text_helpers {
+pub(in crate::analyses) mod emit_text_helpers {
     use crate::analyses::paths::paths_entry::PathsEntry;
     use std::iter;
     use twiggy_ir::Items;
@@ -106,56 +107,58 @@ mod emit_text_helpers {
 
     /// This structure represents a row in the emitted text table. Size, and size
     /// percentage are only shown for the top-most rows.
-    pub(super) struct TableRow {
-        pub size: Option<u32>,
+    pub(in crate::analyses) struct TableRow {
+        pub size: Option<u64>,
         pub size_percent: Option<f64>,
         pub name: String,
     }
 
     /// Process a given path entry, and return an iterator of table rows,
     /// representing its related call paths, according to the given options.
-    pub(super) fn process_entry<'a>(
+    /// The entries are visited with an explicit stack rather than by
+    /// recursion, so that a large maximum depth can't overflow the call stack.
+    pub(in crate::analyses) fn process_entry<'a>(
         entry: &'a PathsEntry,
         depth: u32,
         paths: usize,
         items: &'a Items,
         opts: &'a Paths,
     ) -> Box<dyn Iterator<Item = TableRow> + 'a> {
-        // Get the row's name and size columns using the current depth.
-        let name = get_indented_name(&entry.name, depth, opts.descending());
-        let (size, size_percent) = if depth == 0 {
-            (
-                Some(entry.size),
-                Some(f64::from(entry.size) / f64::from(items.size()) * 100.0),
-            )
+        // If we are beyond the maximum depth, there is nothing to visit.
+        let mut stack = if depth <= opts.max_depth() {
+            vec![(entry, depth)]
         } else {
-            (None, None)
+            vec![]
         };
 
-        // Create an iterator containing the current entry's table row.
-        let row_iter = iter::once(TableRow {
-            size,
-            size_percent,
-            name,
-        });
-
-        if depth < opts.max_depth() {
-            // Process each child entry, and chain together the resulting iterators.
-            let children_iter = entry
-                .children
-                .iter()
-                .take(paths)
-                .flat_map(move |child_entry| {
-                    process_entry(child_entry, depth + 1, paths, items, opts)
-                });
-            Box::new(row_iter.chain(children_iter))
-        } else if depth == opts.max_depth() {
-            // TODO: Create a summary row, and chain it to the row iterator.
-            Box::new(row_iter)
-        } else {
-            // If we are beyond the maximum depth, return an empty iterator.
-            Box::new(iter::empty())
-        }
+        Box::new(iter::from_fn(move || {
+            let (entry, depth) = stack.pop()?;
+
+            // Visit the first few children next, in order, unless we are at
+            // the maximum depth.
+            // TODO: Create a summary row at the maximum depth.
+            if depth < opts.max_depth() {
+                let children = entry.children.iter().take(paths).rev();
+                stack.extend(children.map(|child| (child, depth + 1)));
+            }
+
+            // Get the row's name and size columns using the current depth.
+            let name = get_indented_name(&entry.name, depth, opts.descending());
+            let (size, size_percent) = if depth == 0 {
+                (
+                    Some(entry.size),
+                    Some(crate::analyses::percent(entry.size, items.size())),
+                )
+            } else {
+                (None, None)
+            };
+
+            Some(TableRow {
+                size,
+                size_percent,
+                name,
+            })
+        }))
     }
 
     /// Given the name of an item, its depth, and the traversal direction,
@@ -182,39 +185,71 @@ mod emit_text_helpers {
 }
 
 /// This module contains helper functions and structs used by the `emit_json`
-/// method in Path's implementation of the `traits::Emit` trait.
+/// method in Path's implementation of the `traits::Emit` trait, and by `top`
+/// to emit retaining paths.
 #[cfg(feature = "emit_json")]
-mod emit_json_helpers {
+pub(in crate::analyses) mod emit_json_helpers {
     use crate::analyses::paths::paths_entry::PathsEntry;
-    use crate::formats::json::Object;
+    use crate::formats::json::{self, Object};
     use std::io;
+    use std::mem;
     use twiggy_ir::Items;
     use twiggy_opt::Paths;
 
-    // Process a paths entry, by adding its name and size to the given JSON object.
-    pub(super) fn process_entry(
-        entry: &PathsEntry,
+    // Add a paths entry's name and size to the given JSON object.
+    fn add_fields(entry: &PathsEntry, obj: &mut Object, items: &Items) -> io::Result<()> {
+        obj.field("name", entry.name.as_str())?;
+        obj.field("shallow_size", entry.size)?;
+        let size_percent = crate::analyses::percent(entry.size, items.size());
+        obj.field("shallow_size_percent", size_percent)
+    }
+
+    // Process a paths entry, by adding its name and size to the given JSON
+    // object, along with its callers. The callers are written with an explicit
+    // stack rather than by recursion, so that a large maximum depth can't
+    // overflow the call stack.
+    pub(in crate::analyses) fn process_entry<'a>(
+        entry: &'a PathsEntry,
         obj: &mut Object,
         depth: u32,
         paths: usize,
         items: &Items,
         opts: &Paths,
     ) -> io::Result<()> {
-        let PathsEntry {
-            name,
-            size,
-            children,
-        } = entry;
-        obj.field("name", name.as_str())?;
-        obj.field("shallow_size", *size)?;
-        let size_percent = f64::from(*size) / f64::from(items.size()) * 100.0;
-        obj.field("shallow_size_percent", size_percent)?;
-
-        let mut callers = obj.array("callers")?;
-        if depth < opts.max_depth() {
-            for child in children.iter().take(paths) {
-                let mut obj = callers.object()?;
-                process_entry(child, &mut obj, depth + 1, paths, items, opts)?;
+        add_fields(entry, obj, items)?;
+
+        // The callers of an entry at the given depth that are written out.
+        let callers = |entry: &'a PathsEntry, depth: u32| {
+            let count = if depth < opts.max_depth() { paths } else { 0 };
+            entry.children.iter().take(count)
+        };
+
+        // Each level of the stack holds the callers left to write at that
+        // depth, and whether any have been written yet. Each caller's object
+        // is left open until its own callers have been written.
+        let w = obj.raw("callers")?;
+        write!(w, "[")?;
+        let mut stack = vec![(callers(entry, depth), false)];
+        while let Some((next, any_written)) = stack.last_mut() {
+            match next.next() {
+                Some(child) => {
+                    if mem::replace(any_written, true) {
+                        write!(w, ",")?;
+                    }
+                    let mut obj = json::object(&mut *w)?;
+                    add_fields(child, &mut obj, items)?;
+                    write!(obj.raw("callers")?, "[")?;
+                    obj.leave_open();
+                    let child_depth = depth + stack.len() as u32;
+                    stack.push((callers(child, child_depth), false));
+                }
+                None => {
+                    stack.pop();
+                    write!(w, "]")?;
+                    if !stack.is_empty() {
+                        write!(w, "}}")?;
+                    }
+                }
             }
         }
 
@@ -237,12 +272,14 @@ mod emit_csv_helpers {
     #[serde(rename_all = "PascalCase")]
     pub(super) struct CsvRecord {
         pub name: String,
-        pub shallow_size: u32,
+        pub shallow_size: u64,
         pub shallow_size_percent: f64,
         pub path: Option<String>,
     }
 
-    // Process a given entry and its children, returning an iterator of CSV records.
+    // Process a given entry and its children, returning an iterator of CSV
+    // records. The entries are visited with an explicit stack rather than by
+    // recursion, so that a large maximum depth can't overflow the call stack.
     pub(super) fn process_entry<'a>(
         entry: &'a PathsEntry,
         depth: u32,
@@ -250,36 +287,30 @@ mod emit_csv_helpers {
         items: &'a Items,
         opts: &'a Paths,
     ) -> Box<dyn Iterator<Item = CsvRecord> + 'a> {
-        let name = entry.name.clone();
-        let shallow_size = entry.size;
-        let shallow_size_percent = f64::from(entry.size) / f64::from(items.size()) * 100.0;
-        let path = get_path(entry);
-
-        // Create an iterator containing the current entry's CSV record.
-        let record_iter = iter::once(CsvRecord {
-            name,
-            shallow_size,
-            shallow_size_percent,
-            path,
-        });
-
-        if depth < opts.max_depth() {
-            // Process each child entry, and chain together the resulting iterators.
-            let children_iter = entry
-                .children
-                .iter()
-                .take(paths)
-                .flat_map(move |child_entry| {
-                    process_entry(child_entry, depth + 1, paths, items, opts)
-                });
-            Box::new(record_iter.chain(children_iter))
-        } else if depth == opts.max_depth() {
-            // Create a summary row, and chain it to the row iterator.
-            Box::new(record_iter)
+        // If we are beyond the maximum depth, there is nothing to visit.
+        let mut stack = if depth <= opts.max_depth() {
+            vec![(entry, depth)]
         } else {
-            // If we are beyond the maximum depth, return an empty iterator.
-            Box::new(iter::empty())
-        }
+            vec![]
+        };
+
+        Box::new(iter::from_fn(move || {
+            let (entry, depth) = stack.pop()?;
+
+            // Visit the first few children next, in order, unless we are at
+            // the maximum depth.
+            if depth < opts.max_depth() {
+                let children = entry.children.iter().take(paths).rev();
+                stack.extend(children.map(|child| (child, depth + 1)));
+            }
+
+            Some(CsvRecord {
+                name: entry.name.clone(),
+                shallow_size: entry.size,
+                shallow_size_percent: crate::analyses::percent(entry.size, items.size()),
+                path: get_path(entry),
+            })
+        }))
     }
 
     // Given a path entry, return the value for its corresponding CsvRecord's `path` field.
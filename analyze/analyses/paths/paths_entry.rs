@@ -1,9 +1,10 @@
 use std::cmp;
+use std::mem;
 
 #[derive(Debug, PartialEq, Eq)]
-pub(super) struct PathsEntry {
+pub(in crate::analyses) struct PathsEntry {
     pub name: String,
-    pub size: u32,
+    pub size: u64,
     pub children: Vec<PathsEntry>,
 }
 
@@ -13,6 +14,17 @@ impl PathsEntry {
     }
 }
 
+impl Drop for PathsEntry {
+    // Drop the children with an explicit stack rather than by recursion, so
+    // that very deep trees of entries can't overflow the call stack.
+    fn drop(&mut self) {
+        let mut stack = mem::take(&mut self.children);
+        while let Some(mut entry) = stack.pop() {
+            stack.append(&mut entry.children);
+        }
+    }
+}
+
 impl PartialOrd for PathsEntry {
     fn partial_cmp(&self, rhs: &Self) -> Option<cmp::Ordering> {
         Some(self.cmp(rhs))
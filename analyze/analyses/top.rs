@@ -1,8 +1,10 @@
+use std::cmp;
 use std::io;
 
+use crate::analyses::paths::{self, PathsEntry};
+use crate::analyses::{categories, groups};
 use crate::formats::json;
 use crate::formats::table::{Align, Table};
-use anyhow::anyhow;
 use csv;
 use serde_derive::Serialize;
 use twiggy_ir as ir;
@@ -11,6 +13,9 @@ use twiggy_traits as traits;
 
 struct Top {
     items: Vec<ir::Id>,
+    // The retaining paths of each of the items that are shown, when asked
+    // for, along with the options to emit them with.
+    retaining_paths: Option<(Vec<Option<PathsEntry>>, opt::Paths)>,
     opts: opt::Top,
 }
 
@@ -19,13 +24,13 @@ impl traits::Emit for Top {
     fn emit_text(&self, items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
         // A struct used to represent a row in the table that will be emitted.
         struct TableRow {
-            size: u32,
+            size: u64,
             size_percent: f64,
-            name: String,
         }
 
         // Helper function used to process an item, and return a struct
-        // representing a row containing its size and name.
+        // representing a row containing its size. Names are only looked up
+        // for the rows that are shown, since large binaries have many items.
         fn process_item(id: ir::Id, items: &ir::Items, retained: bool) -> TableRow {
             let item = &items[id];
             let size = if retained {
@@ -33,27 +38,19 @@ impl traits::Emit for Top {
             } else {
                 item.size()
             };
-            let size_percent = (f64::from(size)) / (f64::from(items.size())) * 100.0;
-            let name = item.name().to_string();
-            TableRow {
-                size,
-                size_percent,
-                name,
-            }
+            let size_percent = super::percent(size, items.size());
+            TableRow { size, size_percent }
         }
 
         // Helper function used to summnarize a sequence of table rows. This is
         // used to generate the remaining summary and total rows. Returns a tuple
         // containing the total size, total size percentage, and number of items.
-        fn summarize_rows(rows: impl Iterator<Item = TableRow>) -> (u32, f64, u32) {
+        fn summarize_rows(rows: impl Iterator<Item = TableRow>) -> (u64, f64, u32) {
             rows.fold(
                 (0, 0.0, 0),
-                |(total_size, total_percent, remaining_count),
-                 TableRow {
-                     size, size_percent, ..
-                 }| {
+                |(total_size, total_percent, remaining_count), TableRow { size, size_percent }| {
                     (
-                        total_size + size,
+                        total_size.saturating_add(size),
                         total_percent + size_percent,
                         remaining_count + 1,
                     )
@@ -73,24 +70,36 @@ impl traits::Emit for Top {
             (Align::Left, "Item".to_string()),
         ]);
 
-        // Process the number of items specified, and add them to the table.
+        // Process the number of items specified, and add them to the table,
+        // each followed by its retaining paths if they were asked for.
         self.items
             .iter()
             .take(max_items)
-            .map(|&id| process_item(id, items, retained))
-            .for_each(
-                |TableRow {
-                     size,
-                     size_percent,
-                     name,
-                 }| {
-                    table.add_row(vec![
-                        size.to_string(),
-                        format!("{:.2}%", size_percent),
-                        name,
-                    ])
-                },
-            );
+            .enumerate()
+            .for_each(|(i, &id)| {
+                let TableRow { size, size_percent } = process_item(id, items, retained);
+                table.add_row(vec![
+                    size.to_string(),
+                    format!("{:.2}%", size_percent),
+                    items.display_name(id).into_owned(),
+                ]);
+
+                if let Some((entries, paths_opts)) = &self.retaining_paths {
+                    let max_paths = paths_opts.max_paths() as usize;
+                    for row in entries[i]
+                        .iter()
+                        .flat_map(|entry| &entry.children)
+                        .take(max_paths)
+                        .flat_map(|child| {
+                            paths::emit_text_helpers::process_entry(
+                                child, 1, max_paths, items, paths_opts,
+                            )
+                        })
+                    {
+                        table.add_row(vec![String::new(), String::new(), row.name]);
+                    }
+                }
+            });
 
         // Find the summary statistics by processing the remaining items.
         let remaining_rows = self
@@ -140,23 +149,57 @@ impl traits::Emit for Top {
         let max_items = self.opts.max_items() as usize;
         let items_iter = self.items.iter();
 
-        for &id in items_iter.take(max_items) {
+        for (i, &id) in items_iter.take(max_items).enumerate() {
             let item = &items[id];
 
             let mut obj = arr.object()?;
-            obj.field("name", item.name())?;
+            obj.field("name", items.display_name(id).as_ref())?;
+            obj.field("kind", item.kind().name())?;
+            emit_kind_details(item.kind(), &mut obj)?;
+
+            if !item.aliases().is_empty() {
+                let mut aliases = obj.array("aliases")?;
+                for alias in item.aliases() {
+                    aliases.elem(alias.as_str())?;
+                }
+            }
 
             let size = item.size();
-            let size_percent = f64::from(size) / f64::from(items.size()) * 100.0;
+            let size_percent = super::percent(size, items.size());
             obj.field("shallow_size", size)?;
             obj.field("shallow_size_percent", size_percent)?;
 
             if self.opts.retained() {
                 let size = items.retained_size(id);
-                let size_percent = f64::from(size) / f64::from(items.size()) * 100.0;
+                let size_percent = super::percent(size, items.size());
                 obj.field("retained_size", size)?;
                 obj.field("retained_size_percent", size_percent)?;
             }
+
+            if !item.file_ranges().is_empty() {
+                let mut ranges = obj.array("file_ranges")?;
+                for range in item.file_ranges() {
+                    let mut range_arr = ranges.array()?;
+                    range_arr.elem(range.start)?;
+                    range_arr.elem(range.end)?;
+                }
+            }
+
+            if let Some(address) = item.address() {
+                obj.field("address", address)?;
+            }
+
+            if let Some((entries, paths_opts)) = &self.retaining_paths {
+                let max_paths = paths_opts.max_paths() as usize;
+                let mut callers = obj.array("retaining_paths")?;
+                let children = entries[i].iter().flat_map(|entry| &entry.children);
+                for child in children.take(max_paths) {
+                    let mut obj = callers.object()?;
+                    paths::emit_json_helpers::process_entry(
+                        child, &mut obj, 1, max_paths, items, paths_opts,
+                    )?;
+                }
+            }
         }
 
         Ok(())
@@ -170,37 +213,55 @@ impl traits::Emit for Top {
         #[serde(rename_all = "PascalCase")]
         struct CsvRecord {
             name: String,
-            shallow_size: u32,
+            kind: &'static str,
+            shallow_size: u64,
             shallow_size_percent: f64,
-            retained_size: Option<u32>,
+            retained_size: Option<u64>,
             retained_size_percent: Option<f64>,
+            file_ranges: String,
+            address: Option<u64>,
+            // Only written with `--retaining-paths`.
+            #[serde(skip_serializing_if = "Option::is_none")]
+            retaining_paths: Option<Option<String>>,
         }
 
         let max_items = self.opts.max_items() as usize;
         let items_iter = self.items.iter();
 
-        for &id in items_iter.take(max_items) {
+        for (i, &id) in items_iter.take(max_items).enumerate() {
             let item = &items[id];
 
             let (shallow_size, shallow_size_percent) = {
                 let size = item.size();
-                let size_percent = f64::from(size) / f64::from(items.size()) * 100.0;
+                let size_percent = super::percent(size, items.size());
                 (size, size_percent)
             };
             let (retained_size, retained_size_percent) = if self.opts.retained() {
                 let size = items.retained_size(id);
-                let size_percent = f64::from(size) / f64::from(items.size()) * 100.0;
+                let size_percent = super::percent(size, items.size());
                 (Some(size), Some(size_percent))
             } else {
                 (None, None)
             };
 
             wtr.serialize(CsvRecord {
-                name: item.name().to_string(),
+                name: items.display_name(id).into_owned(),
+                kind: item.kind().name(),
                 shallow_size,
                 shallow_size_percent,
                 retained_size,
                 retained_size_percent,
+                file_ranges: item
+                    .file_ranges()
+                    .iter()
+                    .map(|range| format!("{}..{}", range.start, range.end))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                address: item.address(),
+                retaining_paths: self
+                    .retaining_paths
+                    .as_ref()
+                    .map(|(entries, _)| entries[i].as_ref().map(csv_retaining_paths)),
             })?;
             wtr.flush()?;
         }
@@ -208,35 +269,117 @@ impl traits::Emit for Top {
     }
 }
 
-/// Run the `top` analysis on the given IR items.
-pub fn top(items: &mut ir::Items, opts: &opt::Top) -> anyhow::Result<Box<dyn traits::Emit>> {
-    if opts.retaining_paths() {
-        return Err(anyhow!("retaining paths are not yet implemented",));
+/// Write out each of an item's retaining paths from its root down to the item,
+/// as in `root -> caller -> item`, separated by semicolons.
+#[cfg(feature = "emit_csv")]
+fn csv_retaining_paths(entry: &PathsEntry) -> String {
+    let mut paths = vec![];
+    let mut stack = vec![(entry, vec![entry.name.as_str()])];
+    while let Some((entry, path)) = stack.pop() {
+        if entry.children.is_empty() {
+            paths.push(path.iter().rev().copied().collect::<Vec<_>>().join(" -> "));
+            continue;
+        }
+        for child in entry.children.iter().rev() {
+            let mut path = path.clone();
+            path.push(child.name.as_str());
+            stack.push((child, path));
+        }
     }
+    paths.join("; ")
+}
 
-    if opts.retained() {
+/// Write the details specific to an item's kind as fields of its JSON object.
+#[cfg(feature = "emit_json")]
+fn emit_kind_details(kind: &ir::ItemKind, obj: &mut json::Object<'_>) -> io::Result<()> {
+    match kind {
+        ir::ItemKind::Data(data) => {
+            if let Some(mode) = data.segment_mode() {
+                obj.field("segment", mode.name())?;
+            }
+        }
+        ir::ItemKind::Global(global) => {
+            obj.field("type", global.ty())?;
+            obj.field("mutable", global.is_mutable())?;
+        }
+        ir::ItemKind::Import(import) => {
+            obj.field("module", import.module())?;
+            obj.field("field", import.field())?;
+            if let Some(kind) = import.external_kind() {
+                obj.field("import_kind", kind.name())?;
+            }
+        }
+        ir::ItemKind::Export(export) => {
+            obj.field("target", export.target().name())?;
+        }
+        ir::ItemKind::Type(ty) => {
+            let mut params = obj.array("params")?;
+            for param in ty.params() {
+                params.elem(param.as_str())?;
+            }
+            drop(params);
+            let mut results = obj.array("results")?;
+            for result in ty.results() {
+                results.elem(result.as_str())?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Run the `top` analysis on the given IR items.
+pub fn top(items: &mut ir::Items, opts: &opt::Top) -> anyhow::Result<Box<dyn traits::Emit>> {
+    categories::check_classified(items, opts.categories(), opts.group_by())?;
+    if opts.retained() || opts.group_by().is_some() {
         items.compute_retained_sizes();
     }
 
     let mut top_items: Vec<_> = items
         .iter()
         .filter(|item| item.id() != items.meta_root())
+        .filter(|item| super::matches_kinds(item, opts.kinds()))
+        .filter(|item| categories::in_categories(items, item.id(), opts.categories()))
         .collect();
 
-    top_items.sort_by(|a, b| {
-        if opts.retained() {
-            items
-                .retained_size(b.id())
-                .cmp(&items.retained_size(a.id()))
+    if let Some(group_by) = opts.group_by() {
+        let ids = top_items.iter().map(|item| item.id());
+        let max_groups = opts.max_items() as usize;
+        let groups = groups::group(items, ids, group_by, opts.retained(), max_groups);
+        return Ok(Box::new(groups) as Box<_>);
+    }
+
+    top_items.sort_by_cached_key(|item| {
+        cmp::Reverse(if opts.retained() {
+            items.retained_size(item.id())
         } else {
-            b.size().cmp(&a.size())
-        }
+            item.size()
+        })
     });
 
     let top_items: Vec<_> = top_items.into_iter().map(|i| i.id()).collect();
 
+    // Only the items that are shown need their retaining paths.
+    let retaining_paths = if opts.retaining_paths() {
+        items.compute_predecessors();
+        let mut paths_opts = opt::Paths::new();
+        paths_opts.set_max_paths(opts.max_paths());
+        paths_opts.set_max_depth(opts.max_depth());
+        let entries = top_items
+            .iter()
+            .take(opts.max_items() as usize)
+            .map(|&id| {
+                paths::shortest_retaining_paths(items, id, opts.max_paths(), opts.max_depth())
+            })
+            .collect();
+        Some((entries, paths_opts))
+    } else {
+        None
+    };
+
     let top = Top {
         items: top_items,
+        retaining_paths,
         opts: opts.clone(),
     };
 
@@ -0,0 +1,221 @@
+use std::io;
+
+#[cfg(feature = "emit_csv")]
+use serde::{self, ser::SerializeStruct};
+
+#[cfg(feature = "emit_json")]
+use crate::formats::json;
+#[cfg(feature = "emit_text")]
+use crate::formats::table::{Align, Table};
+use twiggy_ir as ir;
+use twiggy_opt as opt;
+use twiggy_traits as traits;
+
+#[derive(Debug)]
+struct WhatIf {
+    entries: Vec<WhatIfEntry>,
+}
+
+#[derive(Debug)]
+struct WhatIfEntry {
+    change: String,
+    saved: i64,
+}
+
+#[cfg(feature = "emit_csv")]
+impl serde::Serialize for WhatIfEntry {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("WhatIfEntry", 2)?;
+        state.serialize_field("BytesSaved", &self.saved)?;
+        state.serialize_field("Change", &self.change)?;
+        state.end()
+    }
+}
+
+/// The percentage of `total` that the signed `saved` size is.
+fn saved_percent(saved: i64, total: u64) -> f64 {
+    let percent = super::percent(saved.unsigned_abs(), total);
+    if saved < 0 {
+        -percent
+    } else {
+        percent
+    }
+}
+
+impl traits::Emit for WhatIf {
+    #[cfg(feature = "emit_text")]
+    fn emit_text(&self, items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+        let mut table = Table::with_header(vec![
+            (Align::Right, "Bytes Saved".to_string()),
+            (Align::Right, "Saved %".to_string()),
+            (Align::Left, "Change".to_string()),
+        ]);
+
+        for entry in &self.entries {
+            table.add_row(vec![
+                entry.saved.to_string(),
+                format!("{:.2}%", saved_percent(entry.saved, items.size())),
+                entry.change.clone(),
+            ]);
+        }
+
+        write!(dest, "{}", &table)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_json")]
+    fn emit_json(&self, items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+        let mut arr = json::array(dest)?;
+
+        for entry in &self.entries {
+            let mut obj = arr.object()?;
+            obj.field("change", entry.change.as_str())?;
+            obj.field("bytes_saved", entry.saved)?;
+            obj.field("saved_percent", saved_percent(entry.saved, items.size()))?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_csv")]
+    fn emit_csv(&self, _items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+        let mut wtr = csv::Writer::from_writer(dest);
+
+        for entry in &self.entries {
+            wtr.serialize(entry)?;
+            wtr.flush()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Find the ids of the items with the given name.
+fn find_items(items: &ir::Items, name: &str) -> anyhow::Result<Vec<ir::Id>> {
+    let ids: Vec<ir::Id> = items
+        .get_items_by_name(name)
+        .into_iter()
+        .map(|item| item.id())
+        .collect();
+    if ids.is_empty() {
+        anyhow::bail!("Could not find an item named `{}`", name);
+    }
+    Ok(ids)
+}
+
+/// Resolve an edge given as `from->to` into the changes that remove it. Item
+/// names may themselves contain `->`, so every split is tried until both
+/// sides name items.
+fn remove_edge(items: &ir::Items, edge: &str) -> anyhow::Result<(String, ir::Changes)> {
+    for (i, _) in edge.match_indices("->") {
+        let from = edge[..i].trim();
+        let to = edge[i + 2..].trim();
+        let froms = items.get_items_by_name(from);
+        let tos = items.get_items_by_name(to);
+        if froms.is_empty() || tos.is_empty() {
+            continue;
+        }
+
+        let mut changes = ir::Changes::new();
+        let mut found = false;
+        for from_item in &froms {
+            for to_item in &tos {
+                if items.neighbors(from_item.id()).any(|id| id == to_item.id()) {
+                    changes.remove_edge(from_item.id(), to_item.id());
+                    found = true;
+                }
+            }
+        }
+        if !found {
+            anyhow::bail!("There is no edge from `{}` to `{}`", from, to);
+        }
+        return Ok((format!("remove edge {} -> {}", from, to), changes));
+    }
+
+    anyhow::bail!(
+        "Could not find the items of the edge `{}`; edges are given as `from->to`",
+        edge
+    )
+}
+
+/// Report how many bytes each of a set of hypothetical changes to the graph
+/// would save.
+pub fn whatif(items: &mut ir::Items, opts: &opt::WhatIf) -> anyhow::Result<Box<dyn traits::Emit>> {
+    let mut changes: Vec<(String, ir::Changes)> = Vec::new();
+
+    for export in opts.removed_exports() {
+        let name = format!("export \"{}\"", export);
+        let ids = find_items(items, &name)
+            .map_err(|_| anyhow::anyhow!("Could not find an export named `{}`", export))?;
+        let mut change = ir::Changes::new();
+        for id in ids {
+            change.remove_item(id);
+        }
+        changes.push((format!("remove {}", name), change));
+    }
+
+    for name in opts.removed_items() {
+        let mut change = ir::Changes::new();
+        for id in find_items(items, name)? {
+            change.remove_item(id);
+        }
+        changes.push((format!("remove item {}", name), change));
+    }
+
+    for edge in opts.removed_edges() {
+        changes.push(remove_edge(items, edge)?);
+    }
+
+    for name in opts.added_roots() {
+        let mut change = ir::Changes::new();
+        for id in find_items(items, name)? {
+            change.add_root(id);
+        }
+        changes.push((format!("add root {}", name), change));
+    }
+
+    for name in opts.removed_roots() {
+        let mut change = ir::Changes::new();
+        for id in find_items(items, name)? {
+            change.remove_root(id);
+        }
+        changes.push((format!("remove root {}", name), change));
+    }
+
+    if changes.is_empty() {
+        anyhow::bail!(
+            "No changes given; use --remove-export, --remove-item, --remove-edge, \
+             --add-root or --remove-root"
+        );
+    }
+
+    let before = items.reachable_size();
+    let saved = |changes: &ir::Changes| {
+        let after = items.with_changes(changes).reachable_size();
+        super::size_delta(after, before)
+    };
+
+    let mut entries: Vec<WhatIfEntry> = changes
+        .iter()
+        .map(|(change, c)| WhatIfEntry {
+            change: change.clone(),
+            saved: saved(c),
+        })
+        .collect();
+
+    if changes.len() > 1 {
+        let mut all = ir::Changes::new();
+        for (_, c) in &changes {
+            all.extend(c);
+        }
+        entries.push(WhatIfEntry {
+            change: format!("Σ [All {} Changes]", changes.len()),
+            saved: saved(&all),
+        });
+    }
+
+    Ok(Box::new(WhatIf { entries }) as Box<_>)
+}
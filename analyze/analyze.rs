@@ -4,8 +4,21 @@
 #![deny(missing_debug_implementations)]
 
 mod analyses;
+mod budgets;
 mod formats;
+mod rules;
 
 pub use analyses::{
-    diff::diff, dominators::dominators, garbage::garbage, monos::monos, paths::paths, top::top,
+    check::{check, CheckReport},
+    crates::crates,
+    diff::diff,
+    dominators::dominators,
+    garbage::garbage,
+    history::history,
+    monos::monos,
+    paths::paths,
+    top::top,
+    whatif::whatif,
 };
+pub use budgets::Budgets;
+pub use rules::Rules;
@@ -0,0 +1,243 @@
+//! Budgets for the sizes of a binary and its parts, for `twiggy check` to
+//! hold binaries to.
+//!
+//! Budgets are written in TOML, as a list of `[[budget]]` tables:
+//!
+//! ```toml
+//! [[budget]]
+//! description = "the whole binary"
+//! max-size = "200 KiB"
+//! max-growth-percent = 5.0
+//!
+//! [[budget]]
+//! section = "code"
+//! max-size = 150000
+//!
+//! [[budget]]
+//! description = "formatting machinery"
+//! name = "core::fmt::*"
+//! max-retained-size = "20 KiB"
+//! max-growth = "1 KiB"
+//! ```
+//!
+//! Each budget covers the items that its patterns match, in the same way that
+//! the patterns of rules match items: `name` or `name-regex`, `kind`, and
+//! `section` or `section-regex`. A budget without patterns covers the whole
+//! binary. Its limits are on the total shallow size of those items
+//! (`max-size`), their retained size (`max-retained-size`), and how much their
+//! shallow size grew since a baseline, in bytes (`max-growth`) or as a
+//! percentage of the baseline's size (`max-growth-percent`). Sizes are given in
+//! bytes, or as strings with a unit: `B`, `KB`, `KiB`, `MB`, `MiB`, `GB` or
+//! `GiB`.
+
+use anyhow::anyhow;
+use serde_derive::Deserialize;
+
+use crate::rules::Selector;
+
+/// A set of budgets for the sizes of a binary and its parts.
+#[derive(Debug)]
+pub struct Budgets {
+    budgets: Vec<Budget>,
+}
+
+/// A budget: the items that it covers, and the limits on their sizes.
+#[derive(Debug)]
+pub(crate) struct Budget {
+    pub(crate) description: String,
+    pub(crate) selector: Selector,
+    pub(crate) limits: Vec<Limit>,
+}
+
+/// A limit on the size of the items a budget covers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Limit {
+    /// The most bytes that the items may take up themselves.
+    Size(u64),
+    /// The most bytes that the items may retain.
+    RetainedSize(u64),
+    /// The most bytes that the items may have grown by since the baseline.
+    Growth(u64),
+    /// The most that the items may have grown by since the baseline, as a
+    /// percentage of their size in the baseline.
+    GrowthPercent(f64),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BudgetsFile {
+    #[serde(default)]
+    budget: Vec<BudgetDefinition>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+struct BudgetDefinition {
+    description: Option<String>,
+    name: Option<String>,
+    name_regex: Option<String>,
+    kind: Option<String>,
+    section: Option<String>,
+    section_regex: Option<String>,
+    max_size: Option<SizeDefinition>,
+    max_retained_size: Option<SizeDefinition>,
+    max_growth: Option<SizeDefinition>,
+    max_growth_percent: Option<f64>,
+}
+
+/// A size, in bytes or as a string with a unit.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SizeDefinition {
+    Bytes(u64),
+    WithUnit(String),
+}
+
+impl SizeDefinition {
+    fn bytes(self) -> anyhow::Result<u64> {
+        let size = match self {
+            SizeDefinition::Bytes(bytes) => return Ok(bytes),
+            SizeDefinition::WithUnit(size) => size,
+        };
+        let invalid = || {
+            anyhow!(
+                "invalid size `{}`, expected a number of bytes with an optional \
+                 unit of B, KB, KiB, MB, MiB, GB or GiB",
+                size
+            )
+        };
+        let split = size
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(size.len());
+        let (number, unit) = size.split_at(split);
+        let (whole, fraction) = match number.find('.') {
+            Some(point) => (&number[..point], &number[point + 1..]),
+            None => (number, ""),
+        };
+        if whole.is_empty() && fraction.is_empty() || fraction.contains('.') {
+            return Err(invalid());
+        }
+        let unit: u64 = match unit.trim() {
+            "" | "B" => 1,
+            "KB" => 1000,
+            "KiB" => 1 << 10,
+            "MB" => 1000 * 1000,
+            "MiB" => 1 << 20,
+            "GB" => 1000 * 1000 * 1000,
+            "GiB" => 1 << 30,
+            _ => return Err(invalid()),
+        };
+
+        // Multiply the digits after the point by the unit from the right,
+        // carrying into the whole bytes, and round to the nearest byte on the
+        // first digit left over.
+        let mut carry = 0;
+        let mut round_up = false;
+        for digit in fraction.bytes().rev() {
+            let product = u64::from(digit - b'0') * unit + carry;
+            round_up = product % 10 >= 5;
+            carry = product / 10;
+        }
+        let too_large = || anyhow!("size `{}` is too large, it must fit in a u64", size);
+        whole
+            .bytes()
+            .try_fold(0u64, |bytes, digit| {
+                bytes.checked_mul(10)?.checked_add(u64::from(digit - b'0'))
+            })
+            .and_then(|whole| whole.checked_mul(unit))
+            .and_then(|bytes| bytes.checked_add(carry))
+            .and_then(|bytes| bytes.checked_add(u64::from(round_up)))
+            .ok_or_else(too_large)
+    }
+}
+
+impl Budgets {
+    /// Parse budgets from the contents of a TOML budgets file.
+    pub fn parse(toml: &str) -> anyhow::Result<Budgets> {
+        let file: BudgetsFile = toml::from_str(toml)?;
+        let budgets = file
+            .budget
+            .into_iter()
+            .enumerate()
+            .map(|(i, budget)| {
+                let owner = match budget.description {
+                    Some(ref description) => format!("the budget for {}", description),
+                    None => format!("budget #{}", i + 1),
+                };
+                let description = match budget.description {
+                    Some(ref description) => description.clone(),
+                    None => describe(&budget),
+                };
+                let selector = Selector::new(
+                    &owner,
+                    budget.name,
+                    budget.name_regex,
+                    budget.kind,
+                    budget.section,
+                    budget.section_regex,
+                )?;
+
+                let mut limits = vec![];
+                let size =
+                    |size: SizeDefinition| size.bytes().map_err(|e| anyhow!("{}: {}", owner, e));
+                if let Some(max_size) = budget.max_size {
+                    limits.push(Limit::Size(size(max_size)?));
+                }
+                if let Some(max_retained_size) = budget.max_retained_size {
+                    limits.push(Limit::RetainedSize(size(max_retained_size)?));
+                }
+                if let Some(max_growth) = budget.max_growth {
+                    limits.push(Limit::Growth(size(max_growth)?));
+                }
+                if let Some(max_growth_percent) = budget.max_growth_percent {
+                    limits.push(Limit::GrowthPercent(max_growth_percent));
+                }
+                if limits.is_empty() {
+                    return Err(anyhow!(
+                        "{} sets no limits; give it one of `max-size`, \
+                         `max-retained-size`, `max-growth` or `max-growth-percent`",
+                        owner
+                    ));
+                }
+
+                Ok(Budget {
+                    description,
+                    selector,
+                    limits,
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Budgets { budgets })
+    }
+
+    /// Iterate over the budgets, in the order they were given.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &Budget> {
+        self.budgets.iter()
+    }
+}
+
+/// Describe the items that a budget covers, for budgets that weren't given a
+/// description.
+fn describe(budget: &BudgetDefinition) -> String {
+    let mut parts = vec![];
+    if let Some(ref kind) = budget.kind {
+        parts.push(format!("{} items", kind));
+    }
+    if let Some(ref name) = budget.name {
+        parts.push(format!("named `{}`", name));
+    }
+    if let Some(ref name_regex) = budget.name_regex {
+        parts.push(format!("matching `{}`", name_regex));
+    }
+    if let Some(ref section) = budget.section {
+        parts.push(format!("in section `{}`", section));
+    }
+    if let Some(ref section_regex) = budget.section_regex {
+        parts.push(format!("in sections matching `{}`", section_regex));
+    }
+    match parts.first() {
+        None => "the whole binary".to_string(),
+        Some(first) if first.ends_with(" items") => parts.join(" "),
+        Some(_) => format!("items {}", parts.join(" ")),
+    }
+}
@@ -3,12 +3,13 @@
 #![allow(dead_code)]
 
 use std::io;
+use std::mem;
 
 pub trait JsonPrimitive {
     fn json_primitive(&self, w: &mut dyn io::Write) -> io::Result<()>;
 }
 
-impl<'a> JsonPrimitive for &'a str {
+impl JsonPrimitive for &str {
     fn json_primitive(&self, w: &mut dyn io::Write) -> io::Result<()> {
         write!(w, "\"")?;
         for c in self.chars() {
@@ -35,7 +36,25 @@ impl JsonPrimitive for u32 {
     }
 }
 
-pub fn array(w: &mut dyn io::Write) -> io::Result<Array> {
+impl JsonPrimitive for u64 {
+    fn json_primitive(&self, w: &mut dyn io::Write) -> io::Result<()> {
+        write!(w, "{}", self)
+    }
+}
+
+impl JsonPrimitive for i64 {
+    fn json_primitive(&self, w: &mut dyn io::Write) -> io::Result<()> {
+        write!(w, "{}", self)
+    }
+}
+
+impl JsonPrimitive for bool {
+    fn json_primitive(&self, w: &mut dyn io::Write) -> io::Result<()> {
+        write!(w, "{}", self)
+    }
+}
+
+pub fn array(w: &mut dyn io::Write) -> io::Result<Array<'_>> {
     write!(w, "[")?;
     Ok(Array {
         w,
@@ -43,7 +62,7 @@ pub fn array(w: &mut dyn io::Write) -> io::Result<Array> {
     })
 }
 
-pub fn object(w: &mut dyn io::Write) -> io::Result<Object> {
+pub fn object(w: &mut dyn io::Write) -> io::Result<Object<'_>> {
     write!(w, "{{")?;
     Ok(Object {
         w,
@@ -71,12 +90,12 @@ impl<'a> Array<'a> {
         Ok(())
     }
 
-    pub fn object(&mut self) -> io::Result<Object> {
+    pub fn object(&mut self) -> io::Result<Object<'_>> {
         self.comma()?;
         object(&mut *self.w)
     }
 
-    pub fn array(&mut self) -> io::Result<Array> {
+    pub fn array(&mut self) -> io::Result<Array<'_>> {
         self.comma()?;
         array(&mut *self.w)
     }
@@ -114,7 +133,7 @@ impl<'a> Object<'a> {
         write!(self.w, ":")
     }
 
-    pub fn object<S>(&mut self, name: S) -> io::Result<Object>
+    pub fn object<S>(&mut self, name: S) -> io::Result<Object<'_>>
     where
         S: AsRef<str>,
     {
@@ -122,7 +141,7 @@ impl<'a> Object<'a> {
         object(&mut *self.w)
     }
 
-    pub fn array<S>(&mut self, name: S) -> io::Result<Array>
+    pub fn array<S>(&mut self, name: S) -> io::Result<Array<'_>>
     where
         S: AsRef<str>,
     {
@@ -130,6 +149,23 @@ impl<'a> Object<'a> {
         array(&mut *self.w)
     }
 
+    /// Start a field whose value the caller writes out itself, for values
+    /// nested too deeply to write with `object` and `array`.
+    pub fn raw<S>(&mut self, name: S) -> io::Result<&mut dyn io::Write>
+    where
+        S: AsRef<str>,
+    {
+        self.comma_and_name(name)?;
+        Ok(&mut *self.w)
+    }
+
+    /// Stop writing this object without closing it, for a caller that closes
+    /// it itself by writing `}`, e.g. after the contents of a `raw` field that
+    /// are nested too deeply to write with `object` and `array`.
+    pub fn leave_open(self) {
+        mem::forget(self);
+    }
+
     pub fn field<S, P>(&mut self, name: S, val: P) -> io::Result<()>
     where
         S: AsRef<str>,
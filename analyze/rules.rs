@@ -0,0 +1,202 @@
+//! Rules for classifying items into named categories, such as "std" or "panic
+//! machinery", by patterns over their names, kinds and sections.
+//!
+//! Rules are written in TOML, as a list of `[[rule]]` tables:
+//!
+//! ```toml
+//! [[rule]]
+//! category = "panic machinery"
+//! name = "core::panicking::*"
+//!
+//! [[rule]]
+//! category = "std"
+//! name-regex = "^(std|core|alloc)::"
+//!
+//! [[rule]]
+//! category = "data"
+//! kind = "data"
+//! ```
+//!
+//! Each rule has a `category`, and may match items by a glob over their name
+//! (`name`) or a regular expression searched for in it (`name-regex`), by
+//! their kind (`kind`), and by a glob or regular expression over the name of
+//! the section they are in (`section` or `section-regex`). A rule matches the
+//! items that every one of its patterns matches, and the first rule to match
+//! an item puts it in its category.
+
+use anyhow::anyhow;
+use regex::Regex;
+use serde_derive::Deserialize;
+
+use twiggy_ir as ir;
+
+/// A set of rules for classifying items into categories.
+#[derive(Debug)]
+pub struct Rules {
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug)]
+struct Rule {
+    category: String,
+    selector: Selector,
+}
+
+/// Patterns over the names, kinds and sections of items, as rules and budgets
+/// give them. An item matches if every pattern that is given matches it.
+#[derive(Debug)]
+pub(crate) struct Selector {
+    name: Option<Regex>,
+    kind: Option<ir::ItemKindTag>,
+    section: Option<Regex>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RulesFile {
+    #[serde(default)]
+    rule: Vec<RuleDefinition>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+struct RuleDefinition {
+    category: String,
+    name: Option<String>,
+    name_regex: Option<String>,
+    kind: Option<String>,
+    section: Option<String>,
+    section_regex: Option<String>,
+}
+
+/// Compile a glob, in which `*` matches any run of characters and `?` any one
+/// character, into a regular expression that matches the whole string.
+fn glob_to_regex(glob: &str) -> anyhow::Result<Regex> {
+    let mut pattern = String::from("^");
+    let mut literal = String::new();
+    for ch in glob.chars() {
+        match ch {
+            '*' | '?' => {
+                pattern.push_str(&regex::escape(&literal));
+                literal.clear();
+                pattern.push_str(if ch == '*' { ".*" } else { "." });
+            }
+            _ => literal.push(ch),
+        }
+    }
+    pattern.push_str(&regex::escape(&literal));
+    pattern.push('$');
+    Ok(Regex::new(&pattern)?)
+}
+
+/// Compile the pattern given either as a glob or as a regular expression.
+fn pattern(
+    owner: &str,
+    field: &str,
+    glob: Option<String>,
+    regex: Option<String>,
+) -> anyhow::Result<Option<Regex>> {
+    match (glob, regex) {
+        (Some(_), Some(_)) => Err(anyhow!(
+            "{} has both `{}` and `{}-regex`",
+            owner,
+            field,
+            field
+        )),
+        (Some(glob), None) => Ok(Some(glob_to_regex(&glob)?)),
+        (None, Some(regex)) => Ok(Some(Regex::new(&regex)?)),
+        (None, None) => Ok(None),
+    }
+}
+
+impl Selector {
+    /// Compile the given patterns. `owner` describes the rule or budget that
+    /// they belong to, for error messages.
+    pub(crate) fn new(
+        owner: &str,
+        name: Option<String>,
+        name_regex: Option<String>,
+        kind: Option<String>,
+        section: Option<String>,
+        section_regex: Option<String>,
+    ) -> anyhow::Result<Selector> {
+        let kind = match kind {
+            Some(kind) => Some(
+                kind.parse()
+                    .map_err(|_| anyhow!("{} has unknown kind `{}`", owner, kind))?,
+            ),
+            None => None,
+        };
+        Ok(Selector {
+            name: pattern(owner, "name", name, name_regex)?,
+            kind,
+            section: pattern(owner, "section", section, section_regex)?,
+        })
+    }
+
+    /// Does this selector have no patterns, and so match every item?
+    pub(crate) fn is_empty(&self) -> bool {
+        self.name.is_none() && self.kind.is_none() && self.section.is_none()
+    }
+
+    /// Does every pattern of this selector match the given item?
+    pub(crate) fn matches(&self, item: &ir::Item) -> bool {
+        self.name
+            .as_ref()
+            .is_none_or(|name| name.is_match(item.name()))
+            && self.kind.is_none_or(|kind| kind == item.kind().tag())
+            && self
+                .section
+                .as_ref()
+                .is_none_or(|section| item.section().is_some_and(|s| section.is_match(s)))
+    }
+}
+
+impl Rules {
+    /// Parse rules from the contents of a TOML rules file.
+    pub fn parse(toml: &str) -> anyhow::Result<Rules> {
+        let file: RulesFile = toml::from_str(toml)?;
+        let rules = file
+            .rule
+            .into_iter()
+            .map(|rule| {
+                let owner = format!("the rule for category `{}`", rule.category);
+                Ok(Rule {
+                    selector: Selector::new(
+                        &owner,
+                        rule.name,
+                        rule.name_regex,
+                        rule.kind,
+                        rule.section,
+                        rule.section_regex,
+                    )?,
+                    category: rule.category,
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Rules { rules })
+    }
+
+    /// Get the category of the first rule that matches the given item, if
+    /// any do.
+    pub fn category(&self, item: &ir::Item) -> Option<&str> {
+        self.rules
+            .iter()
+            .find(|rule| rule.selector.matches(item))
+            .map(|rule| rule.category.as_str())
+    }
+
+    /// Put each of the given items in the category of the first rule that
+    /// matches it.
+    pub fn classify(&self, items: &mut ir::Items) {
+        let meta_root = items.meta_root();
+        let categories: Vec<(ir::Id, &str)> = items
+            .iter()
+            .filter(|item| item.id() != meta_root)
+            .filter_map(|item| Some((item.id(), self.category(item)?)))
+            .collect();
+        for (id, category) in categories {
+            items.set_category(id, category);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! Dense, index-based adjacency lists for the IR graph.
+
+use std::ops;
+
+/// A list of rows stored in compressed sparse row form: the values of every
+/// row are kept next to each other in one vector, with the row boundaries in
+/// another.
+#[derive(Clone, Debug)]
+pub(crate) struct Csr<T> {
+    // Row `i` is `values[starts[i]..starts[i + 1]]`.
+    starts: Vec<usize>,
+    values: Vec<T>,
+}
+
+impl<T> Csr<T> {
+    /// Build from the given rows, in order.
+    pub(crate) fn from_rows<R, I>(rows: R) -> Csr<T>
+    where
+        R: IntoIterator<Item = I>,
+        I: IntoIterator<Item = T>,
+    {
+        let mut starts = vec![0];
+        let mut values = vec![];
+        for row in rows {
+            values.extend(row);
+            starts.push(values.len());
+        }
+        Csr { starts, values }
+    }
+
+    /// Build `rows` rows from `(row, value)` pairs, keeping the order the
+    /// pairs come in within each row.
+    pub(crate) fn from_pairs<P>(rows: usize, pairs: P) -> Csr<T>
+    where
+        P: Iterator<Item = (usize, T)> + Clone,
+        T: Copy + Default,
+    {
+        let mut starts = vec![0; rows + 1];
+        for (row, _) in pairs.clone() {
+            starts[row + 1] += 1;
+        }
+        for i in 0..rows {
+            starts[i + 1] += starts[i];
+        }
+
+        let mut next = starts.clone();
+        let mut values = vec![T::default(); starts[rows]];
+        for (row, value) in pairs {
+            values[next[row]] = value;
+            next[row] += 1;
+        }
+        Csr { starts, values }
+    }
+
+    /// The number of rows.
+    pub(crate) fn len(&self) -> usize {
+        self.starts.len() - 1
+    }
+}
+
+impl<T> ops::Index<usize> for Csr<T> {
+    type Output = [T];
+
+    #[inline]
+    fn index(&self, row: usize) -> &[T] {
+        &self.values[self.starts[row]..self.starts[row + 1]]
+    }
+}
@@ -0,0 +1,87 @@
+//! Dominators over the dense form of the IR graph.
+//!
+//! This is the "simple, fast" algorithm from Cooper, Harvey and Kennedy's "A
+//! Simple, Fast Dominance Algorithm", working directly on item indices.
+
+use super::csr::Csr;
+use super::EdgeKind;
+
+/// Marks an item without an immediate dominator, because it is either the root
+/// or unreachable from it.
+pub(crate) const NONE: u32 = u32::MAX;
+
+/// Compute the immediate dominator of every item reachable from `root`.
+/// Unreachable items, and the root itself, get `NONE`.
+pub(crate) fn immediate_dominators(
+    edges: &Csr<(u32, EdgeKind)>,
+    predecessors: &Csr<u32>,
+    root: u32,
+) -> Vec<u32> {
+    let n = edges.len();
+
+    // Number the reachable items in post order, without recursing so that
+    // long chains can't overflow the stack.
+    let mut post_order_index = vec![NONE; n];
+    let mut post_order = Vec::with_capacity(n);
+    let mut visited = vec![false; n];
+    let mut stack = vec![(root, 0)];
+    visited[root as usize] = true;
+    while let Some((node, next)) = stack.last_mut() {
+        let node = *node;
+        match edges[node as usize].get(*next) {
+            Some(&(succ, _)) => {
+                *next += 1;
+                if !visited[succ as usize] {
+                    visited[succ as usize] = true;
+                    stack.push((succ, 0));
+                }
+            }
+            None => {
+                post_order_index[node as usize] = post_order.len() as u32;
+                post_order.push(node);
+                stack.pop();
+            }
+        }
+    }
+
+    let intersect = |idoms: &[u32], mut a: u32, mut b: u32| {
+        while a != b {
+            while post_order_index[a as usize] < post_order_index[b as usize] {
+                a = idoms[a as usize];
+            }
+            while post_order_index[b as usize] < post_order_index[a as usize] {
+                b = idoms[b as usize];
+            }
+        }
+        a
+    };
+
+    let mut idoms = vec![NONE; n];
+    idoms[root as usize] = root;
+    let mut changed = true;
+    while changed {
+        changed = false;
+        // Reverse post order, skipping the root, which comes last in post
+        // order.
+        for &node in post_order.iter().rev().skip(1) {
+            let new_idom = predecessors[node as usize]
+                .iter()
+                .copied()
+                .filter(|&pred| idoms[pred as usize] != NONE)
+                .fold(NONE, |idom, pred| {
+                    if idom == NONE {
+                        pred
+                    } else {
+                        intersect(&idoms, pred, idom)
+                    }
+                });
+            if idoms[node as usize] != new_idom {
+                idoms[node as usize] = new_idom;
+                changed = true;
+            }
+        }
+    }
+
+    idoms[root as usize] = NONE;
+    idoms
+}
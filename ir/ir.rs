@@ -3,25 +3,37 @@
 #![deny(missing_docs)]
 #![deny(missing_debug_implementations)]
 
+mod csr;
+mod dominators;
 mod graph_impl;
+mod snapshot;
+mod whatif;
 
+pub use snapshot::{Snapshot, SNAPSHOT_VERSION};
+pub use whatif::Changes;
+
+use anyhow::anyhow;
+use csr::Csr;
 use frozen::Frozen;
+use serde_derive::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::cmp;
-use std::collections::btree_map;
 use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
 use std::ops;
 use std::slice;
-use std::u32;
+use std::str::FromStr;
 
 /// Build up a a set of `Items`.
 #[derive(Debug)]
 pub struct ItemsBuilder {
-    size: u32,
-    size_added: u32,
+    size: u64,
+    size_added: u64,
     parsed: BTreeSet<Id>,
-    items: BTreeMap<Id, Item>,
-    edges: BTreeMap<Id, BTreeSet<Id>>,
+    items: Vec<Item>,
+    edges: BTreeMap<Id, BTreeSet<(Id, EdgeKind)>>,
     roots: BTreeSet<Id>,
+    section_names: BTreeMap<u32, String>,
 
     // Maps the offset some data begins at to its IR item's identifier, and the
     // byte length of the data.
@@ -30,7 +42,7 @@ pub struct ItemsBuilder {
 
 impl ItemsBuilder {
     /// Construct a new builder, with the given size.
-    pub fn new(size: u32) -> ItemsBuilder {
+    pub fn new(size: u64) -> ItemsBuilder {
         ItemsBuilder {
             size,
             size_added: 0,
@@ -38,6 +50,7 @@ impl ItemsBuilder {
             items: Default::default(),
             edges: Default::default(),
             roots: Default::default(),
+            section_names: Default::default(),
             data: Default::default(),
         }
     }
@@ -46,8 +59,8 @@ impl ItemsBuilder {
     /// assigned.
     pub fn add_item(&mut self, item: Item) -> Id {
         let id = item.id;
-        self.size_added += item.size;
-        self.items.insert(id, item);
+        self.size_added = self.size_added.saturating_add(item.size);
+        self.items.push(item);
         let old_value = self.parsed.insert(id);
         assert!(
             old_value,
@@ -65,16 +78,21 @@ impl ItemsBuilder {
         id
     }
 
-    /// Add an edge between the given keys that have already been parsed into
-    /// items.
-    pub fn add_edge(&mut self, from: Id, to: Id) {
-        debug_assert!(self.items.contains_key(&from), "`from` is not known");
-        debug_assert!(self.items.contains_key(&to), "`to` is not known");
+    /// Add an edge of the given kind between the given keys that have already
+    /// been parsed into items.
+    pub fn add_edge(&mut self, from: Id, to: Id, kind: EdgeKind) {
+        debug_assert!(self.parsed.contains(&from), "`from` is not known");
+        debug_assert!(self.parsed.contains(&to), "`to` is not known");
+
+        self.edges.entry(from).or_default().insert((to, kind));
+    }
 
-        self.edges
-            .entry(from)
-            .or_insert_with(BTreeSet::new)
-            .insert(to);
+    /// Name the section with the given index. Items in that section that
+    /// weren't given a section of their own are placed in it when the graph
+    /// is finished.
+    pub fn name_section<S: Into<String>>(&mut self, section: usize, name: S) {
+        assert!(section < u32::MAX as usize);
+        self.section_names.insert(section as u32, name.into());
     }
 
     /// Add a range of static data and the `Id` that defines it.
@@ -101,8 +119,33 @@ impl ItemsBuilder {
             )
     }
 
+    /// Get the parts of the given range of the input file that aren't in any
+    /// of the items added so far.
+    pub fn uncovered_file_ranges(&self, range: ops::Range<u64>) -> Vec<ops::Range<u64>> {
+        let mut covered: Vec<&ops::Range<u64>> = self
+            .items
+            .iter()
+            .flat_map(|item| item.file_ranges())
+            .filter(|covered| covered.start < range.end && covered.end > range.start)
+            .collect();
+        covered.sort_by_key(|covered| covered.start);
+
+        let mut uncovered = vec![];
+        let mut start = range.start;
+        for covered in covered {
+            if covered.start > start {
+                uncovered.push(start..covered.start);
+            }
+            start = cmp::max(start, covered.end);
+        }
+        if start < range.end {
+            uncovered.push(start..range.end);
+        }
+        uncovered
+    }
+
     /// Return the size of all added items so far
-    pub fn size_added(&self) -> u32 {
+    pub fn size_added(&self) -> u64 {
         self.size_added
     }
 
@@ -110,23 +153,73 @@ impl ItemsBuilder {
     pub fn finish(mut self) -> Items {
         let meta_root_id = Id::root();
         let meta_root = Item::new(meta_root_id, "<meta root>", 0, Misc::new());
-        self.items.insert(meta_root_id, meta_root);
-        self.edges.insert(meta_root_id, self.roots.clone());
+        for item in &mut self.items {
+            if item.section.is_none() {
+                item.section = self.section_names.get(&item.id.0).cloned();
+            }
+        }
+        self.items.push(meta_root);
+        self.items.sort_unstable_by_key(|item| item.id);
+        self.edges.insert(
+            meta_root_id,
+            self.roots
+                .iter()
+                .map(|&root| (root, EdgeKind::Root))
+                .collect(),
+        );
+
+        let mut names: BTreeMap<String, Vec<Id>> = BTreeMap::new();
+        let mut aliases: BTreeMap<String, Vec<Id>> = BTreeMap::new();
+        for item in self.items.iter().filter(|item| item.id != meta_root_id) {
+            names
+                .entry(item.name().to_string())
+                .or_default()
+                .push(item.id);
+            for alias in item.aliases() {
+                aliases.entry(alias.clone()).or_default().push(item.id);
+            }
+        }
+
+        assert!(
+            self.items.len() < dominators::NONE as usize,
+            "there should be fewer than 2^32 - 1 items"
+        );
+        let ids: Vec<Id> = self.items.iter().map(|item| item.id).collect();
+        let position = |id: &Id| {
+            ids.binary_search(id)
+                .expect("edges should only refer to known items") as u32
+        };
+        let mut old_edges = std::mem::take(&mut self.edges).into_iter().peekable();
+        let edges = Csr::from_rows(ids.iter().map(|id| {
+            while old_edges.next_if(|(from, _)| from < id).is_some() {}
+            old_edges
+                .next_if(|(from, _)| from == id)
+                .into_iter()
+                .flat_map(|(_, tos)| tos)
+                .map(|(to, kind)| (position(&to), kind))
+        }));
+
+        let mut name_suffixes = vec![0; ids.len()];
+        for group in names.values().filter(|group| group.len() > 1) {
+            for (n, id) in group.iter().enumerate() {
+                name_suffixes[position(id) as usize] = n as u32 + 1;
+            }
+        }
 
         Items {
             size: self.size,
             dominator_tree: None,
             retained_sizes: None,
+            category_names: vec![],
+            categories: None,
             predecessors: None,
             immediate_dominators: None,
             items: Frozen::freeze(self.items),
-            edges: Frozen::freeze(
-                self.edges
-                    .into_iter()
-                    .map(|(from, tos)| (from, tos.into_iter().collect::<Vec<_>>()))
-                    .collect(),
-            ),
-            roots: Frozen::freeze(self.roots),
+            edges: Frozen::freeze(edges),
+            ids: Frozen::freeze(ids),
+            names: Frozen::freeze(names),
+            aliases: Frozen::freeze(aliases),
+            name_suffixes: Frozen::freeze(name_suffixes),
             meta_root: meta_root_id,
         }
     }
@@ -138,14 +231,27 @@ impl ItemsBuilder {
 /// Constructed with `ItemsBuilder`.
 #[derive(Debug)]
 pub struct Items {
-    size: u32,
-    dominator_tree: Option<BTreeMap<Id, Vec<Id>>>,
-    immediate_dominators: Option<BTreeMap<Id, Id>>,
-    retained_sizes: Option<BTreeMap<Id, u32>>,
-    predecessors: Option<BTreeMap<Id, Vec<Id>>>,
-    items: Frozen<BTreeMap<Id, Item>>,
-    edges: Frozen<BTreeMap<Id, Vec<Id>>>,
-    roots: Frozen<BTreeSet<Id>>,
+    size: u64,
+    // Items are stored densely, in id order, and everything else refers to
+    // them by their index in `ids` and `items`.
+    ids: Frozen<Vec<Id>>,
+    items: Frozen<Vec<Item>>,
+    // Each item's outgoing edges, sorted by target and then kind.
+    edges: Frozen<Csr<(u32, EdgeKind)>>,
+    predecessors: Option<Csr<u32>>,
+    immediate_dominators: Option<Vec<u32>>,
+    dominator_tree: Option<Csr<u32>>,
+    retained_sizes: Option<Vec<u64>>,
+    // The categories items were classified into, and each item's index into
+    // them, once any item has been given a category.
+    category_names: Vec<String>,
+    categories: Option<Vec<Option<u32>>>,
+    // The ids of the items with each name, and with each alias, in id order.
+    names: Frozen<BTreeMap<String, Vec<Id>>>,
+    aliases: Frozen<BTreeMap<String, Vec<Id>>>,
+    // Each item's position among the items with its name, counting from one,
+    // or zero when no other item has its name.
+    name_suffixes: Frozen<Vec<u32>>,
     meta_root: Id,
 }
 
@@ -153,42 +259,87 @@ impl ops::Index<Id> for Items {
     type Output = Item;
 
     fn index(&self, id: Id) -> &Item {
-        &self.items[&id]
+        match self.position(id) {
+            Some(index) => &self.items[index],
+            None => panic!("no item with id {:?}", id),
+        }
     }
 }
 
 impl Items {
+    /// The index of the item with the given id in the dense tables.
+    fn position(&self, id: Id) -> Option<usize> {
+        self.ids.binary_search(&id).ok()
+    }
+
     /// Iterate over all of the IR items.
-    pub fn iter(&self) -> Iter {
+    pub fn iter(&self) -> Iter<'_> {
         Iter {
             inner: self.items.iter(),
         }
     }
 
     /// Iterate over an item's neighbors.
-    pub fn neighbors(&self, id: Id) -> Neighbors {
+    pub fn neighbors(&self, id: Id) -> Neighbors<'_> {
         Neighbors {
+            inner: self.edges(id),
+            last: None,
+        }
+    }
+
+    /// Iterate over an item's outgoing edges, and their kinds. An item that
+    /// refers to another item in more than one way has an edge of each kind
+    /// to it.
+    pub fn edges(&self, id: Id) -> Edges<'_> {
+        Edges {
+            ids: &self.ids,
             inner: self
-                .edges
-                .get(&id)
-                .map_or_else(|| [].iter(), |edges| edges.iter()),
+                .position(id)
+                .map_or_else(|| [].iter(), |index| self.edges[index].iter()),
         }
     }
 
+    /// Keep only the edges for which `f(from, to, kind)` returns true, and
+    /// throw away anything computed from the old edges. The meta root's edges
+    /// to the roots are always kept.
+    pub fn retain_edges<F>(&mut self, mut f: F)
+    where
+        F: FnMut(Id, Id, EdgeKind) -> bool,
+    {
+        let meta_root = self.meta_root;
+        let ids = &self.ids;
+        let old_edges = &self.edges;
+        let edges = Csr::from_rows(ids.iter().enumerate().map(|(index, &from)| {
+            old_edges[index]
+                .iter()
+                .copied()
+                .filter(|&(to, kind)| from == meta_root || f(from, ids[to as usize], kind))
+                .collect::<Vec<_>>()
+        }));
+
+        self.edges = Frozen::freeze(edges);
+        self.dominator_tree = None;
+        self.immediate_dominators = None;
+        self.retained_sizes = None;
+        self.predecessors = None;
+    }
+
     /// Iterate over an item's predecessors.
-    pub fn predecessors(&self, id: Id) -> Predecessors {
+    pub fn predecessors(&self, id: Id) -> Predecessors<'_> {
+        let predecessors = self
+            .predecessors
+            .as_ref()
+            .expect("To access predecessors, must have already called compute_predecessors");
         Predecessors {
+            ids: &self.ids,
             inner: self
-                .predecessors
-                .as_ref()
-                .expect("To access predecessors, must have already called compute_predecessors")
-                .get(&id)
-                .map_or_else(|| [].iter(), |edges| edges.iter()),
+                .position(id)
+                .map_or_else(|| [].iter(), |index| predecessors[index].iter()),
         }
     }
 
     /// The size of the total binary, containing all items.
-    pub fn size(&self) -> u32 {
+    pub fn size(&self) -> u64 {
         self.size
     }
 
@@ -204,23 +355,18 @@ impl Items {
             return;
         }
 
-        let mut predecessors = BTreeMap::new();
-
-        for (from, tos) in self.edges.iter() {
-            for to in tos {
-                predecessors
-                    .entry(*to)
-                    .or_insert_with(BTreeSet::new)
-                    .insert(*from);
-            }
-        }
-
-        self.predecessors = Some(
-            predecessors
-                .into_iter()
-                .map(|(k, v)| (k, v.into_iter().collect()))
-                .collect(),
-        );
+        // Edges are sorted by target, so skipping repeated targets leaves one
+        // edge per predecessor, and the predecessors come out in id order.
+        let edges = &self.edges;
+        let pairs = (0..edges.len()).flat_map(|from| {
+            edges[from]
+                .iter()
+                .enumerate()
+                .filter(move |&(i, &(to, _))| i == 0 || edges[from][i - 1].0 != to)
+                .map(move |(_, &(to, _))| (to as usize, from as u32))
+        });
+
+        self.predecessors = Some(Csr::from_pairs(self.items.len(), pairs));
     }
 
     /// Compute dominators for each item.
@@ -228,24 +374,41 @@ impl Items {
         if self.immediate_dominators.is_some() {
             return;
         }
+        self.compute_predecessors();
+
+        let root = self.position(self.meta_root).unwrap() as u32;
+        self.immediate_dominators = Some(dominators::immediate_dominators(
+            &self.edges,
+            self.predecessors.as_ref().unwrap(),
+            root,
+        ));
+    }
 
-        let mut immediate_dominators = BTreeMap::new();
-        let dominators = petgraph::algo::dominators::simple_fast(&*self, self.meta_root);
-
-        for item in self.iter() {
-            if let Some(idom) = dominators.immediate_dominator(item.id()) {
-                immediate_dominators.insert(item.id(), idom);
-            }
+    /// Get the given item's immediate dominator. The meta root and items that
+    /// are not reachable from it have none.
+    ///
+    /// Must have already called `compute_dominators`.
+    pub fn immediate_dominator(&self, id: Id) -> Option<Id> {
+        let immediate_dominators = self
+            .immediate_dominators
+            .as_ref()
+            .expect("must call compute_dominators before calling immediate_dominator");
+        let idom = immediate_dominators[self.position(id)?];
+        if idom == dominators::NONE {
+            None
+        } else {
+            Some(self.ids[idom as usize])
         }
-
-        self.immediate_dominators = Some(immediate_dominators);
     }
 
-    /// Get a refercence to immediate dominators
-    pub fn immediate_dominators(&self) -> &BTreeMap<Id, Id> {
-        self.immediate_dominators
-            .as_ref()
-            .expect("must call compute_immediate_dominators before calling immediate_dominators")
+    /// Get every item's immediate dominator, for the items that have one.
+    ///
+    /// Must have already called `compute_dominators`.
+    #[deprecated(note = "use `Items::immediate_dominator` instead")]
+    pub fn immediate_dominators(&self) -> BTreeMap<Id, Id> {
+        self.iter()
+            .filter_map(|item| Some((item.id(), self.immediate_dominator(item.id())?)))
+            .collect()
     }
 
     /// Force computation of the dominator tree.
@@ -253,33 +416,48 @@ impl Items {
         if self.dominator_tree.is_some() {
             return;
         }
+        self.compute_dominators();
 
-        let mut dominator_tree = BTreeMap::new();
-        let dominators = petgraph::algo::dominators::simple_fast(&*self, self.meta_root);
-        for item in self.iter() {
-            if let Some(idom) = dominators.immediate_dominator(item.id()) {
-                dominator_tree
-                    .entry(idom)
-                    .or_insert_with(BTreeSet::new)
-                    .insert(item.id());
-            }
-        }
+        let immediate_dominators = self.immediate_dominators.as_ref().unwrap();
+        let pairs = immediate_dominators
+            .iter()
+            .enumerate()
+            .filter(|&(_, &idom)| idom != dominators::NONE)
+            .map(|(index, &idom)| (idom as usize, index as u32));
 
-        self.dominator_tree = Some(
-            dominator_tree
-                .into_iter()
-                .map(|(k, v)| (k, v.into_iter().collect()))
-                .collect(),
-        );
+        self.dominator_tree = Some(Csr::from_pairs(self.items.len(), pairs));
     }
 
-    /// Get a reference to the dominator tree.
+    /// Iterate over the items that the given item immediately dominates, which
+    /// are its children in the dominator tree, in id order.
     ///
     /// Must have already called `compute_dominator_tree`.
-    pub fn dominator_tree(&self) -> &BTreeMap<Id, Vec<Id>> {
-        self.dominator_tree
+    pub fn immediately_dominated(&self, id: Id) -> ImmediatelyDominated<'_> {
+        let dominator_tree = self
+            .dominator_tree
             .as_ref()
-            .expect("must call compute_dominator_tree before calling dominator_tree")
+            .expect("must call compute_dominator_tree before calling immediately_dominated");
+        ImmediatelyDominated {
+            ids: &self.ids,
+            inner: self
+                .position(id)
+                .map_or_else(|| [].iter(), |index| dominator_tree[index].iter()),
+        }
+    }
+
+    /// Get the dominator tree, as the items that each item immediately
+    /// dominates, for the items that dominate any.
+    ///
+    /// Must have already called `compute_dominator_tree`.
+    #[deprecated(note = "use `Items::immediately_dominated` instead")]
+    pub fn dominator_tree(&self) -> BTreeMap<Id, Vec<Id>> {
+        self.iter()
+            .map(|item| {
+                let dominated: Vec<Id> = self.immediately_dominated(item.id()).collect();
+                (item.id(), dominated)
+            })
+            .filter(|(_, dominated)| !dominated.is_empty())
+            .collect()
     }
 
     /// Force computation of the retained sizes of each IR item.
@@ -289,78 +467,154 @@ impl Items {
         }
         self.compute_dominator_tree();
 
-        fn recursive_retained_size(
-            retained_sizes: &mut BTreeMap<Id, u32>,
-            items: &Items,
-            item: &Item,
-            dominator_tree: &BTreeMap<Id, Vec<Id>>,
-        ) -> u32 {
-            // Although the dominator tree cannot have cycles, because we
-            // compute retained sizes in item iteration order, rather than from
-            // the bottom of the dominator tree up, it is possible we have
-            // already computed the retained sizes for subtrees.
-            if let Some(rsize) = retained_sizes.get(&item.id()) {
-                return *rsize;
-            }
-
-            let mut rsize = item.size();
-            if let Some(children) = dominator_tree.get(&item.id()) {
-                for child in children {
-                    rsize += recursive_retained_size(
-                        retained_sizes,
-                        items,
-                        &items[*child],
-                        dominator_tree,
-                    );
-                }
-            }
-
-            let old_value = retained_sizes.insert(item.id(), rsize);
-            // The dominator tree is a proper tree, so there shouldn't be
-            // any cycles.
-            assert!(old_value.is_none());
-            rsize
+        // Visit the dominator tree in pre order with an explicit stack, since
+        // long call chains make for trees far deeper than the call stack.
+        // Every item then comes after its immediate dominator, so adding
+        // retained sizes to immediate dominators in reverse order finishes
+        // each subtree before its root. Unreachable items aren't in the tree,
+        // and only retain themselves.
+        let root = self.position(self.meta_root).unwrap();
+        let dominator_tree = self.dominator_tree.as_ref().unwrap();
+        let mut pre_order = Vec::with_capacity(self.items.len());
+        let mut stack = vec![root as u32];
+        while let Some(index) = stack.pop() {
+            pre_order.push(index);
+            stack.extend(&dominator_tree[index as usize]);
         }
 
-        let mut retained_sizes = BTreeMap::new();
-        {
-            let dominator_tree = self.dominator_tree.as_ref().unwrap();
-            for item in self.iter() {
-                recursive_retained_size(&mut retained_sizes, self, item, dominator_tree);
+        let immediate_dominators = self.immediate_dominators.as_ref().unwrap();
+        let mut retained_sizes: Vec<u64> = self.items.iter().map(|item| item.size()).collect();
+        for &index in pre_order.iter().rev() {
+            let idom = immediate_dominators[index as usize];
+            if idom != dominators::NONE {
+                retained_sizes[idom as usize] =
+                    retained_sizes[idom as usize].saturating_add(retained_sizes[index as usize]);
             }
         }
         self.retained_sizes = Some(retained_sizes);
     }
 
     /// Get the given item's retained size.
-    pub fn retained_size(&self, id: Id) -> u32 {
-        self.retained_sizes
-            .as_ref()
-            .expect(
-                "Cannot call retained_sizes unless compute_retained_sizes \
-                 has already been called",
-            )
-            .get(&id)
-            .cloned()
-            .unwrap()
+    pub fn retained_size(&self, id: Id) -> u64 {
+        let retained_sizes = self.retained_sizes.as_ref().expect(
+            "Cannot call retained_sizes unless compute_retained_sizes \
+             has already been called",
+        );
+        retained_sizes[self.position(id).unwrap()]
     }
 
-    /// Get an item with the given name.
-    pub fn get_item_by_name(&self, name: &str) -> Option<&Item> {
-        for item in self.iter() {
-            if item.name() == name {
-                return Some(item);
+    /// Put the given item in the named category, replacing any category it
+    /// was in before.
+    pub fn set_category(&mut self, id: Id, category: &str) {
+        let index = self.position(id).expect("no item with the given id");
+        let category = match self.category_names.iter().position(|name| name == category) {
+            Some(category) => category,
+            None => {
+                self.category_names.push(category.to_string());
+                self.category_names.len() - 1
+            }
+        };
+        let len = self.items.len();
+        self.categories.get_or_insert_with(|| vec![None; len])[index] = Some(category as u32);
+    }
+
+    /// Get the category the given item was put in, if any.
+    pub fn category(&self, id: Id) -> Option<&str> {
+        let index = self.position(id)?;
+        let category = self.categories.as_ref()?[index]?;
+        Some(&self.category_names[category as usize])
+    }
+
+    /// Get the names of the categories items were put in, in the order they
+    /// were first used. This is empty until items are classified.
+    pub fn categories(&self) -> &[String] {
+        &self.category_names
+    }
+
+    /// Find items whose file ranges overlap, which would mean their bytes are
+    /// counted more than once. Each returned pair is an item and a later item
+    /// overlapping it.
+    pub fn overlapping_items(&self) -> Vec<(Id, Id)> {
+        let mut ranges: Vec<(&ops::Range<u64>, Id)> = self
+            .iter()
+            .flat_map(|item| {
+                item.file_ranges()
+                    .iter()
+                    .map(move |range| (range, item.id()))
+            })
+            .collect();
+        ranges.sort_by_key(|&(range, id)| (range.start, range.end, id));
+
+        let mut overlapping = vec![];
+        let mut furthest: Option<(u64, Id)> = None;
+        for (range, id) in ranges {
+            match furthest {
+                Some((end, other)) if range.start < end => {
+                    overlapping.push((other, id));
+                    if range.end > end {
+                        furthest = Some((range.end, id));
+                    }
+                }
+                _ => furthest = Some((range.end, id)),
             }
         }
+        overlapping
+    }
 
-        None // Return `None` if `name` did not match any items.
+    /// Get the first item with the given name. See `get_items_by_name`.
+    pub fn get_item_by_name(&self, name: &str) -> Option<&Item> {
+        self.get_items_by_name(name).into_iter().next()
+    }
+
+    /// Get every item with the given name, followed by every item with the
+    /// given alias, in id order.
+    ///
+    /// A name with the disambiguating suffix that `display_name` adds matches
+    /// only that one item.
+    pub fn get_items_by_name(&self, name: &str) -> Vec<&Item> {
+        let mut ids: Vec<Id> = self
+            .names
+            .get(name)
+            .into_iter()
+            .chain(self.aliases.get(name))
+            .flatten()
+            .copied()
+            .collect();
+        if ids.is_empty() {
+            ids.extend(self.disambiguated(name));
+        }
+        let mut seen = BTreeSet::new();
+        ids.retain(|&id| seen.insert(id));
+        ids.into_iter().map(|id| &self[id]).collect()
+    }
+
+    /// Get the name to show for the given item. This is the item's name,
+    /// followed by a ` #n` suffix when other items share that name, where `n`
+    /// counts the items with that name in id order.
+    pub fn display_name(&self, id: Id) -> Cow<'_, str> {
+        let index = self.position(id).expect("no item with the given id");
+        let name = self.items[index].name();
+        match self.name_suffixes[index] {
+            0 => Cow::Borrowed(name),
+            n => Cow::Owned(format!("{} #{}", name, n)),
+        }
+    }
+
+    /// Find the item that `display_name` gave the given name with a
+    /// disambiguating suffix.
+    fn disambiguated(&self, name: &str) -> Option<Id> {
+        let (name, n) = name.rsplit_once(" #")?;
+        let n: usize = n.parse().ok()?;
+        let ids = self.names.get(name).filter(|ids| ids.len() > 1)?;
+        ids.get(n.checked_sub(1)?).copied()
     }
 }
 
 /// An iterator over an item's neighbors.
 #[derive(Debug)]
 pub struct Neighbors<'a> {
-    inner: slice::Iter<'a, Id>,
+    inner: Edges<'a>,
+    last: Option<Id>,
 }
 
 impl<'a> Iterator for Neighbors<'a> {
@@ -368,14 +622,110 @@ impl<'a> Iterator for Neighbors<'a> {
 
     #[inline]
     fn next(&mut self) -> Option<Id> {
-        self.inner.next().cloned()
+        // Edges are sorted by target, so the edges of different kinds to the
+        // same neighbor are next to each other.
+        for (id, _) in self.inner.by_ref() {
+            if self.last != Some(id) {
+                self.last = Some(id);
+                return Some(id);
+            }
+        }
+        None
+    }
+}
+
+/// An iterator over an item's outgoing edges. Created by `Items::edges`.
+#[derive(Debug)]
+pub struct Edges<'a> {
+    ids: &'a [Id],
+    inner: slice::Iter<'a, (u32, EdgeKind)>,
+}
+
+impl<'a> Iterator for Edges<'a> {
+    type Item = (Id, EdgeKind);
+
+    #[inline]
+    fn next(&mut self) -> Option<(Id, EdgeKind)> {
+        self.inner
+            .next()
+            .map(|&(to, kind)| (self.ids[to as usize], kind))
+    }
+}
+
+/// The kind of an edge: how one item refers to another.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EdgeKind {
+    /// A direct call, e.g. a wasm `call` instruction or a branch relocation.
+    Call,
+    /// A reference to a global, e.g. a wasm `global.get` or `global.set`.
+    Global,
+    /// A reference to static data, e.g. a load from a wasm data segment or an
+    /// address relocation.
+    Data,
+    /// A reference to a type, e.g. a wasm function's type signature.
+    Type,
+    /// An export of the item.
+    Export,
+    /// A table slot, e.g. a wasm element segment entry, through which the
+    /// item can be called indirectly.
+    Table,
+    /// The item is the start function.
+    Start,
+    /// An edge from the meta root to a root.
+    Root,
+    /// Any other reference.
+    Other,
+}
+
+impl EdgeKind {
+    /// The name of this kind of edge, as accepted by `EdgeKind::from_str`.
+    pub fn name(self) -> &'static str {
+        match self {
+            EdgeKind::Call => "call",
+            EdgeKind::Global => "global",
+            EdgeKind::Data => "data",
+            EdgeKind::Type => "type",
+            EdgeKind::Export => "export",
+            EdgeKind::Table => "table",
+            EdgeKind::Start => "start",
+            EdgeKind::Root => "root",
+            EdgeKind::Other => "other",
+        }
+    }
+}
+
+impl fmt::Display for EdgeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for EdgeKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "call" => Ok(EdgeKind::Call),
+            "global" => Ok(EdgeKind::Global),
+            "data" => Ok(EdgeKind::Data),
+            "type" => Ok(EdgeKind::Type),
+            "export" => Ok(EdgeKind::Export),
+            "table" => Ok(EdgeKind::Table),
+            "start" => Ok(EdgeKind::Start),
+            "root" => Ok(EdgeKind::Root),
+            "other" => Ok(EdgeKind::Other),
+            _ => Err(anyhow!("Unknown edge kind: {}", s)),
+        }
     }
 }
 
 /// An iterator over an item's predecessors.
 #[derive(Debug)]
 pub struct Predecessors<'a> {
-    inner: slice::Iter<'a, Id>,
+    ids: &'a [Id],
+    inner: slice::Iter<'a, u32>,
 }
 
 impl<'a> Iterator for Predecessors<'a> {
@@ -383,21 +733,38 @@ impl<'a> Iterator for Predecessors<'a> {
 
     #[inline]
     fn next(&mut self) -> Option<Id> {
-        self.inner.next().cloned()
+        self.inner.next().map(|&index| self.ids[index as usize])
+    }
+}
+
+/// An iterator over the items that an item immediately dominates. Created by
+/// `Items::immediately_dominated`.
+#[derive(Debug)]
+pub struct ImmediatelyDominated<'a> {
+    ids: &'a [Id],
+    inner: slice::Iter<'a, u32>,
+}
+
+impl<'a> Iterator for ImmediatelyDominated<'a> {
+    type Item = Id;
+
+    #[inline]
+    fn next(&mut self) -> Option<Id> {
+        self.inner.next().map(|&index| self.ids[index as usize])
     }
 }
 
 /// An iterator over IR items. Created by `Items::iter`.
 #[derive(Clone, Debug)]
 pub struct Iter<'a> {
-    inner: btree_map::Iter<'a, Id, Item>,
+    inner: slice::Iter<'a, Item>,
 }
 
 impl<'a> Iterator for Iter<'a> {
     type Item = &'a Item;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.inner.next().map(|(_, item)| item)
+        self.inner.next()
     }
 }
 
@@ -437,13 +804,17 @@ impl Id {
 pub struct Item {
     id: Id,
     name: String,
-    size: u32,
+    size: u64,
     kind: ItemKind,
+    aliases: Vec<String>,
+    file_ranges: Vec<ops::Range<u64>>,
+    address: Option<u64>,
+    section: Option<String>,
 }
 
 impl Item {
     /// Construct a new `Item` of the given kind.
-    pub fn new<S, K>(id: Id, name: S, size: u32, kind: K) -> Item
+    pub fn new<S, K>(id: Id, name: S, size: u64, kind: K) -> Item
     where
         S: Into<String>,
         K: Into<ItemKind>,
@@ -454,9 +825,75 @@ impl Item {
             name,
             size,
             kind: kind.into(),
+            aliases: vec![],
+            file_ranges: vec![],
+            address: None,
+            section: None,
+        }
+    }
+
+    /// Record that the given range of the input file holds some of this
+    /// item's bytes. Ranges that touch the last one added are merged with it.
+    pub fn add_file_range(&mut self, range: ops::Range<u64>) {
+        if range.start >= range.end {
+            return;
+        }
+        match self.file_ranges.last_mut() {
+            Some(last) if last.end == range.start => last.end = range.end,
+            _ => self.file_ranges.push(range),
         }
     }
 
+    /// Get the ranges of the input file that hold this item's bytes, in the
+    /// order they were added. Items without any bytes in the file, like
+    /// zero-initialized data, have none.
+    #[inline]
+    pub fn file_ranges(&self) -> &[ops::Range<u64>] {
+        &self.file_ranges
+    }
+
+    /// Set the virtual address this item is loaded at.
+    pub fn set_address(&mut self, address: u64) {
+        self.address = Some(address);
+    }
+
+    /// Get the virtual address this item is loaded at, if it is known.
+    #[inline]
+    pub fn address(&self) -> Option<u64> {
+        self.address
+    }
+
+    /// Set the name of the section this item lives in.
+    pub fn set_section<S: Into<String>>(&mut self, section: S) {
+        self.section = Some(section.into());
+    }
+
+    /// Get the name of the section this item lives in, if it is known.
+    #[inline]
+    pub fn section(&self) -> Option<&str> {
+        self.section.as_deref()
+    }
+
+    /// Add another name for this item, e.g. a symbol at the same address
+    /// whose code was folded into this one.
+    pub fn add_alias<S: Into<String>>(&mut self, alias: S) {
+        let alias = alias.into();
+        let alias = match self.kind {
+            ItemKind::Code(_) => Code::demangle(&alias).unwrap_or(alias),
+            ItemKind::Data(ref data) if data.demangled.is_some() => {
+                Code::demangle(&alias).unwrap_or(alias)
+            }
+            _ => alias,
+        };
+        self.aliases.push(alias);
+    }
+
+    /// Get the other names this item is known by.
+    #[inline]
+    pub fn aliases(&self) -> &[String] {
+        &self.aliases
+    }
+
     /// Get this item's identifier.
     #[inline]
     pub fn id(&self) -> Id {
@@ -465,17 +902,17 @@ impl Item {
 
     /// Get this item's size.
     #[inline]
-    pub fn size(&self) -> u32 {
+    pub fn size(&self) -> u64 {
         self.size
     }
 
     /// Get this item's name.
     #[inline]
     pub fn name(&self) -> &str {
-        if let ItemKind::Code(ref code) = self.kind {
-            code.demangled().unwrap_or(&self.name)
-        } else {
-            &self.name
+        match self.kind {
+            ItemKind::Code(ref code) => code.demangled().unwrap_or(&self.name),
+            ItemKind::Data(ref data) => data.demangled().unwrap_or(&self.name),
+            _ => &self.name,
         }
     }
 
@@ -495,11 +932,22 @@ impl Item {
             None
         }
     }
+
+    /// The generic arguments that this monomorphization was instantiated with,
+    /// when they are known.
+    #[inline]
+    pub fn generic_args(&self) -> &[String] {
+        if let ItemKind::Code(ref code) = self.kind {
+            code.generic_args()
+        } else {
+            &[]
+        }
+    }
 }
 
 impl PartialOrd for Item {
     fn partial_cmp(&self, rhs: &Item) -> Option<cmp::Ordering> {
-        self.id.partial_cmp(&rhs.id)
+        Some(self.cmp(rhs))
     }
 }
 
@@ -519,6 +967,31 @@ pub enum ItemKind {
     /// with the executable code.
     Data(Data),
 
+    /// A global variable, such as a wasm global.
+    Global(Global),
+
+    /// Something the binary imports from its environment.
+    Import(Import),
+
+    /// Something the binary exports to its environment.
+    Export(Export),
+
+    /// A type definition, such as a wasm function signature.
+    Type(Type),
+
+    /// A table of references, such as a wasm table.
+    Table(Table),
+
+    /// A linear memory, such as a wasm memory.
+    Memory(Memory),
+
+    /// An element segment, initializing a table.
+    Element(Element),
+
+    /// The headers of a section, and any other bytes of it that aren't part
+    /// of another item.
+    Section(Section),
+
     /// Debugging symbols and information, such as a DWARF section.
     Debug(DebugInfo),
 
@@ -527,11 +1000,109 @@ pub enum ItemKind {
 }
 
 impl ItemKind {
-    /// Returns true if `self` is the `Data` variant
+    /// Returns true if `self` is the `Data` or `Global` variant
     pub fn is_data(&self) -> bool {
+        matches!(self, ItemKind::Data(_) | ItemKind::Global(_))
+    }
+
+    /// The kind of this item, without its details.
+    pub fn tag(&self) -> ItemKindTag {
         match self {
-            ItemKind::Data(_) => true,
-            _ => false,
+            ItemKind::Code(_) => ItemKindTag::Code,
+            ItemKind::Data(_) => ItemKindTag::Data,
+            ItemKind::Global(_) => ItemKindTag::Global,
+            ItemKind::Import(_) => ItemKindTag::Import,
+            ItemKind::Export(_) => ItemKindTag::Export,
+            ItemKind::Type(_) => ItemKindTag::Type,
+            ItemKind::Table(_) => ItemKindTag::Table,
+            ItemKind::Memory(_) => ItemKindTag::Memory,
+            ItemKind::Element(_) => ItemKindTag::Element,
+            ItemKind::Section(_) => ItemKindTag::Section,
+            ItemKind::Debug(_) => ItemKindTag::Debug,
+            ItemKind::Misc(_) => ItemKindTag::Misc,
+        }
+    }
+
+    /// The name of this kind of item, e.g. `"code"` or `"import"`.
+    pub fn name(&self) -> &'static str {
+        self.tag().name()
+    }
+}
+
+/// The kind of an item, without its details: which variant of `ItemKind` it
+/// is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ItemKindTag {
+    /// See `ItemKind::Code`.
+    Code,
+    /// See `ItemKind::Data`.
+    Data,
+    /// See `ItemKind::Global`.
+    Global,
+    /// See `ItemKind::Import`.
+    Import,
+    /// See `ItemKind::Export`.
+    Export,
+    /// See `ItemKind::Type`.
+    Type,
+    /// See `ItemKind::Table`.
+    Table,
+    /// See `ItemKind::Memory`.
+    Memory,
+    /// See `ItemKind::Element`.
+    Element,
+    /// See `ItemKind::Section`.
+    Section,
+    /// See `ItemKind::Debug`.
+    Debug,
+    /// See `ItemKind::Misc`.
+    Misc,
+}
+
+impl ItemKindTag {
+    /// The name of this kind of item, as accepted by `ItemKindTag::from_str`.
+    pub fn name(self) -> &'static str {
+        match self {
+            ItemKindTag::Code => "code",
+            ItemKindTag::Data => "data",
+            ItemKindTag::Global => "global",
+            ItemKindTag::Import => "import",
+            ItemKindTag::Export => "export",
+            ItemKindTag::Type => "type",
+            ItemKindTag::Table => "table",
+            ItemKindTag::Memory => "memory",
+            ItemKindTag::Element => "element",
+            ItemKindTag::Section => "section",
+            ItemKindTag::Debug => "debug",
+            ItemKindTag::Misc => "misc",
+        }
+    }
+}
+
+impl fmt::Display for ItemKindTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ItemKindTag {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "code" => Ok(ItemKindTag::Code),
+            "data" => Ok(ItemKindTag::Data),
+            "global" => Ok(ItemKindTag::Global),
+            "import" => Ok(ItemKindTag::Import),
+            "export" => Ok(ItemKindTag::Export),
+            "type" => Ok(ItemKindTag::Type),
+            "table" => Ok(ItemKindTag::Table),
+            "memory" => Ok(ItemKindTag::Memory),
+            "element" => Ok(ItemKindTag::Element),
+            "section" => Ok(ItemKindTag::Section),
+            "debug" => Ok(ItemKindTag::Debug),
+            "misc" => Ok(ItemKindTag::Misc),
+            _ => Err(anyhow!("Unknown item kind: {}", s)),
         }
     }
 }
@@ -548,6 +1119,54 @@ impl From<Data> for ItemKind {
     }
 }
 
+impl From<Global> for ItemKind {
+    fn from(g: Global) -> ItemKind {
+        ItemKind::Global(g)
+    }
+}
+
+impl From<Import> for ItemKind {
+    fn from(i: Import) -> ItemKind {
+        ItemKind::Import(i)
+    }
+}
+
+impl From<Export> for ItemKind {
+    fn from(e: Export) -> ItemKind {
+        ItemKind::Export(e)
+    }
+}
+
+impl From<Type> for ItemKind {
+    fn from(t: Type) -> ItemKind {
+        ItemKind::Type(t)
+    }
+}
+
+impl From<Table> for ItemKind {
+    fn from(t: Table) -> ItemKind {
+        ItemKind::Table(t)
+    }
+}
+
+impl From<Memory> for ItemKind {
+    fn from(m: Memory) -> ItemKind {
+        ItemKind::Memory(m)
+    }
+}
+
+impl From<Element> for ItemKind {
+    fn from(e: Element) -> ItemKind {
+        ItemKind::Element(e)
+    }
+}
+
+impl From<Section> for ItemKind {
+    fn from(s: Section) -> ItemKind {
+        ItemKind::Section(s)
+    }
+}
+
 impl From<DebugInfo> for ItemKind {
     fn from(d: DebugInfo) -> ItemKind {
         ItemKind::Debug(d)
@@ -565,33 +1184,59 @@ impl From<Misc> for ItemKind {
 pub struct Code {
     demangled: Option<String>,
     monomorphization_of: Option<String>,
+    generic_args: Vec<String>,
 }
 
 impl Code {
     /// Construct a new IR item for executable code.
     pub fn new(name: &str) -> Code {
-        let demangled = Self::demangle(&name);
-        let monomorphization_of =
-            Self::extract_generic_function(demangled.as_ref().map(|s| s.as_str()).unwrap_or(name));
+        let demangled = Self::demangle(name);
+        let (monomorphization_of, generic_args) = if Self::is_rust_v0(name) {
+            match demangled.as_deref().and_then(Self::split_generic_args) {
+                Some((generic, args)) => (Some(generic), args),
+                None => (None, vec![]),
+            }
+        } else {
+            let generic = Self::extract_generic_function(demangled.as_deref().unwrap_or(name));
+            let args = if generic.is_some() && Self::is_cpp(name) {
+                Self::cpp_generic_args(name)
+            } else {
+                vec![]
+            };
+            (generic, args)
+        };
         Code {
             demangled,
             monomorphization_of,
+            generic_args,
         }
     }
 
     /// Get the demangled name of this function, if any.
     pub fn demangled(&self) -> Option<&str> {
-        self.demangled.as_ref().map(|s| s.as_str())
+        self.demangled.as_deref()
     }
 
     /// Get the name of the generic function that this is a monomorphization of,
     /// if any.
     pub fn monomorphization_of(&self) -> Option<&str> {
-        self.monomorphization_of.as_ref().map(|s| s.as_str())
+        self.monomorphization_of.as_deref()
+    }
+
+    /// Get the generic arguments that this monomorphization was instantiated
+    /// with, in order. These are only known for C++ symbols and Rust symbols
+    /// mangled with the v0 scheme, and are empty otherwise.
+    pub fn generic_args(&self) -> &[String] {
+        &self.generic_args
     }
 
     fn demangle(s: &str) -> Option<String> {
         if let Ok(sym) = rustc_demangle::try_demangle(s) {
+            // The alternate form leaves out the disambiguating hashes that v0
+            // symbols carry for every crate in their paths.
+            if Self::is_rust_v0(s) {
+                return Some(format!("{:#}", sym));
+            }
             return Some(sym.to_string());
         }
 
@@ -612,7 +1257,7 @@ impl Code {
         //
         // To avoid that, only pass C++-mangled symbols to the C++
         // demangler
-        if !s.starts_with("_Z") && !s.starts_with("__Z") && !s.starts_with("_GLOBAL_") {
+        if !Self::is_cpp(s) {
             return Some(s.to_string());
         }
 
@@ -623,6 +1268,188 @@ impl Code {
         None
     }
 
+    /// Does the given symbol look like a mangled C++ symbol?
+    fn is_cpp(s: &str) -> bool {
+        s.starts_with("_Z") || s.starts_with("__Z") || s.starts_with("_GLOBAL_")
+    }
+
+    /// Is the given symbol mangled with Rust's v0 scheme, rather than the
+    /// legacy one?
+    fn is_rust_v0(s: &str) -> bool {
+        let unprefixed = s
+            .strip_prefix("__")
+            .or_else(|| s.strip_prefix('_'))
+            .unwrap_or(s);
+        unprefixed.starts_with('R') && rustc_demangle::try_demangle(s).is_ok()
+    }
+
+    /// Split a demangled v0 symbol into its generic function, with `_` in
+    /// place of each generic argument, and the arguments themselves. For
+    /// example, `<alloc::vec::Vec<u8>>::push` is a monomorphization of
+    /// `<alloc::vec::Vec<_>>::push` with the argument `u8`. Returns `None` if
+    /// the symbol has no generic arguments.
+    ///
+    /// Only the outermost argument lists are split up, so the arguments of
+    /// `core::mem::drop::<alloc::vec::Vec<u8>>` are `[alloc::vec::Vec<u8>]`.
+    fn split_generic_args(demangled: &str) -> Option<(String, Vec<String>)> {
+        // Does the `<` at the end of `before` open a list of generic arguments,
+        // as in `Vec<u8>` or `drop::<u8>`, rather than a qualified path like
+        // `<u8 as Trait>` or a binder like `for<'a>`?
+        fn opens_generic_args(before: &str) -> bool {
+            let is_ident = |ch: char| ch.is_alphanumeric() || ch == '_';
+            match before.chars().last() {
+                Some(':') | Some('}') => true,
+                Some(ch) if is_ident(ch) => {
+                    let word = before.trim_end_matches(is_ident);
+                    &before[word.len()..] != "for"
+                }
+                _ => false,
+            }
+        }
+
+        // Find the index of the bracket that closes the one at the start of
+        // `s`. The `>` of a `->` is not a bracket.
+        fn closing_bracket(s: &str) -> Option<usize> {
+            let mut depth = 0;
+            let mut prev = None;
+            for (i, ch) in s.char_indices() {
+                match ch {
+                    '<' | '(' | '[' | '{' => depth += 1,
+                    '>' if prev == Some('-') => {}
+                    '>' | ')' | ']' | '}' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Some(i);
+                        }
+                    }
+                    _ => {}
+                }
+                prev = Some(ch);
+            }
+            None
+        }
+
+        let mut generic = String::with_capacity(demangled.len());
+        let mut args = vec![];
+        let mut rest = demangled;
+        while let Some(open) = rest.find('<') {
+            generic.push_str(&rest[..open]);
+            if !opens_generic_args(&generic) {
+                generic.push('<');
+                rest = &rest[open + 1..];
+                continue;
+            }
+
+            let close = open + closing_bracket(&rest[open..])?;
+            let list = Self::split_generic_arg_list(&rest[open + 1..close]);
+            generic.push('<');
+            generic.push_str(&vec!["_"; list.len()].join(", "));
+            generic.push('>');
+            args.extend(list);
+            rest = &rest[close + 1..];
+        }
+        generic.push_str(rest);
+
+        if args.is_empty() {
+            None
+        } else {
+            Some((generic, args))
+        }
+    }
+
+    /// Split a list of generic arguments at its top-level commas.
+    fn split_generic_arg_list(list: &str) -> Vec<String> {
+        let mut args = vec![];
+        let mut depth = 0;
+        let mut start = 0;
+        let mut prev = None;
+        for (i, ch) in list.char_indices() {
+            match ch {
+                '<' | '(' | '[' | '{' => depth += 1,
+                '>' if prev == Some('-') => {}
+                '>' | ')' | ']' | '}' => depth -= 1,
+                ',' if depth == 0 => {
+                    args.push(list[start..i].trim().to_string());
+                    start = i + 1;
+                }
+                _ => {}
+            }
+            prev = Some(ch);
+        }
+        args.push(list[start..].trim().to_string());
+        args
+    }
+
+    /// Get the template arguments of a mangled C++ symbol, from the outermost
+    /// template argument lists in its name. For example, the arguments of
+    /// `std::vector<int, std::allocator<int> >::push_back(int const&)` are
+    /// `[int, std::allocator<int>]`. Template arguments in the parameter list
+    /// don't make a function a template instantiation, so they are skipped.
+    fn cpp_generic_args(s: &str) -> Vec<String> {
+        // Collects the text of each outermost `<template-args>` node that the
+        // demangler walks through before the parameter list.
+        #[derive(Default)]
+        struct ArgLists {
+            lists: Vec<String>,
+            // The kinds of the AST nodes the demangler is currently inside.
+            nodes: Vec<cpp_demangle::DemangleNodeType>,
+            in_params: bool,
+        }
+
+        impl cpp_demangle::DemangleWrite for ArgLists {
+            fn push_demangle_node(&mut self, node: cpp_demangle::DemangleNodeType) {
+                let outermost = !self
+                    .nodes
+                    .contains(&cpp_demangle::DemangleNodeType::TemplateArgs);
+                if node == cpp_demangle::DemangleNodeType::TemplateArgs
+                    && outermost
+                    && !self.in_params
+                {
+                    self.lists.push(String::new());
+                }
+                self.nodes.push(node);
+            }
+
+            fn write_string(&mut self, s: &str) -> fmt::Result {
+                if self.nodes.is_empty() && s == "(" {
+                    self.in_params = true;
+                }
+                if !self.in_params
+                    && self
+                        .nodes
+                        .contains(&cpp_demangle::DemangleNodeType::TemplateArgs)
+                {
+                    if let Some(list) = self.lists.last_mut() {
+                        list.push_str(s);
+                    }
+                }
+                Ok(())
+            }
+
+            fn pop_demangle_node(&mut self) {
+                self.nodes.pop();
+            }
+        }
+
+        let sym = match cpp_demangle::Symbol::new(s) {
+            Ok(sym) => sym,
+            Err(_) => return vec![],
+        };
+        let mut arg_lists = ArgLists::default();
+        if sym
+            .structured_demangle(&mut arg_lists, &Default::default())
+            .is_err()
+        {
+            return vec![];
+        }
+        arg_lists
+            .lists
+            .iter()
+            .flat_map(|list| Self::split_generic_arg_list(list))
+            .filter(|arg| !arg.is_empty())
+            .collect()
+    }
+
     fn extract_generic_function(demangled: &str) -> Option<String> {
         // XXX: This is some hacky, ad-hoc parsing shit! This should
         // approximately work for Rust and C++ symbols, but who knows for other
@@ -632,12 +1459,12 @@ impl Code {
         // "::h1234567890" hash from the end of the symbol. If it's there, the
         // generic function is just the symbol without that hash, so remove it.
         //
-        // I know what you're thinking, and it's true: mangled (and therefore
-        // also demangled) Rust symbols don't include the concrete type(s) used
-        // to instantiate the generic function, which gives us much less to work
-        // with than we have with C++ demangled symbols. It would sure be nice
-        // if we could tell the user more about the monomorphization, but
-        // alas... :(
+        // I know what you're thinking, and it's true: legacy mangled (and
+        // therefore also demangled) Rust symbols don't include the concrete
+        // type(s) used to instantiate the generic function, which gives us much
+        // less to work with than we have with C++ demangled symbols. Symbols
+        // mangled with the v0 scheme do, and `split_generic_args` handles those
+        // instead.
         if let Some(idx) = demangled.rfind("::h") {
             let idx2 = demangled.rfind("::").unwrap();
             assert!(idx2 >= idx);
@@ -679,12 +1506,252 @@ impl Code {
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Data {
     ty: Option<String>,
+    demangled: Option<String>,
+    segment: Option<SegmentMode>,
 }
 
 impl Data {
     /// Construct a new `Data` that has a type of the given type name, if known.
     pub fn new(ty: Option<String>) -> Data {
-        Data { ty }
+        Data {
+            ty,
+            demangled: None,
+            segment: None,
+        }
+    }
+
+    /// Construct a new `Data` for a data segment with the given mode.
+    pub fn segment(mode: SegmentMode) -> Data {
+        Data {
+            ty: None,
+            demangled: None,
+            segment: Some(mode),
+        }
+    }
+
+    /// Construct a new `Data` for the data defined by the symbol with the
+    /// given name.
+    pub fn symbol(name: &str) -> Data {
+        Data {
+            ty: None,
+            demangled: Code::demangle(name),
+            segment: None,
+        }
+    }
+
+    /// Get the name of this data's type, if known.
+    pub fn ty(&self) -> Option<&str> {
+        self.ty.as_deref()
+    }
+
+    /// Get the demangled name of this data's symbol, if any.
+    pub fn demangled(&self) -> Option<&str> {
+        self.demangled.as_deref()
+    }
+
+    /// Get whether this data is an active or passive data segment, if it is
+    /// a data segment at all.
+    pub fn segment_mode(&self) -> Option<SegmentMode> {
+        self.segment
+    }
+}
+
+/// How a data segment is used.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SegmentMode {
+    /// The segment is copied into memory when the binary is instantiated.
+    Active,
+    /// The segment is only copied into memory when the code asks for it,
+    /// e.g. with wasm's `memory.init`.
+    Passive,
+}
+
+impl SegmentMode {
+    /// The name of this mode, e.g. `"active"`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            SegmentMode::Active => "active",
+            SegmentMode::Passive => "passive",
+        }
+    }
+}
+
+/// What kind of thing an import or export is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExternalKind {
+    /// A function.
+    Function,
+    /// A table.
+    Table,
+    /// A memory.
+    Memory,
+    /// A global.
+    Global,
+    /// An exception tag.
+    Tag,
+}
+
+impl ExternalKind {
+    /// The name of this kind of thing, e.g. `"function"`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExternalKind::Function => "function",
+            ExternalKind::Table => "table",
+            ExternalKind::Memory => "memory",
+            ExternalKind::Global => "global",
+            ExternalKind::Tag => "tag",
+        }
+    }
+}
+
+/// A global variable, such as a wasm global.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Global {
+    ty: String,
+    mutable: bool,
+}
+
+impl Global {
+    /// Construct a new `Global` of the given type.
+    pub fn new(ty: String, mutable: bool) -> Global {
+        Global { ty, mutable }
+    }
+
+    /// Get the name of this global's type.
+    pub fn ty(&self) -> &str {
+        &self.ty
+    }
+
+    /// Get whether this global can be changed after initialization.
+    pub fn is_mutable(&self) -> bool {
+        self.mutable
+    }
+}
+
+/// Something the binary imports from its environment.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Import {
+    module: String,
+    field: String,
+    kind: Option<ExternalKind>,
+}
+
+impl Import {
+    /// Construct a new `Import` of the given module's field, which is of the
+    /// given kind, if known.
+    pub fn new(module: String, field: String, kind: Option<ExternalKind>) -> Import {
+        Import {
+            module,
+            field,
+            kind,
+        }
+    }
+
+    /// Get the name of the module this is imported from.
+    pub fn module(&self) -> &str {
+        &self.module
+    }
+
+    /// Get the name of the imported field within its module.
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+
+    /// Get what kind of thing is imported, if known.
+    pub fn external_kind(&self) -> Option<ExternalKind> {
+        self.kind
+    }
+}
+
+/// Something the binary exports to its environment.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Export {
+    target: ExternalKind,
+}
+
+impl Export {
+    /// Construct a new `Export` of the given kind of thing.
+    pub fn new(target: ExternalKind) -> Export {
+        Export { target }
+    }
+
+    /// Get what kind of thing is exported.
+    pub fn target(&self) -> ExternalKind {
+        self.target
+    }
+}
+
+/// A type definition, such as a wasm function signature.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Type {
+    params: Vec<String>,
+    results: Vec<String>,
+}
+
+impl Type {
+    /// Construct a new function signature `Type` with the given parameter and
+    /// result types.
+    pub fn new(params: Vec<String>, results: Vec<String>) -> Type {
+        Type { params, results }
+    }
+
+    /// Get the names of the parameter types.
+    pub fn params(&self) -> &[String] {
+        &self.params
+    }
+
+    /// Get the names of the result types.
+    pub fn results(&self) -> &[String] {
+        &self.results
+    }
+}
+
+/// A table of references, such as a wasm table.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct Table;
+
+impl Table {
+    /// Construct a new IR item for a table.
+    pub fn new() -> Table {
+        Table
+    }
+}
+
+/// A linear memory, such as a wasm memory.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct Memory;
+
+impl Memory {
+    /// Construct a new IR item for a memory.
+    pub fn new() -> Memory {
+        Memory
+    }
+}
+
+/// An element segment, initializing a table.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct Element;
+
+impl Element {
+    /// Construct a new IR item for an element segment.
+    pub fn new() -> Element {
+        Element
+    }
+}
+
+/// The headers of a section, and any other bytes of it that aren't part of
+/// another item.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct Section;
+
+impl Section {
+    /// Construct a new IR item for a section's headers.
+    pub fn new() -> Section {
+        Section
     }
 }
 
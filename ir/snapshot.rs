@@ -0,0 +1,246 @@
+//! A serializable snapshot of `Items`, so that a parsed binary can be saved
+//! and analyzed later without the binary itself.
+
+use std::collections::BTreeSet;
+
+use serde_derive::{Deserialize, Serialize};
+
+use super::{
+    Code, Data, DebugInfo, EdgeKind, Element, Export, ExternalKind, Global, Id, Import, Item,
+    ItemKind, Items, ItemsBuilder, Memory, Misc, Section, SegmentMode, Table, Type,
+};
+
+/// The version of the snapshot format. Bump this whenever the format changes
+/// in a way that older versions of `twiggy` can't read.
+pub const SNAPSHOT_VERSION: u32 = 3;
+
+/// A snapshot of an `Items` graph: its items, edges and roots.
+///
+/// Ids are stored in their `Id::serializable` form.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+    // Kept first so that snapshots are easy to recognize.
+    twiggy_snapshot: u32,
+    size: u64,
+    items: Vec<SnapshotItem>,
+    roots: Vec<u64>,
+    edges: Vec<(u64, Vec<(u64, EdgeKind)>)>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SnapshotItem {
+    id: u64,
+    name: String,
+    size: u64,
+    #[serde(flatten)]
+    kind: SnapshotKind,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    aliases: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    file_ranges: Vec<(u64, u64)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    address: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    section: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum SnapshotKind {
+    Code,
+    Data {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ty: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        segment: Option<SegmentMode>,
+        #[serde(default, skip_serializing_if = "is_false")]
+        symbol: bool,
+    },
+    Global {
+        ty: String,
+        mutable: bool,
+    },
+    Import {
+        module: String,
+        field: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        import_kind: Option<ExternalKind>,
+    },
+    Export {
+        target: ExternalKind,
+    },
+    Type {
+        params: Vec<String>,
+        results: Vec<String>,
+    },
+    Table,
+    Memory,
+    Element,
+    Section,
+    Debug,
+    Misc,
+}
+
+impl Snapshot {
+    /// The version of the format this snapshot was written in.
+    pub fn version(&self) -> u32 {
+        self.twiggy_snapshot
+    }
+
+    /// Rebuild the `Items` graph from this snapshot. Edges between items that
+    /// are not in the snapshot are dropped.
+    pub fn into_items(self) -> Items {
+        let mut builder = ItemsBuilder::new(self.size);
+
+        let roots: BTreeSet<Id> = self.roots.into_iter().map(from_serializable).collect();
+        for item in self.items {
+            let id = from_serializable(item.id);
+            let kind: ItemKind = match item.kind {
+                SnapshotKind::Code => Code::new(&item.name).into(),
+                SnapshotKind::Data {
+                    ty,
+                    segment,
+                    symbol,
+                } => {
+                    let mut data = if symbol {
+                        Data::symbol(&item.name)
+                    } else {
+                        Data::new(None)
+                    };
+                    data.ty = ty;
+                    data.segment = segment;
+                    data.into()
+                }
+                SnapshotKind::Global { ty, mutable } => Global::new(ty, mutable).into(),
+                SnapshotKind::Import {
+                    module,
+                    field,
+                    import_kind,
+                } => Import::new(module, field, import_kind).into(),
+                SnapshotKind::Export { target } => Export::new(target).into(),
+                SnapshotKind::Type { params, results } => Type::new(params, results).into(),
+                SnapshotKind::Table => Table::new().into(),
+                SnapshotKind::Memory => Memory::new().into(),
+                SnapshotKind::Element => Element::new().into(),
+                SnapshotKind::Section => Section::new().into(),
+                SnapshotKind::Debug => DebugInfo::new().into(),
+                SnapshotKind::Misc => Misc::new().into(),
+            };
+            let mut new_item = Item::new(id, item.name, item.size, kind);
+            new_item.aliases = item.aliases;
+            for (start, end) in item.file_ranges {
+                new_item.add_file_range(start..end);
+            }
+            new_item.address = item.address;
+            new_item.section = item.section;
+            if roots.contains(&id) {
+                builder.add_root(new_item);
+            } else {
+                builder.add_item(new_item);
+            }
+        }
+
+        for (from, tos) in self.edges {
+            let from = from_serializable(from);
+            if !builder.parsed.contains(&from) {
+                continue;
+            }
+            for (to, kind) in tos {
+                let to = from_serializable(to);
+                if builder.parsed.contains(&to) {
+                    builder.add_edge(from, to, kind);
+                }
+            }
+        }
+
+        builder.finish()
+    }
+}
+
+impl Items {
+    /// Take a snapshot of these items, which can be serialized and later
+    /// turned back into `Items` with `Snapshot::into_items`.
+    pub fn snapshot(&self) -> Snapshot {
+        let meta_root = self.meta_root();
+        let items = self
+            .iter()
+            .filter(|item| item.id() != meta_root)
+            .map(|item| SnapshotItem {
+                id: item.id().serializable(),
+                name: item.name.clone(),
+                size: item.size(),
+                kind: match item.kind() {
+                    ItemKind::Code(_) => SnapshotKind::Code,
+                    ItemKind::Data(data) => SnapshotKind::Data {
+                        ty: data.ty.clone(),
+                        segment: data.segment,
+                        symbol: data.demangled.is_some(),
+                    },
+                    ItemKind::Global(global) => SnapshotKind::Global {
+                        ty: global.ty.clone(),
+                        mutable: global.mutable,
+                    },
+                    ItemKind::Import(import) => SnapshotKind::Import {
+                        module: import.module.clone(),
+                        field: import.field.clone(),
+                        import_kind: import.kind,
+                    },
+                    ItemKind::Export(export) => SnapshotKind::Export {
+                        target: export.target,
+                    },
+                    ItemKind::Type(ty) => SnapshotKind::Type {
+                        params: ty.params.clone(),
+                        results: ty.results.clone(),
+                    },
+                    ItemKind::Table(_) => SnapshotKind::Table,
+                    ItemKind::Memory(_) => SnapshotKind::Memory,
+                    ItemKind::Element(_) => SnapshotKind::Element,
+                    ItemKind::Section(_) => SnapshotKind::Section,
+                    ItemKind::Debug(_) => SnapshotKind::Debug,
+                    ItemKind::Misc(_) => SnapshotKind::Misc,
+                },
+                aliases: item.aliases().to_vec(),
+                file_ranges: item
+                    .file_ranges()
+                    .iter()
+                    .map(|range| (range.start, range.end))
+                    .collect(),
+                address: item.address(),
+                section: item.section.clone(),
+            })
+            .collect();
+        let roots = self
+            .neighbors(meta_root)
+            .map(|id| id.serializable())
+            .collect();
+        let edges = self
+            .iter()
+            .map(|item| item.id())
+            .filter(|&from| from != meta_root)
+            .map(|from| {
+                let tos: Vec<_> = self
+                    .edges(from)
+                    .map(|(to, kind)| (to.serializable(), kind))
+                    .collect();
+                (from.serializable(), tos)
+            })
+            .filter(|(_, tos)| !tos.is_empty())
+            .collect();
+
+        Snapshot {
+            twiggy_snapshot: SNAPSHOT_VERSION,
+            size: self.size(),
+            items,
+            roots,
+            edges,
+        }
+    }
+}
+
+fn from_serializable(id: u64) -> Id {
+    Id((id >> 32) as u32, id as u32)
+}
+
+fn is_false(b: &bool) -> bool {
+    !*b
+}
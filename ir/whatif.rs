@@ -0,0 +1,121 @@
+//! Hypothetical changes to an `Items` graph, for asking what would happen if
+//! some items, edges or roots went away.
+
+use std::collections::BTreeSet;
+
+use petgraph::visit::{Dfs, Walker};
+
+use super::{Id, Items, ItemsBuilder};
+
+/// A set of hypothetical changes to an `Items` graph. Apply them with
+/// `Items::with_changes`.
+#[derive(Clone, Debug, Default)]
+pub struct Changes {
+    removed_items: BTreeSet<Id>,
+    removed_edges: BTreeSet<(Id, Id)>,
+    added_roots: BTreeSet<Id>,
+    removed_roots: BTreeSet<Id>,
+}
+
+impl Changes {
+    /// Construct a new, empty set of changes.
+    pub fn new() -> Changes {
+        Changes::default()
+    }
+
+    /// Remove the given item, along with every edge to or from it.
+    pub fn remove_item(&mut self, id: Id) -> &mut Changes {
+        self.removed_items.insert(id);
+        self
+    }
+
+    /// Remove every edge from `from` to `to`, whatever its kind.
+    pub fn remove_edge(&mut self, from: Id, to: Id) -> &mut Changes {
+        self.removed_edges.insert((from, to));
+        self
+    }
+
+    /// Make the given item a root.
+    pub fn add_root(&mut self, id: Id) -> &mut Changes {
+        self.added_roots.insert(id);
+        self
+    }
+
+    /// Stop treating the given item as a root. It is kept if something else
+    /// still refers to it.
+    pub fn remove_root(&mut self, id: Id) -> &mut Changes {
+        self.removed_roots.insert(id);
+        self
+    }
+
+    /// Add all of the given changes to these ones.
+    pub fn extend(&mut self, other: &Changes) -> &mut Changes {
+        self.removed_items.extend(&other.removed_items);
+        self.removed_edges.extend(&other.removed_edges);
+        self.added_roots.extend(&other.added_roots);
+        self.removed_roots.extend(&other.removed_roots);
+        self
+    }
+
+    /// Are there no changes at all?
+    pub fn is_empty(&self) -> bool {
+        self.removed_items.is_empty()
+            && self.removed_edges.is_empty()
+            && self.added_roots.is_empty()
+            && self.removed_roots.is_empty()
+    }
+}
+
+impl Items {
+    /// Derive a new graph with the given changes made to this one.
+    ///
+    /// The new graph starts without any computed dominators or retained
+    /// sizes, so they reflect the changes once computed. Its total size is
+    /// the same as this graph's.
+    pub fn with_changes(&self, changes: &Changes) -> Items {
+        let removed = |id: &Id| changes.removed_items.contains(id);
+
+        let roots: BTreeSet<Id> = self
+            .neighbors(self.meta_root)
+            .filter(|id| !changes.removed_roots.contains(id))
+            .chain(changes.added_roots.iter().copied())
+            .filter(|id| !removed(id) && self.position(*id).is_some())
+            .collect();
+
+        let mut builder = ItemsBuilder::new(self.size);
+        for item in self.iter() {
+            let id = item.id();
+            if id == self.meta_root || removed(&id) {
+                continue;
+            }
+            if roots.contains(&id) {
+                builder.add_root(item.clone());
+            } else {
+                builder.add_item(item.clone());
+            }
+        }
+
+        for item in self.iter() {
+            let from = item.id();
+            if from == self.meta_root || removed(&from) {
+                continue;
+            }
+            for (to, kind) in self.edges(from) {
+                if removed(&to) || changes.removed_edges.contains(&(from, to)) {
+                    continue;
+                }
+                builder.add_edge(from, to, kind);
+            }
+        }
+
+        builder.finish()
+    }
+
+    /// The total size of the items that are reachable from the roots.
+    pub fn reachable_size(&self) -> u64 {
+        Dfs::new(self, self.meta_root)
+            .iter(self)
+            .map(|id| self[id].size())
+            .fold(0, u64::saturating_add)
+    }
+}
@@ -26,18 +26,19 @@ where
     S: AsRef<str>,
 {
     let from = from.as_ref();
-    let from = fs::File::open(from).expect(&format!("should open `{}` OK", from.display()));
+    let from =
+        fs::File::open(from).unwrap_or_else(|_| panic!("should open `{}` OK", from.display()));
     let from = io::BufReader::new(from);
 
     let to = to.as_ref();
-    let to = fs::File::create(to).expect(&format!("should open `{}` OK", to.display()));
+    let to = fs::File::create(to).unwrap_or_else(|_| panic!("should open `{}` OK", to.display()));
     let mut to = io::BufWriter::new(to);
 
     let pattern_str = pattern.as_ref();
     let pattern = regex::RegexBuilder::new(pattern_str)
         .case_insensitive(true)
         .build()
-        .expect(&format!("should create regex from '{}' OK", pattern_str));
+        .unwrap_or_else(|_| panic!("should create regex from '{}' OK", pattern_str));
 
     for line in from.lines() {
         let line = line.expect("should read line OK");
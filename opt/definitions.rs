@@ -33,6 +33,11 @@ pub enum Options {
     #[structopt(name = "monos")]
     Monos(Monos),
 
+    /// Roll the sizes of functions up into the crates, modules and types
+    /// they belong to.
+    #[structopt(name = "crates")]
+    Crates(Crates),
+
     /// Diff the old and new versions of a binary to see what sizes changed.
     #[structopt(name = "diff")]
     Diff(Diff),
@@ -41,6 +46,47 @@ pub enum Options {
     /// any exports or public functions.
     #[structopt(name = "garbage")]
     Garbage(Garbage),
+
+    /// Parse a binary and save the result as a snapshot, which every other
+    /// command accepts as input in place of the binary.
+    #[structopt(name = "snapshot")]
+    Snapshot(Snapshot),
+
+    /// Find out how many bytes hypothetical changes to the call graph, such
+    /// as removing an export or an edge, would save.
+    #[structopt(name = "whatif")]
+    WhatIf(WhatIf),
+
+    /// Check a binary's sizes against a file of budgets, and fail if any
+    /// budget is exceeded.
+    #[structopt(name = "check")]
+    Check(Check),
+
+    /// Show how the sizes of items, groups of items and the whole binary
+    /// changed over a series of builds, such as one per release.
+    #[structopt(name = "history")]
+    History(History),
+}
+
+/// Options for reading the input and classifying its items, shared by every
+/// command.
+#[cfg(feature = "cli")]
+#[derive(Clone, Debug, Default)]
+#[derive(StructOpt)]
+pub struct InputOptions {
+    /// A split debug info file to read symbols from, for stripped ELF
+    /// binaries. May be given more than once.
+    #[structopt(long = "debug-file", parse(from_os_str), number_of_values = 1)]
+    debug_files: Vec<path::PathBuf>,
+
+    /// A directory to search for split debug info, by build-id or
+    /// `.gnu_debuglink`. May be given more than once.
+    #[structopt(long = "debug-dir", parse(from_os_str), number_of_values = 1)]
+    debug_dirs: Vec<path::PathBuf>,
+
+    /// A file of rules to classify items into categories with.
+    #[structopt(long = "rules", parse(from_os_str))]
+    rules: Option<path::PathBuf>,
 }
 
 /// List the top code size offenders in a binary.
@@ -58,6 +104,11 @@ pub struct Top {
     #[structopt(long = "mode", default_value = "auto")]
     parse_mode: traits::ParseMode,
 
+    /// Options for reading and classifying the input.
+    #[cfg(feature = "cli")]
+    #[structopt(flatten)]
+    input_options: InputOptions,
+
     /// The destination to write the output to. Defaults to `stdout`.
     #[cfg(feature = "cli")]
     #[structopt(short = "o", default_value = "-")]
@@ -72,13 +123,41 @@ pub struct Top {
     #[structopt(short = "n", default_value = "4294967295")]
     max_items: u32,
 
-    /// Display retaining paths.
+    /// Display the shortest retaining paths of each item, from the roots that
+    /// reach it.
     #[structopt(short = "r", long = "retaining-paths")]
     retaining_paths: bool,
 
+    /// The maximum number of retaining paths to display for each item.
+    #[structopt(long = "max-paths", default_value = "10")]
+    max_paths: u32,
+
+    /// The maximum depth to display the retaining paths to.
+    #[structopt(long = "max-depth", default_value = "10")]
+    max_depth: u32,
+
     /// Sort list by retained size, rather than shallow size.
     #[structopt(long = "retained")]
     retained: bool,
+
+    /// Only consider items of these kinds: code, data, global, import, export,
+    /// type, table, memory, element, section, debug or misc. May be given
+    /// more than once.
+    #[structopt(long = "kind", use_delimiter = true, number_of_values = 1)]
+    kinds: Vec<ir::ItemKindTag>,
+
+    /// Only consider items in these categories, as classified by the rules
+    /// given with `--rules`. Items that no rule matches are in
+    /// `[uncategorized]`. May be given more than once.
+    #[structopt(long = "category", number_of_values = 1)]
+    categories: Vec<String>,
+
+    /// Show the sizes of groups of items rather than of each item: the
+    /// categories that the rules given with `--rules` classify items into, the
+    /// crates that code belongs to, the generic functions that code is a
+    /// monomorphization of, or the sections or kinds of items.
+    #[structopt(long = "group-by")]
+    group_by: Option<traits::GroupBy>,
 }
 
 impl Default for Top {
@@ -89,17 +168,52 @@ impl Default for Top {
             #[cfg(feature = "cli")]
             parse_mode: Default::default(),
             #[cfg(feature = "cli")]
+            input_options: Default::default(),
+            #[cfg(feature = "cli")]
             output_destination: Default::default(),
             #[cfg(feature = "cli")]
             output_format: Default::default(),
 
             max_items: 4_294_967_295,
             retaining_paths: false,
+            max_paths: 10,
+            max_depth: 10,
             retained: false,
+            kinds: Default::default(),
+            categories: Default::default(),
+            group_by: None,
         }
     }
 }
 
+impl Top {
+    // TODO: wasm-bindgen does not support sending Vec<String> across
+    // the wasm ABI boundary yet.
+
+    /// The kinds of items to consider. All kinds are considered if this is
+    /// empty.
+    pub fn kinds(&self) -> &[ir::ItemKindTag] {
+        &self.kinds
+    }
+
+    /// The categories of items to consider. All categories are considered if
+    /// this is empty.
+    pub fn categories(&self) -> &[String] {
+        &self.categories
+    }
+
+    /// What to group items by, if anything: `category`, `crate`, `generic`,
+    /// `section` or `kind`.
+    pub fn group_by(&self) -> Option<traits::GroupBy> {
+        self.group_by
+    }
+
+    /// Set what to group items by.
+    pub fn set_group_by(&mut self, group_by: Option<traits::GroupBy>) {
+        self.group_by = group_by;
+    }
+}
+
 #[wasm_bindgen]
 impl Top {
     /// Construct a new, default `Top`.
@@ -117,6 +231,16 @@ impl Top {
         self.retaining_paths
     }
 
+    /// The maximum number of retaining paths to display for each item.
+    pub fn max_paths(&self) -> u32 {
+        self.max_paths
+    }
+
+    /// The maximum depth to display the retaining paths to.
+    pub fn max_depth(&self) -> u32 {
+        self.max_depth
+    }
+
     /// Sort list by retained size, rather than shallow size.
     pub fn retained(&self) -> bool {
         self.retained
@@ -132,6 +256,16 @@ impl Top {
         self.retaining_paths = do_it;
     }
 
+    /// Set the maximum number of retaining paths to display for each item.
+    pub fn set_max_paths(&mut self, max_paths: u32) {
+        self.max_paths = max_paths;
+    }
+
+    /// Set the maximum depth to display the retaining paths to.
+    pub fn set_max_depth(&mut self, max_depth: u32) {
+        self.max_depth = max_depth;
+    }
+
     /// Set whether to sort list by retained size, rather than shallow size.
     pub fn set_retained(&mut self, do_it: bool) {
         self.retained = do_it;
@@ -153,6 +287,11 @@ pub struct Dominators {
     #[structopt(long = "mode", default_value = "auto")]
     parse_mode: traits::ParseMode,
 
+    /// Options for reading and classifying the input.
+    #[cfg(feature = "cli")]
+    #[structopt(flatten)]
+    input_options: InputOptions,
+
     /// The destination to write the output to. Defaults to `stdout`.
     #[cfg(feature = "cli")]
     #[structopt(short = "o", default_value = "-")]
@@ -177,6 +316,27 @@ pub struct Dominators {
     /// Whether or not `items` should be treated as regular expressions.
     #[structopt(long = "regex")]
     using_regexps: bool,
+
+    /// Only follow edges of these kinds: call, global, data, type, export,
+    /// table, start or other. May be given more than once.
+    #[structopt(long = "edges", use_delimiter = true, number_of_values = 1)]
+    edge_kinds: Vec<ir::EdgeKind>,
+
+    /// Don't follow edges of these kinds. May be given more than once.
+    #[structopt(long = "exclude-edges", use_delimiter = true, number_of_values = 1)]
+    excluded_edge_kinds: Vec<ir::EdgeKind>,
+
+    /// Only consider items of these kinds: code, data, global, import, export,
+    /// type, table, memory, element, section, debug or misc. May be given
+    /// more than once.
+    #[structopt(long = "kind", use_delimiter = true, number_of_values = 1)]
+    kinds: Vec<ir::ItemKindTag>,
+
+    /// Only consider items in these categories, as classified by the rules
+    /// given with `--rules`. Items that no rule matches are in
+    /// `[uncategorized]`. May be given more than once.
+    #[structopt(long = "category", number_of_values = 1)]
+    categories: Vec<String>,
 }
 
 impl Dominators {
@@ -187,6 +347,28 @@ impl Dominators {
     pub fn items(&self) -> &[String] {
         &self.items
     }
+
+    /// The kinds of edges to follow. All kinds are followed if this is empty.
+    pub fn edge_kinds(&self) -> &[ir::EdgeKind] {
+        &self.edge_kinds
+    }
+
+    /// The kinds of edges not to follow.
+    pub fn excluded_edge_kinds(&self) -> &[ir::EdgeKind] {
+        &self.excluded_edge_kinds
+    }
+
+    /// The kinds of items to consider. All kinds are considered if this is
+    /// empty.
+    pub fn kinds(&self) -> &[ir::ItemKindTag] {
+        &self.kinds
+    }
+
+    /// The categories of items to consider. All categories are considered if
+    /// this is empty.
+    pub fn categories(&self) -> &[String] {
+        &self.categories
+    }
 }
 
 #[wasm_bindgen]
@@ -243,6 +425,11 @@ pub struct Paths {
     #[structopt(long = "mode", default_value = "auto")]
     parse_mode: traits::ParseMode,
 
+    /// Options for reading and classifying the input.
+    #[cfg(feature = "cli")]
+    #[structopt(flatten)]
+    input_options: InputOptions,
+
     /// The destination to write the output to. Defaults to `stdout`.
     #[cfg(feature = "cli")]
     #[structopt(short = "o", default_value = "-")]
@@ -271,6 +458,21 @@ pub struct Paths {
     /// Whether or not `functions` should be treated as regular expressions.
     #[structopt(long = "regex")]
     using_regexps: bool,
+
+    /// Only follow edges of these kinds: call, global, data, type, export,
+    /// table, start or other. May be given more than once.
+    #[structopt(long = "edges", use_delimiter = true, number_of_values = 1)]
+    edge_kinds: Vec<ir::EdgeKind>,
+
+    /// Don't follow edges of these kinds. May be given more than once.
+    #[structopt(long = "exclude-edges", use_delimiter = true, number_of_values = 1)]
+    excluded_edge_kinds: Vec<ir::EdgeKind>,
+
+    /// Only consider items of these kinds: code, data, global, import, export,
+    /// type, table, memory, element, section, debug or misc. May be given
+    /// more than once.
+    #[structopt(long = "kind", use_delimiter = true, number_of_values = 1)]
+    kinds: Vec<ir::ItemKindTag>,
 }
 
 impl Default for Paths {
@@ -281,6 +483,8 @@ impl Default for Paths {
             #[cfg(feature = "cli")]
             parse_mode: Default::default(),
             #[cfg(feature = "cli")]
+            input_options: Default::default(),
+            #[cfg(feature = "cli")]
             output_destination: Default::default(),
             #[cfg(feature = "cli")]
             output_format: Default::default(),
@@ -290,6 +494,9 @@ impl Default for Paths {
             max_paths: 10,
             descending: false,
             using_regexps: false,
+            edge_kinds: Default::default(),
+            excluded_edge_kinds: Default::default(),
+            kinds: Default::default(),
         }
     }
 }
@@ -302,6 +509,22 @@ impl Paths {
     pub fn functions(&self) -> &[String] {
         &self.functions
     }
+
+    /// The kinds of edges to follow. All kinds are followed if this is empty.
+    pub fn edge_kinds(&self) -> &[ir::EdgeKind] {
+        &self.edge_kinds
+    }
+
+    /// The kinds of edges not to follow.
+    pub fn excluded_edge_kinds(&self) -> &[ir::EdgeKind] {
+        &self.excluded_edge_kinds
+    }
+
+    /// The kinds of items to consider. All kinds are considered if this is
+    /// empty.
+    pub fn kinds(&self) -> &[ir::ItemKindTag] {
+        &self.kinds
+    }
 }
 
 #[wasm_bindgen]
@@ -377,6 +600,11 @@ pub struct Monos {
     #[structopt(short = "d", long = "mode", default_value = "auto")]
     parse_mode: traits::ParseMode,
 
+    /// Options for reading and classifying the input.
+    #[cfg(feature = "cli")]
+    #[structopt(flatten)]
+    input_options: InputOptions,
+
     /// The destination to write the output to. Defaults to `stdout`.
     #[cfg(feature = "cli")]
     #[structopt(short = "o", default_value = "-")]
@@ -391,6 +619,12 @@ pub struct Monos {
     #[structopt(short = "g", long = "only-generics")]
     only_generics: bool,
 
+    /// Group the monomorphizations by the generic arguments they were
+    /// instantiated with, instead of by generic function, to find the types
+    /// that cause the most bloat.
+    #[structopt(short = "t", long = "by-type")]
+    by_type: bool,
+
     /// The maximum number of generics to list.
     #[structopt(short = "m", long = "max-generics", default_value = "10")]
     max_generics: u32,
@@ -418,6 +652,12 @@ pub struct Monos {
     /// Whether or not `names` should be treated as regular expressions.
     #[structopt(long = "regex")]
     using_regexps: bool,
+
+    /// Only consider items of these kinds: code, data, global, import, export,
+    /// type, table, memory, element, section, debug or misc. May be given
+    /// more than once.
+    #[structopt(long = "kind", use_delimiter = true, number_of_values = 1)]
+    kinds: Vec<ir::ItemKindTag>,
 }
 
 impl Default for Monos {
@@ -428,6 +668,8 @@ impl Default for Monos {
             #[cfg(feature = "cli")]
             parse_mode: Default::default(),
             #[cfg(feature = "cli")]
+            input_options: Default::default(),
+            #[cfg(feature = "cli")]
             output_destination: Default::default(),
             #[cfg(feature = "cli")]
             output_format: Default::default(),
@@ -435,6 +677,7 @@ impl Default for Monos {
             functions: Default::default(),
 
             only_generics: false,
+            by_type: false,
             max_generics: 10,
             max_monos: 10,
 
@@ -443,6 +686,7 @@ impl Default for Monos {
             all_monos: false,
 
             using_regexps: false,
+            kinds: Default::default(),
         }
     }
 }
@@ -455,6 +699,12 @@ impl Monos {
     pub fn functions(&self) -> &[String] {
         &self.functions
     }
+
+    /// The kinds of items to consider. All kinds are considered if this is
+    /// empty.
+    pub fn kinds(&self) -> &[ir::ItemKindTag] {
+        &self.kinds
+    }
 }
 
 #[wasm_bindgen]
@@ -469,6 +719,11 @@ impl Monos {
         self.only_generics
     }
 
+    /// Whether to group the monomorphizations by their generic arguments.
+    pub fn by_type(&self) -> bool {
+        self.by_type
+    }
+
     /// The maximum number of generics to list.
     pub fn max_generics(&self) -> u32 {
         if self.all_generics_and_monos || self.all_generics {
@@ -499,6 +754,11 @@ impl Monos {
         self.only_generics = do_it;
     }
 
+    /// Set whether to group the monomorphizations by their generic arguments.
+    pub fn set_by_type(&mut self, do_it: bool) {
+        self.by_type = do_it;
+    }
+
     /// Set the maximum number of generics to list.
     pub fn set_max_generics(&mut self, max: u32) {
         self.max_generics = max;
@@ -521,6 +781,131 @@ impl Monos {
     }
 }
 
+/// Roll the sizes of functions up into the crates, modules and types they
+/// belong to.
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+#[derive(StructOpt)]
+pub struct Crates {
+    /// The path to the input binary to size profile.
+    #[cfg(feature = "cli")]
+    #[structopt(parse(from_os_str))]
+    input: path::PathBuf,
+
+    /// The parse mode for the input binary data.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "mode", default_value = "auto")]
+    parse_mode: traits::ParseMode,
+
+    /// Options for reading and classifying the input.
+    #[cfg(feature = "cli")]
+    #[structopt(flatten)]
+    input_options: InputOptions,
+
+    /// The destination to write the output to. Defaults to `stdout`.
+    #[cfg(feature = "cli")]
+    #[structopt(short = "o", default_value = "-")]
+    output_destination: OutputDestination,
+
+    /// The format the output should be written in.
+    #[cfg(feature = "cli")]
+    #[structopt(short = "f", long = "format", default_value = "text")]
+    output_format: traits::OutputFormat,
+
+    /// The maximum depth of the tree to display: 1 for crates only, 2 for
+    /// their top-level modules and types too, and so on.
+    #[structopt(short = "d", default_value = "1")]
+    max_depth: u32,
+
+    /// The maximum number of crates, or of the children of a module or type,
+    /// to display.
+    #[structopt(short = "n", default_value = "10")]
+    max_items: u32,
+
+    /// Sort by retained size, rather than shallow size.
+    #[structopt(long = "retained")]
+    retained: bool,
+
+    /// Only consider items of these kinds: code, data, global, import, export,
+    /// type, table, memory, element, section, debug or misc. May be given
+    /// more than once.
+    #[structopt(long = "kind", use_delimiter = true, number_of_values = 1)]
+    kinds: Vec<ir::ItemKindTag>,
+}
+
+impl Default for Crates {
+    fn default() -> Crates {
+        Crates {
+            #[cfg(feature = "cli")]
+            input: Default::default(),
+            #[cfg(feature = "cli")]
+            parse_mode: Default::default(),
+            #[cfg(feature = "cli")]
+            input_options: Default::default(),
+            #[cfg(feature = "cli")]
+            output_destination: Default::default(),
+            #[cfg(feature = "cli")]
+            output_format: Default::default(),
+
+            max_depth: 1,
+            max_items: 10,
+            retained: false,
+            kinds: Default::default(),
+        }
+    }
+}
+
+impl Crates {
+    // TODO: wasm-bindgen does not support sending Vec<String> across
+    // the wasm ABI boundary yet.
+
+    /// The kinds of items to consider. All kinds are considered if this is
+    /// empty.
+    pub fn kinds(&self) -> &[ir::ItemKindTag] {
+        &self.kinds
+    }
+}
+
+#[wasm_bindgen]
+impl Crates {
+    /// Construct a new, default `Crates`.
+    pub fn new() -> Crates {
+        Crates::default()
+    }
+
+    /// The maximum depth of the tree to display.
+    pub fn max_depth(&self) -> u32 {
+        self.max_depth
+    }
+
+    /// The maximum number of crates, or of the children of a module or type,
+    /// to display.
+    pub fn max_items(&self) -> u32 {
+        self.max_items
+    }
+
+    /// Sort by retained size, rather than shallow size.
+    pub fn retained(&self) -> bool {
+        self.retained
+    }
+
+    /// Set the maximum depth of the tree to display.
+    pub fn set_max_depth(&mut self, max_depth: u32) {
+        self.max_depth = max_depth;
+    }
+
+    /// Set the maximum number of crates, or of the children of a module or
+    /// type, to display.
+    pub fn set_max_items(&mut self, max_items: u32) {
+        self.max_items = max_items;
+    }
+
+    /// Set whether to sort by retained size, rather than shallow size.
+    pub fn set_retained(&mut self, do_it: bool) {
+        self.retained = do_it;
+    }
+}
+
 /// Diff the old and new versions of a binary to see what sizes changed.
 #[wasm_bindgen]
 #[derive(Clone, Debug)]
@@ -536,6 +921,11 @@ pub struct Diff {
     #[structopt(long = "mode", default_value = "auto")]
     parse_mode: traits::ParseMode,
 
+    /// Options for reading and classifying the input.
+    #[cfg(feature = "cli")]
+    #[structopt(flatten)]
+    input_options: InputOptions,
+
     /// The path to the new version of the input binary.
     #[cfg(feature = "cli")]
     #[structopt(parse(from_os_str))]
@@ -565,6 +955,56 @@ pub struct Diff {
     /// Displays all items. Overrides -n <max_items>
     #[structopt(short = "a", long = "all")]
     all_items: bool,
+
+    /// Only consider items of these kinds: code, data, global, import, export,
+    /// type, table, memory, element, section, debug or misc. May be given
+    /// more than once.
+    #[structopt(long = "kind", use_delimiter = true, number_of_values = 1)]
+    kinds: Vec<ir::ItemKindTag>,
+
+    /// Only consider items in these categories, as classified by the rules
+    /// given with `--rules`. Items that no rule matches are in
+    /// `[uncategorized]`. May be given more than once.
+    #[structopt(long = "category", number_of_values = 1)]
+    categories: Vec<String>,
+
+
+    /// Show the sizes of groups of items rather than of each item: the
+    /// categories that the rules given with `--rules` classify items into, the
+    /// crates that code belongs to, the generic functions that code is a
+    /// monomorphization of, or the sections or kinds of items.
+    #[structopt(long = "group-by")]
+    group_by: Option<traits::GroupBy>,
+
+    /// Show the items that changed in each group under it, when grouping.
+    #[structopt(long = "expand")]
+    expand: bool,
+
+    /// Only match old and new items that have exactly the same name, rather
+    /// than also matching renamed items by their normalized names and shapes.
+    #[structopt(long = "exact-names")]
+    exact_names: bool,
+
+    /// Also show the old and new retained sizes of items, and how they changed.
+    #[structopt(long = "retained")]
+    retained: bool,
+
+    /// Only show items that changed in these ways: added, removed, grown,
+    /// shrunk or renamed. May be given more than once.
+    #[structopt(long = "change", use_delimiter = true, number_of_values = 1)]
+    changes: Vec<traits::Change>,
+
+    /// Explain why each item that is shown appeared, or why its retained size
+    /// grew: which items newly reference it, which new path from a root now
+    /// reaches it, and how its place in the dominator tree changed.
+    #[structopt(long = "explain")]
+    explain: bool,
+
+    /// Show the dominator trees of the old and new versions aligned by matched
+    /// items, with how the retained size of each subtree changed. Subtrees
+    /// that didn't change are collapsed.
+    #[structopt(long = "dominators")]
+    dominators: bool,
 }
 
 impl Default for Diff {
@@ -575,6 +1015,8 @@ impl Default for Diff {
             #[cfg(feature = "cli")]
             parse_mode: Default::default(),
             #[cfg(feature = "cli")]
+            input_options: Default::default(),
+            #[cfg(feature = "cli")]
             new_input: Default::default(),
             #[cfg(feature = "cli")]
             output_destination: Default::default(),
@@ -585,6 +1027,15 @@ impl Default for Diff {
             max_items: 20,
             using_regexps: false,
             all_items: false,
+            kinds: Default::default(),
+            categories: Default::default(),
+            group_by: None,
+            expand: false,
+            exact_names: false,
+            retained: false,
+            changes: Default::default(),
+            explain: false,
+            dominators: false,
         }
     }
 }
@@ -597,6 +1048,35 @@ impl Diff {
     pub fn items(&self) -> &[String] {
         &self.items
     }
+
+    /// The kinds of items to consider. All kinds are considered if this is
+    /// empty.
+    pub fn kinds(&self) -> &[ir::ItemKindTag] {
+        &self.kinds
+    }
+
+    /// The categories of items to consider. All categories are considered if
+    /// this is empty.
+    pub fn categories(&self) -> &[String] {
+        &self.categories
+    }
+
+    /// The ways of changing that items must have changed in to be shown. All
+    /// changed items are shown if this is empty.
+    pub fn changes(&self) -> &[traits::Change] {
+        &self.changes
+    }
+
+    /// What to group items by, if anything: `category`, `crate`, `generic`,
+    /// `section` or `kind`.
+    pub fn group_by(&self) -> Option<traits::GroupBy> {
+        self.group_by
+    }
+
+    /// Set what to group items by.
+    pub fn set_group_by(&mut self, group_by: Option<traits::GroupBy>) {
+        self.group_by = group_by;
+    }
 }
 
 #[wasm_bindgen]
@@ -625,6 +1105,58 @@ impl Diff {
     pub fn set_using_regexps(&mut self, using_regexps: bool) {
         self.using_regexps = using_regexps;
     }
+
+    /// Show the items that changed in each group under it.
+    pub fn expand(&self) -> bool {
+        self.expand
+    }
+
+    /// Set whether to show the items that changed in each group under it.
+    pub fn set_expand(&mut self, expand: bool) {
+        self.expand = expand;
+    }
+
+    /// Only match old and new items that have exactly the same name.
+    pub fn exact_names(&self) -> bool {
+        self.exact_names
+    }
+
+    /// Set whether to only match old and new items that have exactly the same
+    /// name.
+    pub fn set_exact_names(&mut self, exact_names: bool) {
+        self.exact_names = exact_names;
+    }
+
+    /// Also show the old and new retained sizes of items.
+    pub fn retained(&self) -> bool {
+        self.retained
+    }
+
+    /// Set whether to also show the old and new retained sizes of items.
+    pub fn set_retained(&mut self, retained: bool) {
+        self.retained = retained;
+    }
+
+    /// Explain why each item that is shown appeared or grew.
+    pub fn explain(&self) -> bool {
+        self.explain
+    }
+
+    /// Set whether to explain why each item that is shown appeared or grew.
+    pub fn set_explain(&mut self, explain: bool) {
+        self.explain = explain;
+    }
+
+    /// Show the aligned dominator trees of the old and new versions.
+    pub fn dominators(&self) -> bool {
+        self.dominators
+    }
+
+    /// Set whether to show the aligned dominator trees of the old and new
+    /// versions.
+    pub fn set_dominators(&mut self, dominators: bool) {
+        self.dominators = dominators;
+    }
 }
 
 /// Find and display code and data that is not transitively referenced by any
@@ -643,6 +1175,11 @@ pub struct Garbage {
     #[structopt(long = "mode", default_value = "auto")]
     parse_mode: traits::ParseMode,
 
+    /// Options for reading and classifying the input.
+    #[cfg(feature = "cli")]
+    #[structopt(flatten)]
+    input_options: InputOptions,
+
     /// The destination to write the output to. Defaults to `stdout`.
     #[cfg(feature = "cli")]
     #[structopt(short = "o", default_value = "-")]
@@ -664,6 +1201,34 @@ pub struct Garbage {
     /// Show data segments rather than summarizing them in a single line.
     #[structopt(long = "show-data-segments")]
     show_data_segments: bool,
+
+    /// Only follow edges of these kinds: call, global, data, type, export,
+    /// table, start or other. May be given more than once.
+    #[structopt(long = "edges", use_delimiter = true, number_of_values = 1)]
+    edge_kinds: Vec<ir::EdgeKind>,
+
+    /// Don't follow edges of these kinds. May be given more than once.
+    #[structopt(long = "exclude-edges", use_delimiter = true, number_of_values = 1)]
+    excluded_edge_kinds: Vec<ir::EdgeKind>,
+
+    /// Only consider items of these kinds: code, data, global, import, export,
+    /// type, table, memory, element, section, debug or misc. May be given
+    /// more than once.
+    #[structopt(long = "kind", use_delimiter = true, number_of_values = 1)]
+    kinds: Vec<ir::ItemKindTag>,
+
+    /// Only consider items in these categories, as classified by the rules
+    /// given with `--rules`. Items that no rule matches are in
+    /// `[uncategorized]`. May be given more than once.
+    #[structopt(long = "category", number_of_values = 1)]
+    categories: Vec<String>,
+
+    /// Show the sizes of groups of items rather than of each item: the
+    /// categories that the rules given with `--rules` classify items into, the
+    /// crates that code belongs to, the generic functions that code is a
+    /// monomorphization of, or the sections or kinds of items.
+    #[structopt(long = "group-by")]
+    group_by: Option<traits::GroupBy>,
 }
 
 impl Default for Garbage {
@@ -674,6 +1239,8 @@ impl Default for Garbage {
             #[cfg(feature = "cli")]
             parse_mode: Default::default(),
             #[cfg(feature = "cli")]
+            input_options: Default::default(),
+            #[cfg(feature = "cli")]
             output_destination: Default::default(),
             #[cfg(feature = "cli")]
             output_format: Default::default(),
@@ -681,10 +1248,53 @@ impl Default for Garbage {
             max_items: 10,
             all_items: false,
             show_data_segments: false,
+            edge_kinds: Default::default(),
+            excluded_edge_kinds: Default::default(),
+            kinds: Default::default(),
+            categories: Default::default(),
+            group_by: None,
         }
     }
 }
 
+impl Garbage {
+    // TODO: wasm-bindgen does not support sending Vec<EdgeKind> across the
+    // wasm ABI boundary yet.
+
+    /// The kinds of edges to follow. All kinds are followed if this is empty.
+    pub fn edge_kinds(&self) -> &[ir::EdgeKind] {
+        &self.edge_kinds
+    }
+
+    /// The kinds of edges not to follow.
+    pub fn excluded_edge_kinds(&self) -> &[ir::EdgeKind] {
+        &self.excluded_edge_kinds
+    }
+
+    /// The kinds of items to consider. All kinds are considered if this is
+    /// empty.
+    pub fn kinds(&self) -> &[ir::ItemKindTag] {
+        &self.kinds
+    }
+
+    /// The categories of items to consider. All categories are considered if
+    /// this is empty.
+    pub fn categories(&self) -> &[String] {
+        &self.categories
+    }
+
+    /// What to group items by, if anything: `category`, `crate`, `generic`,
+    /// `section` or `kind`.
+    pub fn group_by(&self) -> Option<traits::GroupBy> {
+        self.group_by
+    }
+
+    /// Set what to group items by.
+    pub fn set_group_by(&mut self, group_by: Option<traits::GroupBy>) {
+        self.group_by = group_by;
+    }
+}
+
 #[wasm_bindgen]
 impl Garbage {
     /// Construct a new, default `Garbage`
@@ -712,3 +1322,318 @@ impl Garbage {
         self.show_data_segments
     }
 }
+
+/// Parse a binary and save the result as a snapshot, which every other command
+/// accepts as input in place of the binary.
+#[wasm_bindgen]
+#[derive(Clone, Debug, Default)]
+#[derive(StructOpt)]
+pub struct Snapshot {
+    /// The path to the input binary to snapshot.
+    #[cfg(feature = "cli")]
+    #[structopt(parse(from_os_str))]
+    input: path::PathBuf,
+
+    /// The parse mode for the input binary data.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "mode", default_value = "auto")]
+    parse_mode: traits::ParseMode,
+
+    /// Options for reading and classifying the input.
+    #[cfg(feature = "cli")]
+    #[structopt(flatten)]
+    input_options: InputOptions,
+
+    /// The destination to write the snapshot to. Defaults to `stdout`.
+    #[cfg(feature = "cli")]
+    #[structopt(short = "o", default_value = "-")]
+    output_destination: OutputDestination,
+}
+
+#[wasm_bindgen]
+impl Snapshot {
+    /// Construct a new, default `Snapshot`.
+    pub fn new() -> Snapshot {
+        Snapshot::default()
+    }
+}
+
+/// Find out how many bytes hypothetical changes to the call graph, such as
+/// removing an export or an edge, would save.
+#[wasm_bindgen]
+#[derive(Clone, Debug, Default)]
+#[derive(StructOpt)]
+pub struct WhatIf {
+    /// The path to the input binary to size profile.
+    #[cfg(feature = "cli")]
+    #[structopt(parse(from_os_str))]
+    input: path::PathBuf,
+
+    /// The parse mode for the input binary data.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "mode", default_value = "auto")]
+    parse_mode: traits::ParseMode,
+
+    /// Options for reading and classifying the input.
+    #[cfg(feature = "cli")]
+    #[structopt(flatten)]
+    input_options: InputOptions,
+
+    /// The destination to write the output to. Defaults to `stdout`.
+    #[cfg(feature = "cli")]
+    #[structopt(short = "o", default_value = "-")]
+    output_destination: OutputDestination,
+
+    /// The format the output should be written in.
+    #[cfg(feature = "cli")]
+    #[structopt(short = "f", long = "format", default_value = "text")]
+    output_format: traits::OutputFormat,
+
+    /// Remove the export with the given name. May be given more than once.
+    #[structopt(long = "remove-export", number_of_values = 1)]
+    removed_exports: Vec<String>,
+
+    /// Remove the items with the given name. May be given more than once.
+    #[structopt(long = "remove-item", number_of_values = 1)]
+    removed_items: Vec<String>,
+
+    /// Remove the edges between two items, given as `from->to`. May be given
+    /// more than once.
+    #[structopt(long = "remove-edge", number_of_values = 1)]
+    removed_edges: Vec<String>,
+
+    /// Make the items with the given name roots. May be given more than once.
+    #[structopt(long = "add-root", number_of_values = 1)]
+    added_roots: Vec<String>,
+
+    /// Stop treating the items with the given name as roots. May be given
+    /// more than once.
+    #[structopt(long = "remove-root", number_of_values = 1)]
+    removed_roots: Vec<String>,
+}
+
+impl WhatIf {
+    // TODO: wasm-bindgen does not support sending Vec<String> across
+    // the wasm ABI boundary yet.
+
+    /// The names of the exports to remove.
+    pub fn removed_exports(&self) -> &[String] {
+        &self.removed_exports
+    }
+
+    /// The names of the items to remove.
+    pub fn removed_items(&self) -> &[String] {
+        &self.removed_items
+    }
+
+    /// The edges to remove, as `from->to`.
+    pub fn removed_edges(&self) -> &[String] {
+        &self.removed_edges
+    }
+
+    /// The names of the items to make roots.
+    pub fn added_roots(&self) -> &[String] {
+        &self.added_roots
+    }
+
+    /// The names of the items to no longer treat as roots.
+    pub fn removed_roots(&self) -> &[String] {
+        &self.removed_roots
+    }
+}
+
+#[wasm_bindgen]
+impl WhatIf {
+    /// Construct a new, default `WhatIf`.
+    pub fn new() -> WhatIf {
+        WhatIf::default()
+    }
+}
+
+/// Check a binary's sizes against a file of budgets, and fail if any budget is
+/// exceeded.
+#[wasm_bindgen]
+#[derive(Clone, Debug, Default)]
+#[derive(StructOpt)]
+pub struct Check {
+    /// The path to the input binary to check.
+    #[cfg(feature = "cli")]
+    #[structopt(parse(from_os_str))]
+    input: path::PathBuf,
+
+    /// The parse mode for the input binary data.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "mode", default_value = "auto")]
+    parse_mode: traits::ParseMode,
+
+    /// Options for reading and classifying the input.
+    #[cfg(feature = "cli")]
+    #[structopt(flatten)]
+    input_options: InputOptions,
+
+    /// The destination to write the output to. Defaults to `stdout`.
+    #[cfg(feature = "cli")]
+    #[structopt(short = "o", default_value = "-")]
+    output_destination: OutputDestination,
+
+    /// The format the output should be written in.
+    #[cfg(feature = "cli")]
+    #[structopt(short = "f", long = "format", default_value = "text")]
+    output_format: traits::OutputFormat,
+
+    /// The file of budgets to check the binary against.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "budget", parse(from_os_str))]
+    budget: path::PathBuf,
+
+    /// An earlier version of the binary, or a snapshot of it, to check growth
+    /// budgets against.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "baseline", parse(from_os_str))]
+    baseline: Option<path::PathBuf>,
+}
+
+#[wasm_bindgen]
+impl Check {
+    /// Construct a new, default `Check`.
+    pub fn new() -> Check {
+        Check::default()
+    }
+}
+
+/// Show how the sizes of items, groups of items and the whole binary changed
+/// over a series of builds.
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+#[derive(StructOpt)]
+pub struct History {
+    /// The paths to the builds, binaries or snapshots, oldest first.
+    #[cfg(feature = "cli")]
+    #[structopt(parse(from_os_str), required = true, min_values = 2)]
+    inputs: Vec<path::PathBuf>,
+
+    /// The parse mode for the input binary data.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "mode", default_value = "auto")]
+    parse_mode: traits::ParseMode,
+
+    /// Options for reading and classifying the input.
+    #[cfg(feature = "cli")]
+    #[structopt(flatten)]
+    input_options: InputOptions,
+
+    /// The destination to write the output to. Defaults to `stdout`.
+    #[cfg(feature = "cli")]
+    #[structopt(short = "o", default_value = "-")]
+    output_destination: OutputDestination,
+
+    /// The format the output should be written in.
+    #[cfg(feature = "cli")]
+    #[structopt(short = "f", long = "format", default_value = "text")]
+    output_format: traits::OutputFormat,
+
+    /// The names to show for the builds, in the same order. Defaults to their
+    /// file names. May be given more than once.
+    #[structopt(long = "label", number_of_values = 1)]
+    labels: Vec<String>,
+
+    /// The maximum number of items to display.
+    #[structopt(short = "n", default_value = "20")]
+    max_items: u32,
+
+    /// Displays all items. Overrides -n <max_items>
+    #[structopt(short = "a", long = "all")]
+    all_items: bool,
+
+    /// Show the sizes of groups of items rather than of each item: the
+    /// categories that the rules given with `--rules` classify items into, the
+    /// crates that code belongs to, the generic functions that code is a
+    /// monomorphization of, or the sections or kinds of items.
+    #[structopt(long = "group-by")]
+    group_by: Option<traits::GroupBy>,
+
+    /// Only match items in consecutive builds that have exactly the same name,
+    /// rather than also matching renamed items by their normalized names and
+    /// shapes.
+    #[structopt(long = "exact-names")]
+    exact_names: bool,
+}
+
+impl Default for History {
+    fn default() -> History {
+        History {
+            #[cfg(feature = "cli")]
+            inputs: Default::default(),
+            #[cfg(feature = "cli")]
+            parse_mode: Default::default(),
+            #[cfg(feature = "cli")]
+            input_options: Default::default(),
+            #[cfg(feature = "cli")]
+            output_destination: Default::default(),
+            #[cfg(feature = "cli")]
+            output_format: Default::default(),
+
+            labels: Default::default(),
+            max_items: 20,
+            all_items: false,
+            group_by: None,
+            exact_names: false,
+        }
+    }
+}
+
+impl History {
+    // TODO: wasm-bindgen does not support sending Vec<String> across
+    // the wasm ABI boundary yet.
+
+    /// The names to show for the builds. The analysis is given them in
+    /// place of the file names when this is empty.
+    pub fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    /// What to group items by, if anything: `category`, `crate`, `generic`,
+    /// `section` or `kind`.
+    pub fn group_by(&self) -> Option<traits::GroupBy> {
+        self.group_by
+    }
+
+    /// Set what to group items by.
+    pub fn set_group_by(&mut self, group_by: Option<traits::GroupBy>) {
+        self.group_by = group_by;
+    }
+}
+
+#[wasm_bindgen]
+impl History {
+    /// Construct a new, default `History`.
+    pub fn new() -> History {
+        History::default()
+    }
+
+    /// The maximum number of items to display.
+    pub fn max_items(&self) -> u32 {
+        if self.all_items {
+            u32::MAX
+        } else {
+            self.max_items
+        }
+    }
+
+    /// Set the maximum number of items to display.
+    pub fn set_max_items(&mut self, n: u32) {
+        self.max_items = n;
+        self.all_items = false;
+    }
+
+    /// Only match items in consecutive builds that have exactly the same name.
+    pub fn exact_names(&self) -> bool {
+        self.exact_names
+    }
+
+    /// Set whether to only match items in consecutive builds that have
+    /// exactly the same name.
+    pub fn set_exact_names(&mut self, exact_names: bool) {
+        self.exact_names = exact_names;
+    }
+}
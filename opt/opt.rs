@@ -3,6 +3,7 @@
 #![deny(missing_debug_implementations)]
 
 use cfg_if::cfg_if;
+use twiggy_ir as ir;
 use twiggy_traits as traits;
 
 cfg_if! {
@@ -16,8 +17,6 @@ cfg_if! {
     }
 }
 
-use std::u32;
-
 cfg_if! {
     if #[cfg(feature = "cli")] {
         use std::fs;
@@ -33,11 +32,31 @@ cfg_if! {
             /// Get the input data parse mode.
             fn parse_mode(&self) -> traits::ParseMode;
 
+            /// Get the options for reading the input and classifying its
+            /// items.
+            fn input_options(&self) -> &InputOptions;
+
+            /// Get the split debug info files to read symbols from.
+            fn debug_files(&self) -> &[path::PathBuf] {
+                &self.input_options().debug_files
+            }
+
+            /// Get the directories to search for split debug info.
+            fn debug_dirs(&self) -> &[path::PathBuf] {
+                &self.input_options().debug_dirs
+            }
+
             /// Get the output destination.
             fn output_destination(&self) -> &OutputDestination;
 
             /// Get the output format.
             fn output_format(&self) -> traits::OutputFormat;
+
+            /// Get the file of rules to classify items into categories with,
+            /// if any.
+            fn rules(&self) -> Option<&path::Path> {
+                self.input_options().rules.as_deref()
+            }
         }
 
         impl CommonCliOptions for Options {
@@ -47,8 +66,13 @@ cfg_if! {
                     Options::Dominators(ref doms) => doms.input(),
                     Options::Paths(ref paths) => paths.input(),
                     Options::Monos(ref monos) => monos.input(),
+                    Options::Crates(ref crates) => crates.input(),
                     Options::Diff(ref diff) => diff.input(),
                     Options::Garbage(ref garbo) => garbo.input(),
+                    Options::Snapshot(ref snapshot) => snapshot.input(),
+                    Options::WhatIf(ref whatif) => whatif.input(),
+                    Options::Check(ref check) => check.input(),
+                    Options::History(ref history) => history.input(),
                 }
             }
 
@@ -58,8 +82,29 @@ cfg_if! {
                     Options::Dominators(ref doms) => doms.parse_mode(),
                     Options::Paths(ref paths) => paths.parse_mode(),
                     Options::Monos(ref monos) => monos.parse_mode(),
+                    Options::Crates(ref crates) => crates.parse_mode(),
                     Options::Diff(ref diff) => diff.parse_mode(),
                     Options::Garbage(ref garbo) => garbo.parse_mode(),
+                    Options::Snapshot(ref snapshot) => snapshot.parse_mode(),
+                    Options::WhatIf(ref whatif) => whatif.parse_mode(),
+                    Options::Check(ref check) => check.parse_mode(),
+                    Options::History(ref history) => history.parse_mode(),
+                }
+            }
+
+            fn input_options(&self) -> &InputOptions {
+                match *self {
+                    Options::Top(ref top) => top.input_options(),
+                    Options::Dominators(ref doms) => doms.input_options(),
+                    Options::Paths(ref paths) => paths.input_options(),
+                    Options::Monos(ref monos) => monos.input_options(),
+                    Options::Crates(ref crates) => crates.input_options(),
+                    Options::Diff(ref diff) => diff.input_options(),
+                    Options::Garbage(ref garbo) => garbo.input_options(),
+                    Options::Snapshot(ref snapshot) => snapshot.input_options(),
+                    Options::WhatIf(ref whatif) => whatif.input_options(),
+                    Options::Check(ref check) => check.input_options(),
+                    Options::History(ref history) => history.input_options(),
                 }
             }
 
@@ -69,8 +114,13 @@ cfg_if! {
                     Options::Dominators(ref doms) => doms.output_destination(),
                     Options::Paths(ref paths) => paths.output_destination(),
                     Options::Monos(ref monos) => monos.output_destination(),
+                    Options::Crates(ref crates) => crates.output_destination(),
                     Options::Diff(ref diff) => diff.output_destination(),
                     Options::Garbage(ref garbo) => garbo.output_destination(),
+                    Options::Snapshot(ref snapshot) => snapshot.output_destination(),
+                    Options::WhatIf(ref whatif) => whatif.output_destination(),
+                    Options::Check(ref check) => check.output_destination(),
+                    Options::History(ref history) => history.output_destination(),
                 }
             }
 
@@ -80,8 +130,13 @@ cfg_if! {
                     Options::Dominators(ref doms) => doms.output_format(),
                     Options::Paths(ref paths) => paths.output_format(),
                     Options::Monos(ref monos) => monos.output_format(),
+                    Options::Crates(ref crates) => crates.output_format(),
                     Options::Diff(ref diff) => diff.output_format(),
                     Options::Garbage(ref garbo) => garbo.output_format(),
+                    Options::Snapshot(ref snapshot) => snapshot.output_format(),
+                    Options::WhatIf(ref whatif) => whatif.output_format(),
+                    Options::Check(ref check) => check.output_format(),
+                    Options::History(ref history) => history.output_format(),
                 }
             }
         }
@@ -95,6 +150,10 @@ cfg_if! {
                 self.parse_mode
             }
 
+            fn input_options(&self) -> &InputOptions {
+                &self.input_options
+            }
+
             fn output_destination(&self) -> &OutputDestination {
                 &self.output_destination
             }
@@ -113,6 +172,10 @@ cfg_if! {
                 self.parse_mode
             }
 
+            fn input_options(&self) -> &InputOptions {
+                &self.input_options
+            }
+
             fn output_destination(&self) -> &OutputDestination {
                 &self.output_destination
             }
@@ -131,6 +194,10 @@ cfg_if! {
                 self.parse_mode
             }
 
+            fn input_options(&self) -> &InputOptions {
+                &self.input_options
+            }
+
             fn output_destination(&self) -> &OutputDestination {
                 &self.output_destination
             }
@@ -149,6 +216,10 @@ cfg_if! {
                 self.parse_mode
             }
 
+            fn input_options(&self) -> &InputOptions {
+                &self.input_options
+            }
+
             fn output_destination(&self) -> &OutputDestination {
                 &self.output_destination
             }
@@ -167,6 +238,10 @@ cfg_if! {
                 self.parse_mode
             }
 
+            fn input_options(&self) -> &InputOptions {
+                &self.input_options
+            }
+
             fn output_destination(&self) -> &OutputDestination {
                 &self.output_destination
             }
@@ -192,6 +267,134 @@ cfg_if! {
                 self.parse_mode
             }
 
+            fn input_options(&self) -> &InputOptions {
+                &self.input_options
+            }
+
+            fn output_destination(&self) -> &OutputDestination {
+                &self.output_destination
+            }
+
+            fn output_format(&self) -> traits::OutputFormat {
+                self.output_format
+            }
+        }
+
+        impl CommonCliOptions for Crates {
+            fn input(&self) -> &path::Path {
+                &self.input
+            }
+
+            fn parse_mode(&self) -> traits::ParseMode {
+                self.parse_mode
+            }
+
+            fn input_options(&self) -> &InputOptions {
+                &self.input_options
+            }
+
+            fn output_destination(&self) -> &OutputDestination {
+                &self.output_destination
+            }
+
+            fn output_format(&self) -> traits::OutputFormat {
+                self.output_format
+            }
+        }
+
+        impl CommonCliOptions for WhatIf {
+            fn input(&self) -> &path::Path {
+                &self.input
+            }
+
+            fn parse_mode(&self) -> traits::ParseMode {
+                self.parse_mode
+            }
+
+            fn input_options(&self) -> &InputOptions {
+                &self.input_options
+            }
+
+            fn output_destination(&self) -> &OutputDestination {
+                &self.output_destination
+            }
+
+            fn output_format(&self) -> traits::OutputFormat {
+                self.output_format
+            }
+        }
+
+        impl CommonCliOptions for Snapshot {
+            fn input(&self) -> &path::Path {
+                &self.input
+            }
+
+            fn parse_mode(&self) -> traits::ParseMode {
+                self.parse_mode
+            }
+
+            fn input_options(&self) -> &InputOptions {
+                &self.input_options
+            }
+
+            fn output_destination(&self) -> &OutputDestination {
+                &self.output_destination
+            }
+
+            fn output_format(&self) -> traits::OutputFormat {
+                // Snapshots have a format of their own.
+                Default::default()
+            }
+        }
+
+        impl CommonCliOptions for Check {
+            fn input(&self) -> &path::Path {
+                &self.input
+            }
+
+            fn parse_mode(&self) -> traits::ParseMode {
+                self.parse_mode
+            }
+
+            fn input_options(&self) -> &InputOptions {
+                &self.input_options
+            }
+
+            fn output_destination(&self) -> &OutputDestination {
+                &self.output_destination
+            }
+
+            fn output_format(&self) -> traits::OutputFormat {
+                self.output_format
+            }
+        }
+
+        impl Check {
+            /// The path to the file of budgets to check against.
+            pub fn budget(&self) -> &path::Path {
+                &self.budget
+            }
+
+            /// The path to the baseline binary or snapshot to check growth
+            /// against, if any.
+            pub fn baseline(&self) -> Option<&path::Path> {
+                self.baseline.as_deref()
+            }
+        }
+
+        impl CommonCliOptions for History {
+            fn input(&self) -> &path::Path {
+                &self.inputs[0]
+            }
+
+            fn parse_mode(&self) -> traits::ParseMode {
+                self.parse_mode
+            }
+
+            fn input_options(&self) -> &InputOptions {
+                &self.input_options
+            }
+
             fn output_destination(&self) -> &OutputDestination {
                 &self.output_destination
             }
@@ -201,22 +404,24 @@ cfg_if! {
             }
         }
 
+        impl History {
+            /// The paths to the builds, oldest first.
+            pub fn inputs(&self) -> &[path::PathBuf] {
+                &self.inputs
+            }
+        }
+
         /// Where to output results.
-        #[derive(Clone, Debug)]
+        #[derive(Clone, Debug, Default)]
         pub enum OutputDestination {
             /// Emit the results to `stdout`.
+            #[default]
             Stdout,
 
             /// Write the results to a file at the given path.
             Path(path::PathBuf),
         }
 
-        impl Default for OutputDestination {
-            fn default() -> OutputDestination {
-                OutputDestination::Stdout
-            }
-        }
-
         impl FromStr for OutputDestination {
             type Err = anyhow::Error;
 
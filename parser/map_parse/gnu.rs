@@ -0,0 +1,237 @@
+//! GNU ld's map files.
+//!
+//! ```text
+//! Linker script and memory map
+//!
+//! .text           0x0000000000401000      0x1b5
+//!  *(.text .stub .text.*)
+//!  .text.main     0x0000000000401026       0x13 main.o
+//!                 0x0000000000401026                main
+//!  .text.count_vowels
+//!                 0x0000000000401084       0x48 libutil.a(strutil.o)
+//!                 0x0000000000401084                count_vowels
+//! ```
+
+use super::{Contribution, LinkerMap, Symbol};
+
+const MEMBERS_HEADER: &str = "Archive member included to satisfy reference by file (symbol)";
+const SCRIPT_HEADER: &str = "Linker script and memory map";
+const CREF_HEADER: &str = "Cross Reference Table";
+
+/// The parts of the map file we care about.
+enum Part {
+    Other,
+    Members,
+    Script,
+}
+
+pub(super) fn sniff(text: &str) -> bool {
+    text.lines().any(|line| {
+        line == MEMBERS_HEADER || line == SCRIPT_HEADER || line == "Memory Configuration"
+    })
+}
+
+pub(super) fn parse(text: &str) -> LinkerMap {
+    let mut map = LinkerMap::default();
+    let mut part = Part::Other;
+    let mut lines = text.lines();
+
+    // A member or section name that was too long to share its line with the
+    // rest of its entry.
+    let mut pending: Option<String> = None;
+    // Whether we are inside an output section that is loaded at runtime, and
+    // whether it holds code.
+    let mut output: Option<bool> = None;
+
+    while let Some(line) = lines.next() {
+        match line {
+            MEMBERS_HEADER => {
+                part = Part::Members;
+                continue;
+            }
+            SCRIPT_HEADER => {
+                part = Part::Script;
+                continue;
+            }
+            CREF_HEADER => {
+                // The table starts after its `Symbol    File` header.
+                let header = lines.by_ref().find(|line| line.starts_with("Symbol"));
+                let column = header.and_then(|header| header.find("File")).unwrap_or(50);
+                map.cross_refs = super::parse_cross_refs(lines, column);
+                break;
+            }
+            "Allocating common symbols" | "Discarded input sections" | "Memory Configuration" => {
+                part = Part::Other;
+                continue;
+            }
+            _ => {}
+        }
+
+        match part {
+            Part::Other => {}
+            Part::Members => {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                if !line.starts_with(char::is_whitespace) {
+                    let member = line.split_whitespace().next().unwrap();
+                    let rest = line[member.len()..].trim();
+                    if rest.is_empty() {
+                        pending = Some(member.to_string());
+                    } else {
+                        map.members
+                            .push((referencing_file(rest).to_string(), member.to_string()));
+                    }
+                } else if let Some(member) = pending.take() {
+                    map.members
+                        .push((referencing_file(line.trim()).to_string(), member));
+                }
+            }
+            Part::Script => parse_script_line(&mut map, line, &mut pending, &mut output),
+        }
+    }
+
+    map
+}
+
+/// Strip the `(symbol)` off of a `file (symbol)` archive member reference.
+fn referencing_file(reference: &str) -> &str {
+    match reference.rfind(" (") {
+        Some(paren) => &reference[..paren],
+        None => reference,
+    }
+}
+
+fn parse_script_line(
+    map: &mut LinkerMap,
+    line: &str,
+    pending: &mut Option<String>,
+    output: &mut Option<bool>,
+) {
+    if line.trim().is_empty() {
+        *pending = None;
+        return;
+    }
+
+    let indent = line.len() - line.trim_start().len();
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+
+    // The rest of an entry whose name was too long for its own line.
+    if indent > 1 {
+        if let Some(name) = pending.take() {
+            match *output {
+                None if tokens.len() >= 2 => start_output_section(map, &name, &tokens, output),
+                Some(code) if tokens.len() >= 3 => {
+                    let object = super::after_tokens(line, 2);
+                    add_input_section(map, &name, tokens[0], tokens[1], object, code);
+                }
+                _ => {}
+            }
+            return;
+        }
+    }
+    *pending = None;
+
+    match indent {
+        // An output section, or a top level command like `LOAD main.o`.
+        0 => {
+            *output = None;
+            if tokens.len() == 1 && !tokens[0].contains('(') {
+                *pending = Some(tokens[0].to_string());
+            } else if tokens.len() >= 2 && tokens[1].starts_with("0x") {
+                start_output_section(map, tokens[0], &tokens[1..], output);
+            }
+        }
+
+        // An input section, or an input section pattern like `*(.text)`.
+        1 => {
+            let code = match *output {
+                Some(code) => code,
+                None => return,
+            };
+            if tokens[0].starts_with('*') {
+                return;
+            }
+            if tokens.len() == 1 {
+                *pending = Some(tokens[0].to_string());
+            } else if tokens.len() >= 4 && tokens[1].starts_with("0x") {
+                let object = super::after_tokens(line, 3);
+                add_input_section(map, tokens[0], tokens[1], tokens[2], object, code);
+            }
+        }
+
+        // A symbol, or an assignment like `0x... . = ALIGN (0x10)`.
+        _ => {
+            if output.is_none() || tokens.len() < 2 {
+                return;
+            }
+            let address = match super::parse_hex(tokens[0]) {
+                Some(address) if tokens[0].starts_with("0x") => address,
+                _ => return,
+            };
+            let name = super::after_tokens(line, 1);
+            if name.contains('=')
+                || name.starts_with("0x")
+                || name.starts_with("PROVIDE")
+                || name.starts_with("ASSERT")
+                || name.starts_with('[')
+            {
+                return;
+            }
+            let contribution = match map.contributions.last_mut() {
+                Some(contribution) => contribution,
+                None => return,
+            };
+            // Contributions whose end overflows are skipped, with a warning,
+            // when the map is turned into items.
+            let end = contribution.address.checked_add(contribution.size);
+            if address >= contribution.address && end.is_some_and(|end| address < end) {
+                contribution.symbols.push(Symbol {
+                    name: name.to_string(),
+                    address,
+                    size: 0,
+                });
+            }
+        }
+    }
+}
+
+/// Start an output section, given its name and its `address size ...`.
+fn start_output_section(
+    map: &mut LinkerMap,
+    name: &str,
+    tokens: &[&str],
+    output: &mut Option<bool>,
+) {
+    if super::is_unallocated_section(name) {
+        *output = None;
+        return;
+    }
+    let size = tokens.get(1).and_then(|size| super::parse_hex(size));
+    map.size = map.size.saturating_add(size.unwrap_or(0));
+    *output = Some(super::is_code_section(name));
+}
+
+/// Add an input section, unless it is empty.
+fn add_input_section(
+    map: &mut LinkerMap,
+    name: &str,
+    address: &str,
+    size: &str,
+    object: &str,
+    code: bool,
+) {
+    let (address, size) = match (super::parse_hex(address), super::parse_hex(size)) {
+        (Some(address), Some(size)) if size > 0 => (address, size),
+        _ => return,
+    };
+
+    map.contributions.push(Contribution {
+        object: object.to_string(),
+        section: name.to_string(),
+        address,
+        size,
+        code,
+        symbols: vec![],
+    });
+}
@@ -0,0 +1,141 @@
+//! lld's map files.
+//!
+//! ```text
+//!              VMA              LMA     Size Align Out     In      Symbol
+//!           201000           201000      1b5    16 .text
+//!           201026           201026       13    16         main.o:(.text.main)
+//!           201026           201026       13     1                 main
+//! ```
+
+use super::{Contribution, LinkerMap, Symbol};
+
+/// The columns of the map, found from its header.
+struct Columns {
+    /// How many numeric columns (`VMA`, `LMA`, `Size`, `Align`) there are.
+    numbers: usize,
+    input: usize,
+    symbol: usize,
+}
+
+impl Columns {
+    fn from_header(line: &str) -> Option<Columns> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let numbers = tokens.iter().position(|&token| token == "Out")?;
+        if tokens.get(numbers + 1) != Some(&"In") || tokens.get(numbers + 2) != Some(&"Symbol") {
+            return None;
+        }
+        if tokens[0] != "VMA" && tokens[0] != "Address" {
+            return None;
+        }
+        Some(Columns {
+            numbers,
+            input: line.find(" In ")? + 1,
+            symbol: line.find(" Symbol")? + 1,
+        })
+    }
+}
+
+pub(super) fn sniff(text: &str) -> bool {
+    text.lines()
+        .find(|line| !line.trim().is_empty())
+        .and_then(Columns::from_header)
+        .is_some()
+}
+
+pub(super) fn parse(text: &str) -> LinkerMap {
+    let mut map = LinkerMap::default();
+    let mut lines = text.lines().skip_while(|line| line.trim().is_empty());
+    let columns = match lines.next().and_then(Columns::from_header) {
+        Some(columns) => columns,
+        None => return map,
+    };
+
+    // Whether we are inside an output section that is loaded at runtime, and
+    // whether it holds code.
+    let mut output: Option<bool> = None;
+
+    while let Some(line) = lines.next() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        // With `--cref`, the cross reference table follows the memory map.
+        if line.starts_with("Symbol") {
+            let column = line.find("File").unwrap_or(50);
+            map.cross_refs = super::parse_cross_refs(lines, column);
+            break;
+        }
+
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if tokens.len() <= columns.numbers {
+            continue;
+        }
+        let numbers: Option<Vec<u64>> = tokens[..columns.numbers]
+            .iter()
+            .map(|token| super::parse_hex(token))
+            .collect();
+        let numbers = match numbers {
+            Some(numbers) => numbers,
+            None => continue,
+        };
+        let address = numbers[0];
+        // The size comes right before the alignment.
+        let size = numbers[columns.numbers - 2];
+
+        let name = super::after_tokens(line, columns.numbers);
+        let column = line.len() - name.len();
+        // Assignments like `. = ALIGN(16)` and `__bss_start = .`.
+        if name.contains(" = ") {
+            continue;
+        }
+
+        if column >= columns.symbol {
+            if output.is_none() {
+                continue;
+            }
+            let contribution = match map.contributions.last_mut() {
+                Some(contribution) => contribution,
+                None => continue,
+            };
+            // Contributions whose end overflows are skipped, with a warning,
+            // when the map is turned into items.
+            let end = contribution.address.checked_add(contribution.size);
+            if address >= contribution.address && end.is_some_and(|end| address < end) {
+                contribution.symbols.push(Symbol {
+                    name: name.to_string(),
+                    address,
+                    size,
+                });
+            }
+        } else if column >= columns.input {
+            let code = match output {
+                Some(code) => code,
+                None => continue,
+            };
+            let (object, section) = match name.rfind(":(") {
+                Some(colon) if name.ends_with(')') => {
+                    (&name[..colon], &name[colon + 2..name.len() - 1])
+                }
+                _ => continue,
+            };
+            if size == 0 {
+                continue;
+            }
+            map.contributions.push(Contribution {
+                object: object.to_string(),
+                section: section.to_string(),
+                address,
+                size,
+                code,
+                symbols: vec![],
+            });
+        } else if super::is_unallocated_section(name) {
+            output = None;
+        } else {
+            map.size = map.size.saturating_add(size);
+            output = Some(super::is_code_section(name));
+        }
+    }
+
+    map
+}
@@ -0,0 +1,320 @@
+//! Parsing of linker map files, as written by GNU ld (`-Map`), lld (`-Map`)
+//! and MSVC's link.exe (`/MAP`).
+//!
+//! Each linker's format is first read into a `LinkerMap`, which is then
+//! turned into IR items: one item per symbol (or per input section when it
+//! has no symbols), grouped under an item for the object file that
+//! contributed it, and an item for the archive that object was pulled from.
+
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+use twiggy_ir as ir;
+
+mod gnu;
+mod lld;
+mod msvc;
+
+/// A linker map, in a linker-independent form.
+#[derive(Default)]
+struct LinkerMap {
+    /// The total size of everything placed in the output.
+    size: u64,
+    /// The pieces of input files placed in the output.
+    contributions: Vec<Contribution>,
+    /// `(referencing object, archive member)` pairs, for archive members that
+    /// were pulled into the link to satisfy a reference.
+    members: Vec<(String, String)>,
+    /// The cross reference table, if the map has one.
+    cross_refs: Vec<CrossRef>,
+    /// The address of the entry point, if the map has one.
+    entry: Option<u64>,
+}
+
+/// An input section (or part of one) that was placed in the output.
+struct Contribution {
+    object: String,
+    section: String,
+    address: u64,
+    size: u64,
+    code: bool,
+    symbols: Vec<Symbol>,
+}
+
+/// A symbol defined within a contribution. A size of zero means the linker
+/// did not say, and the symbol extends to the next one.
+struct Symbol {
+    name: String,
+    address: u64,
+    size: u64,
+}
+
+/// An entry of the cross reference table (`--cref`).
+struct CrossRef {
+    symbol: String,
+    defined_in: String,
+    referenced_by: Vec<String>,
+}
+
+/// Does the given data look like a linker map?
+pub(crate) fn sniff(data: &[u8]) -> bool {
+    let head = String::from_utf8_lossy(&data[..data.len().min(64 * 1024)]);
+    gnu::sniff(&head) || lld::sniff(&head) || msvc::sniff(&head)
+}
+
+/// Parse a linker map into IR items.
+pub(crate) fn parse(data: &[u8]) -> anyhow::Result<ir::Items> {
+    let text = String::from_utf8_lossy(data);
+    let map = if gnu::sniff(&text) {
+        gnu::parse(&text)
+    } else if lld::sniff(&text) {
+        lld::parse(&text)
+    } else if msvc::sniff(&text) {
+        msvc::parse(&text)
+    } else {
+        return Err(anyhow!("unrecognized linker map format"));
+    };
+    Ok(build(map))
+}
+
+/// Parse a hexadecimal number, with or without a `0x` prefix.
+fn parse_hex(s: &str) -> Option<u64> {
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    u64::from_str_radix(s, 16).ok()
+}
+
+/// The rest of the line after its first `n` whitespace separated tokens.
+fn after_tokens(line: &str, n: usize) -> &str {
+    let mut rest = line.trim();
+    for _ in 0..n {
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        rest = rest[end..].trim_start();
+    }
+    rest
+}
+
+/// Is this the name of an output section holding code?
+fn is_code_section(name: &str) -> bool {
+    [".text", ".init", ".fini", ".plt", ".iplt"]
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+}
+
+/// Is this the name of an output section that is not loaded at runtime?
+fn is_unallocated_section(name: &str) -> bool {
+    [
+        ".debug",
+        ".zdebug",
+        ".comment",
+        ".stab",
+        ".note.GNU-stack",
+        ".gnu.attributes",
+        ".gnu_debuglink",
+        ".ARM.attributes",
+        ".symtab",
+        ".strtab",
+        ".shstrtab",
+        "/DISCARD/",
+    ]
+    .iter()
+    .any(|prefix| name.starts_with(prefix))
+}
+
+/// Parse the cross reference table that GNU ld and lld write with `--cref`.
+/// `lines` starts right after the `Symbol ... File` header, and the files
+/// start at `file_column`.
+fn parse_cross_refs<'a, I>(lines: I, file_column: usize) -> Vec<CrossRef>
+where
+    I: Iterator<Item = &'a str>,
+{
+    let mut cross_refs: Vec<CrossRef> = vec![];
+    let mut pending_symbol = None;
+
+    for line in lines {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if !line.starts_with(char::is_whitespace) {
+            // A new symbol. Long names push the defining file onto the next
+            // line.
+            let (symbol, file) = match line.get(file_column..) {
+                Some(file) if line[..file_column].ends_with(' ') => {
+                    (line[..file_column].trim(), file.trim())
+                }
+                _ => (line.trim(), ""),
+            };
+            if file.is_empty() {
+                pending_symbol = Some(symbol.to_string());
+            } else {
+                cross_refs.push(CrossRef {
+                    symbol: symbol.to_string(),
+                    defined_in: file.to_string(),
+                    referenced_by: vec![],
+                });
+            }
+        } else if let Some(symbol) = pending_symbol.take() {
+            cross_refs.push(CrossRef {
+                symbol,
+                defined_in: line.trim().to_string(),
+                referenced_by: vec![],
+            });
+        } else if let Some(cross_ref) = cross_refs.last_mut() {
+            cross_ref.referenced_by.push(line.trim().to_string());
+        }
+    }
+
+    cross_refs
+}
+
+/// Split an object name like `libfoo.a(foo.o)` into its archive and member.
+fn archive_of(object: &str) -> Option<&str> {
+    if object.ends_with(')') {
+        if let Some(open) = object.rfind('(') {
+            if open > 0 {
+                return Some(&object[..open]);
+            }
+        }
+    }
+
+    // link.exe's `lib:object.obj`, taking care not to split a drive letter.
+    match object.find(':') {
+        Some(colon) if colon > 1 && !object.starts_with('<') => Some(&object[..colon]),
+        _ => None,
+    }
+}
+
+/// Ids for the items built from a linker map: archives live in section 0,
+/// objects in section 1, and the symbols of each contribution in their own
+/// section after that.
+struct Groups {
+    archives: HashMap<String, ir::Id>,
+    objects: HashMap<String, ir::Id>,
+}
+
+impl Groups {
+    fn object(&mut self, items: &mut ir::ItemsBuilder, object: &str) -> ir::Id {
+        if let Some(&id) = self.objects.get(object) {
+            return id;
+        }
+
+        let id = ir::Id::entry(1, self.objects.len());
+        let item = ir::Item::new(id, object, 0, ir::Misc::new());
+        self.objects.insert(object.to_string(), id);
+
+        match archive_of(object) {
+            Some(archive) => {
+                items.add_item(item);
+                let archive = self.archive(items, archive);
+                items.add_edge(archive, id, ir::EdgeKind::Other);
+            }
+            None => {
+                items.add_root(item);
+            }
+        }
+
+        id
+    }
+
+    fn archive(&mut self, items: &mut ir::ItemsBuilder, archive: &str) -> ir::Id {
+        if let Some(&id) = self.archives.get(archive) {
+            return id;
+        }
+
+        let id = ir::Id::entry(0, self.archives.len());
+        items.add_root(ir::Item::new(id, archive, 0, ir::Misc::new()));
+        self.archives.insert(archive.to_string(), id);
+        id
+    }
+}
+
+fn build(map: LinkerMap) -> ir::Items {
+    let mut items = ir::ItemsBuilder::new(map.size);
+    let mut groups = Groups {
+        archives: HashMap::new(),
+        objects: HashMap::new(),
+    };
+    let mut defined = HashMap::new();
+    let entry = map.entry;
+
+    for (i, contribution) in map.contributions.into_iter().enumerate() {
+        let start = contribution.address;
+        let end = match start.checked_add(contribution.size) {
+            Some(end) => end,
+            None => {
+                eprintln!(
+                    "Warning: Skipping {}:({}) at {:#x}, whose size {:#x} runs past the end \
+                     of the address space.\n",
+                    contribution.object, contribution.section, start, contribution.size
+                );
+                continue;
+            }
+        };
+        let object = groups.object(&mut items, &contribution.object);
+        items.name_section(i + 2, contribution.section.as_str());
+        let code = contribution.code;
+
+        let mut symbols = contribution.symbols;
+        symbols.retain(|symbol| symbol.address >= start && symbol.address <= end);
+        symbols.sort_by_key(|symbol| symbol.address);
+
+        let add_item = |items: &mut ir::ItemsBuilder, index, name: &str, address, size| {
+            let id = ir::Id::entry(i + 2, index);
+            let kind: ir::ItemKind = if code {
+                ir::Code::new(name).into()
+            } else {
+                ir::Data::new(None).into()
+            };
+            let item = ir::Item::new(id, name, size, kind);
+            let contains_entry = entry.is_some_and(|entry| {
+                entry >= address && address.checked_add(size).is_some_and(|end| entry < end)
+            });
+            if contains_entry {
+                items.add_root(item);
+            } else {
+                items.add_item(item);
+            }
+            items.add_edge(object, id, ir::EdgeKind::Other);
+            id
+        };
+
+        // Whatever comes before the first symbol (or the whole contribution,
+        // when there are no symbols) is attributed to the section itself.
+        let first = symbols.first().map_or(end, |symbol| symbol.address);
+        if first > start {
+            let name = format!("{}:({})", contribution.object, contribution.section);
+            add_item(&mut items, 0, &name, start, first - start);
+        }
+
+        for (j, symbol) in symbols.iter().enumerate() {
+            let next = symbols.get(j + 1).map_or(end, |next| next.address);
+            let size = if symbol.size != 0 {
+                symbol.size.min(end - symbol.address)
+            } else {
+                next - symbol.address
+            };
+            let id = add_item(&mut items, j + 1, &symbol.name, symbol.address, size);
+            defined.insert((contribution.object.clone(), symbol.name.clone()), id);
+        }
+    }
+
+    for (referencer, member) in map.members {
+        let from = groups.object(&mut items, &referencer);
+        let to = groups.object(&mut items, &member);
+        items.add_edge(from, to, ir::EdgeKind::Other);
+    }
+
+    for cross_ref in map.cross_refs {
+        let key = (cross_ref.defined_in, cross_ref.symbol);
+        let to = match defined.get(&key) {
+            Some(&to) => to,
+            None => continue,
+        };
+        for referencer in cross_ref.referenced_by {
+            let from = groups.object(&mut items, &referencer);
+            items.add_edge(from, to, ir::EdgeKind::Other);
+        }
+    }
+
+    items.finish()
+}
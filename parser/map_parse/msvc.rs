@@ -0,0 +1,135 @@
+//! MSVC link.exe's map files.
+//!
+//! ```text
+//!  Start         Length     Name                   Class
+//!  0001:00000000 00000180H .text$mn                CODE
+//!
+//!   Address         Publics by Value              Rva+Base               Lib:Object
+//!
+//!  0001:00000000       main                       0000000140001000 f   main.obj
+//!
+//!  entry point at        0001:00000130
+//! ```
+//!
+//! Symbols are given by `section:offset` and have no sizes, so each symbol
+//! extends to the next one in its section. Addresses in the resulting
+//! `LinkerMap` are `section << 32 | offset`.
+
+use std::collections::BTreeMap;
+
+use super::{Contribution, LinkerMap, Symbol};
+
+/// A `Start Length Name Class` entry.
+struct SectionContribution {
+    address: u64,
+    size: u64,
+    name: String,
+    code: bool,
+}
+
+pub(super) fn sniff(text: &str) -> bool {
+    text.lines().any(|line| {
+        let line = line.trim();
+        line.starts_with("Preferred load address is") || line.starts_with("Timestamp is")
+    })
+}
+
+pub(super) fn parse(text: &str) -> LinkerMap {
+    let mut map = LinkerMap::default();
+    let mut sections = vec![];
+    // Symbols by section number, as `(address, name, object, is function)`.
+    let mut symbols: BTreeMap<u64, Vec<(u64, &str, &str, bool)>> = BTreeMap::new();
+
+    for line in text.lines() {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+
+        if line.trim_start().starts_with("entry point at") {
+            map.entry = tokens.last().and_then(|address| parse_address(address));
+            continue;
+        }
+
+        let address = match tokens.first().and_then(|address| parse_address(address)) {
+            Some(address) if address >> 32 != 0 => address,
+            _ => continue,
+        };
+
+        match tokens.len() {
+            // 0001:00000000 00000180H .text$mn CODE
+            4 if tokens[1].len() == 9 && tokens[1].ends_with('H') => {
+                let size = match super::parse_hex(tokens[1].trim_end_matches('H')) {
+                    Some(size) => size,
+                    None => continue,
+                };
+                if address.checked_add(size).is_none() {
+                    eprintln!(
+                        "Warning: Skipping section contribution {} at {:#x}, whose size {:#x} \
+                         runs past the end of the address space.\n",
+                        tokens[2], address, size
+                    );
+                    continue;
+                }
+                map.size = map.size.saturating_add(size);
+                sections.push(SectionContribution {
+                    address,
+                    size,
+                    name: tokens[2].to_string(),
+                    code: tokens[3] == "CODE",
+                });
+            }
+            // 0001:00000000 main 0000000140001000 f main.obj
+            n if n >= 4 => {
+                let function = tokens[3..n - 1].contains(&"f");
+                symbols.entry(address >> 32).or_default().push((
+                    address,
+                    tokens[1],
+                    tokens[n - 1],
+                    function,
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    for (section, mut section_symbols) in symbols {
+        section_symbols.sort_by_key(|&(address, ..)| address);
+        let end = sections
+            .iter()
+            .filter(|contribution| contribution.address >> 32 == section)
+            .map(|contribution| contribution.address + contribution.size)
+            .max()
+            .unwrap_or(0);
+
+        for (i, &(address, name, object, function)) in section_symbols.iter().enumerate() {
+            let next = section_symbols.get(i + 1).map_or(end, |&(next, ..)| next);
+            let contribution = sections.iter().find(|contribution| {
+                address >= contribution.address
+                    && address < contribution.address + contribution.size
+            });
+            map.contributions.push(Contribution {
+                object: object.to_string(),
+                section: contribution.map_or("", |c| &c.name).to_string(),
+                address,
+                size: next.saturating_sub(address),
+                code: function || contribution.is_some_and(|c| c.code),
+                symbols: vec![Symbol {
+                    name: name.to_string(),
+                    address,
+                    size: 0,
+                }],
+            });
+        }
+    }
+
+    map
+}
+
+/// Parse a `section:offset` address.
+fn parse_address(address: &str) -> Option<u64> {
+    let mut parts = address.split(':');
+    let section = u64::from_str_radix(parts.next()?, 16).ok()?;
+    let offset = u64::from_str_radix(parts.next()?, 16).ok()?;
+    if parts.next().is_some() || offset > u64::from(u32::MAX) {
+        return None;
+    }
+    Some(section << 32 | offset)
+}
@@ -0,0 +1,105 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use object::{File, Object, ObjectSection};
+
+/// A function described by a `DW_TAG_subprogram` entry with a contiguous
+/// address range.
+pub(super) struct Subprogram {
+    pub name: String,
+    pub address: u64,
+    pub size: u64,
+}
+
+/// Collect the functions described by the DWARF in the given file. This is
+/// used when the symbol table does not provide any.
+pub(super) fn subprograms(file: &File<'_>) -> anyhow::Result<Vec<Subprogram>> {
+    let endian = if file.is_little_endian() {
+        gimli::RunTimeEndian::Little
+    } else {
+        gimli::RunTimeEndian::Big
+    };
+    let sections = gimli::DwarfSections::load(|id| -> anyhow::Result<Cow<'_, [u8]>> {
+        Ok(match file.section_by_name(id.name()) {
+            Some(section) => section.uncompressed_data()?,
+            None => Cow::Borrowed(&[]),
+        })
+    })?;
+    let dwarf = sections.borrow(|section| gimli::EndianSlice::new(section, endian));
+
+    // Keyed by address, so that a function described in multiple units is
+    // only reported once.
+    let mut subprograms = BTreeMap::new();
+
+    let mut units = dwarf.units();
+    while let Some(header) = units.next()? {
+        let unit = dwarf.unit(header)?;
+        let mut entries = unit.entries();
+        while let Some((_, entry)) = entries.next_dfs()? {
+            if entry.tag() != gimli::DW_TAG_subprogram {
+                continue;
+            }
+
+            let address = match entry.attr_value(gimli::DW_AT_low_pc)? {
+                Some(low_pc) => match dwarf.attr_address(&unit, low_pc)? {
+                    Some(address) if address != 0 => address,
+                    _ => continue,
+                },
+                None => continue,
+            };
+            let size = match entry.attr_value(gimli::DW_AT_high_pc)? {
+                Some(gimli::AttributeValue::Udata(size)) => size,
+                Some(high_pc) => match dwarf.attr_address(&unit, high_pc)? {
+                    Some(high_pc) if high_pc > address => high_pc - address,
+                    _ => continue,
+                },
+                None => continue,
+            };
+
+            let name = match subprogram_name(&dwarf, &unit, entry)? {
+                Some(name) => name,
+                None => format!("func@{:#x}", address),
+            };
+            subprograms.entry(address).or_insert(Subprogram {
+                name,
+                address,
+                size,
+            });
+        }
+    }
+
+    Ok(subprograms.into_values().collect())
+}
+
+/// Get the (preferably mangled) name of a subprogram, following its abstract
+/// origin or specification within the same unit if it is not named itself.
+fn subprogram_name<R: gimli::Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &gimli::Unit<R>,
+    entry: &gimli::DebuggingInformationEntry<'_, '_, R>,
+) -> anyhow::Result<Option<String>> {
+    for attr in &[
+        gimli::DW_AT_linkage_name,
+        gimli::DW_AT_MIPS_linkage_name,
+        gimli::DW_AT_name,
+    ] {
+        if let Some(value) = entry.attr_value(*attr)? {
+            let name = dwarf.attr_string(unit, value)?;
+            return Ok(Some(name.to_string_lossy()?.into_owned()));
+        }
+    }
+
+    for attr in &[gimli::DW_AT_abstract_origin, gimli::DW_AT_specification] {
+        if let Some(gimli::AttributeValue::UnitRef(offset)) = entry.attr_value(*attr)? {
+            let origin = unit.entry(offset)?;
+            for attr in &[gimli::DW_AT_linkage_name, gimli::DW_AT_name] {
+                if let Some(value) = origin.attr_value(*attr)? {
+                    let name = dwarf.attr_string(unit, value)?;
+                    return Ok(Some(name.to_string_lossy()?.into_owned()));
+                }
+            }
+        }
+    }
+
+    Ok(None)
+}
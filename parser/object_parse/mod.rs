@@ -1,13 +1,24 @@
+use std::collections::HashMap;
 use std::convert::TryInto;
+use std::ops::Range;
+use std::path::Path;
 
 use anyhow::anyhow;
 use object::{
     elf, Architecture, BinaryFormat, Endianness, File, Object, ObjectSection, ObjectSegment,
-    ObjectSymbol, Relocation, RelocationFlags, RelocationTarget, SectionFlags, Symbol, SymbolIndex,
-    SymbolKind,
+    ObjectSymbol, Relocation, RelocationEncoding, RelocationFlags, RelocationKind,
+    RelocationTarget, SectionFlags, SectionKind, SymbolKind,
 };
 use twiggy_ir as ir;
 
+use crate::DebugInfoSearch;
+
+use self::warnings::{Warning, Warnings};
+
+mod dwarf;
+mod split_debug;
+mod warnings;
+
 fn maybe_thumb_real_addr(file: &File, addr: u64) -> u64 {
     match file.architecture() {
         Architecture::Arm => {
@@ -17,19 +28,33 @@ fn maybe_thumb_real_addr(file: &File, addr: u64) -> u64 {
     }
 }
 
-pub fn parse(data: &[u8]) -> anyhow::Result<ir::Items> {
+pub fn parse(path: &Path, data: &[u8], debug_info: &DebugInfoSearch) -> anyhow::Result<ir::Items> {
     let file: File =
         File::parse(data).map_err(|err| anyhow!("Failed to parse data with err: {:?}", err))?;
 
-    let mut alloc_size = 0;
-    for segment in file.segments() {
-        alloc_size += segment.size();
-    }
+    // Stripped binaries keep their symbols (and DWARF) in a separate debug
+    // file. When we find one, we take symbols and relocations from it, but
+    // sizes and data are still measured in the binary itself.
+    let debug_data = split_debug::read_debug_file(path, &file, debug_info)?;
+    let debug_file = match debug_data {
+        Some(ref debug_data) => Some(
+            File::parse(&debug_data[..])
+                .map_err(|err| anyhow!("Failed to parse split debug info with err: {:?}", err))?,
+        ),
+        None => None,
+    };
+    let symbols_file = debug_file.as_ref().unwrap_or(&file);
+
+    let alloc_size = file
+        .segments()
+        .try_fold(0u64, |size, segment| size.checked_add(segment.size()))
+        .ok_or_else(|| anyhow!("The total size of the binary's segments overflows a u64"))?;
 
-    let mut items = ir::ItemsBuilder::new(alloc_size as u32);
+    let mut items = ir::ItemsBuilder::new(alloc_size);
+    let mut warnings = Warnings::default();
 
-    let mut symbols = vec![];
-    for symbol in file.symbols() {
+    let mut definitions = vec![];
+    for symbol in symbols_file.symbols() {
         if !symbol.is_definition() {
             continue;
         }
@@ -38,59 +63,104 @@ pub fn parse(data: &[u8]) -> anyhow::Result<ir::Items> {
             continue;
         }
 
+        let section = match symbol
+            .section_index()
+            .and_then(|index| symbols_file.section_by_index(index).ok())
+        {
+            Some(section) => section,
+            None => {
+                warnings.push(Warning::SymbolSection {
+                    index: symbol.index().0,
+                });
+                continue;
+            }
+        };
+
         // Filter out symbols in non-allocated sections. Their symbol values do not correspond to
         // actual runtime addresses.
-        match file
-            .section_by_index(symbol.section_index().unwrap())
-            .unwrap()
-            .flags()
-        {
-            SectionFlags::Elf { sh_flags } => {
-                if sh_flags as u32 & elf::SHF_ALLOC != elf::SHF_ALLOC {
-                    continue;
-                }
+        if let SectionFlags::Elf { sh_flags } = section.flags() {
+            if sh_flags as u32 & elf::SHF_ALLOC != elf::SHF_ALLOC {
+                continue;
             }
-            _ => {}
         }
 
-        if !file.segments().any(|segment| {
-            segment
-                .data_range(maybe_thumb_real_addr(&file, symbol.address()), 1)
-                .ok()
-                .flatten()
-                .is_some()
-        }) {
-            // Symbol not part of any loaded segment
-            continue;
+        let name = match symbol.name() {
+            Ok(name) => name,
+            Err(_) => {
+                warnings.push(Warning::SymbolName {
+                    index: symbol.index().0,
+                });
+                continue;
+            }
+        };
+
+        if let Ok(section_name) = section.name() {
+            items.name_section(section.index().0, section_name);
         }
 
-        symbols.push((symbol.address(), symbol.size(), symbol.index()));
+        definitions.push(Definition {
+            id: ir::Id::entry(section.index().0, symbol.index().0),
+            name: name.to_string(),
+            address: symbol.address(),
+            size: symbol.size(),
+            global: symbol.is_global(),
+            code: match symbol.kind() {
+                SymbolKind::Text => true,
+                SymbolKind::Unknown => section.kind() == SectionKind::Text,
+                _ => false,
+            },
+        });
+    }
+    let mut symbols = add_items(&file, &mut items, &mut warnings, definitions);
 
-        let id = ir::Id::entry(symbol.section_index().unwrap().0, symbol.index().0);
-        let name = symbol.name().unwrap();
-        let kind: ir::ItemKind = ir::Code::new(name).into();
-        let item = ir::Item::new(id, name, symbol.size() as u32, kind);
-        if maybe_thumb_real_addr(&file, symbol.address())
-            == maybe_thumb_real_addr(&file, file.entry())
-        {
-            items.add_root(item);
-        } else {
-            items.add_item(item);
+    // Without a symbol table, fall back to the functions described by DWARF.
+    if symbols.is_empty() {
+        if let Some(debug_info) = symbols_file.section_by_name(".debug_info") {
+            let subprograms = dwarf::subprograms(symbols_file).unwrap_or_else(|error| {
+                warnings.push(Warning::Dwarf {
+                    error: error.to_string(),
+                });
+                vec![]
+            });
+            let definitions = subprograms
+                .into_iter()
+                .enumerate()
+                .map(|(i, subprogram)| Definition {
+                    id: ir::Id::entry(debug_info.index().0, i),
+                    name: subprogram.name,
+                    address: subprogram.address,
+                    size: subprogram.size,
+                    global: true,
+                    code: true,
+                })
+                .collect();
+            symbols = add_items(&file, &mut items, &mut warnings, definitions);
         }
     }
 
-    if let BinaryFormat::Elf = file.format() {
+    if let BinaryFormat::Elf = symbols_file.format() {
         let mut any_relocs = false;
-        for section in file.sections() {
-            if section.name().unwrap().starts_with(".debug")
-                || section.name().unwrap().starts_with(".eh_frame")
-            {
+        for section in symbols_file.sections() {
+            let name = match section.name() {
+                Ok(name) => name,
+                Err(_) => {
+                    warnings.push(Warning::SectionName {
+                        index: section.index().0,
+                    });
+                    continue;
+                }
+            };
+            if name.starts_with(".debug") || name.starts_with(".eh_frame") {
                 continue;
             }
 
             for (offset, reloc) in section.relocations() {
                 any_relocs = true;
-                edge_for_reloc(&file, &mut items, &symbols, offset, reloc);
+                if let Err(warning) =
+                    edge_for_reloc(&file, symbols_file, &mut items, &symbols, offset, reloc)
+                {
+                    warnings.push(warning);
+                }
             }
         }
 
@@ -108,58 +178,210 @@ pub fn parse(data: &[u8]) -> anyhow::Result<ir::Items> {
         )
     }
 
+    warnings.report();
+
     Ok(items.finish())
 }
 
-fn read_at<const N: usize>(file: &File<'_>, offset: u64) -> [u8; N] {
+/// A symbol (or DWARF function) defining some code or data.
+struct Definition {
+    id: ir::Id,
+    name: String,
+    address: u64,
+    size: u64,
+    global: bool,
+    code: bool,
+}
+
+/// The items made from definitions, and where they live.
+#[derive(Default)]
+struct SymbolTable {
+    /// The start and end addresses of each item.
+    ranges: Vec<(u64, u64, ir::Id)>,
+    /// Maps the ids of definitions that were merged into another item to
+    /// that item's id.
+    aliases: HashMap<ir::Id, ir::Id>,
+}
+
+impl SymbolTable {
+    fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The item covering the given address.
+    fn covering(&self, address: u64) -> Option<ir::Id> {
+        self.ranges
+            .iter()
+            .find(|&&(start, end, _id)| (start..end).contains(&address))
+            .map(|&(_, _, id)| id)
+    }
+
+    /// The item that the definition with the given id ended up in, if any.
+    fn canonical(&self, id: ir::Id) -> Option<ir::Id> {
+        if self.ranges.iter().any(|&(_, _, item)| item == id) {
+            Some(id)
+        } else {
+            self.aliases.get(&id).copied()
+        }
+    }
+}
+
+/// Add items for the definitions that are part of one of the binary's loaded
+/// segments.
+///
+/// Definitions sharing both address and size (aliases, or functions merged by
+/// identical code folding) become a single item, named after the first global
+/// definition, so that their bytes are only counted once.
+fn add_items(
+    file: &File<'_>,
+    items: &mut ir::ItemsBuilder,
+    warnings: &mut Warnings,
+    definitions: Vec<Definition>,
+) -> SymbolTable {
+    let mut groups: Vec<Vec<Definition>> = vec![];
+    let mut by_location = HashMap::new();
+    for definition in definitions {
+        let location = (definition.address, definition.size);
+        let index = *by_location.entry(location).or_insert_with(|| {
+            groups.push(vec![]);
+            groups.len() - 1
+        });
+        groups[index].push(definition);
+    }
+
+    let mut table = SymbolTable::default();
+    for mut group in groups {
+        let (address, size) = (group[0].address, group[0].size);
+        let end = match address.checked_add(size) {
+            Some(end) => end,
+            None => {
+                warnings.push(Warning::SymbolRange { address, size });
+                continue;
+            }
+        };
+        if !file.segments().any(|segment| {
+            segment
+                .data_range(maybe_thumb_real_addr(file, address), 1)
+                .ok()
+                .flatten()
+                .is_some()
+        }) {
+            // Symbol not part of any loaded segment
+            continue;
+        }
+
+        let canonical = group.iter().position(|def| def.global).unwrap_or(0);
+        let canonical = group.swap_remove(canonical);
+        table.ranges.push((address, end, canonical.id));
+
+        let kind: ir::ItemKind = if canonical.code {
+            ir::Code::new(&canonical.name).into()
+        } else {
+            ir::Data::symbol(&canonical.name).into()
+        };
+        let mut item = ir::Item::new(canonical.id, canonical.name.as_str(), size, kind);
+        let address = maybe_thumb_real_addr(file, address);
+        item.set_address(address);
+        if let Some(range) = file_range(file, address, size) {
+            item.add_file_range(range);
+        }
+        group.sort_by_key(|alias| alias.id);
+        for alias in group {
+            if alias.name != canonical.name {
+                item.add_alias(alias.name);
+            }
+            table.aliases.insert(alias.id, canonical.id);
+        }
+
+        if address == maybe_thumb_real_addr(file, file.entry()) {
+            items.add_root(item);
+        } else {
+            items.add_item(item);
+        }
+    }
+
+    table
+}
+
+/// The range of the file holding the bytes loaded at the given address, if
+/// they are in the file at all. Zero-initialized data, like `.bss`, is not.
+fn file_range(file: &File<'_>, address: u64, size: u64) -> Option<Range<u64>> {
+    file.segments().find_map(|segment| {
+        let offset = address.checked_sub(segment.address())?;
+        let (file_offset, file_size) = segment.file_range();
+        if offset >= file_size {
+            return None;
+        }
+        let start = file_offset + offset;
+        Some(start..start + size.min(file_size - offset))
+    })
+}
+
+fn read_at<const N: usize>(file: &File<'_>, offset: u64) -> Option<[u8; N]> {
     file.segments()
-        .find_map(|segment| segment.data_range(offset, N as u64).unwrap())
-        .unwrap()
-        .try_into()
-        .unwrap()
+        .find_map(|segment| segment.data_range(offset, N as u64).ok().flatten())
+        .and_then(|data| data.try_into().ok())
 }
 
+/// Add the edge described by the given relocation, if it is between two of
+/// the items we know about.
 fn edge_for_reloc(
-    file: &File<'_>,
-    items: &mut twiggy_ir::ItemsBuilder,
-    symbols: &Vec<(u64, u64, SymbolIndex)>,
+    file: &File,
+    symbols_file: &File,
+    items: &mut ir::ItemsBuilder,
+    symbols: &SymbolTable,
     offset: u64,
     reloc: Relocation,
-) {
-    let Some(reloc_source) = symbol_for_addr(file, symbols, offset) else {
-        return;
+) -> Result<(), Warning> {
+    let Some(reloc_source) = symbols.covering(offset) else {
+        return Ok(());
     };
 
-    match reloc.target() {
-        // If the reloc is relative to a non-section symbol, we can directly use this symbol as target.
-        RelocationTarget::Symbol(reloc_target_idx)
-            if file.symbol_by_index(reloc_target_idx).unwrap().kind() != SymbolKind::Section =>
-        {
-            if !symbols.iter().any(|&(_, _, idx)| idx == reloc_target_idx) {
-                return;
+    let target_symbol = match reloc.target() {
+        RelocationTarget::Symbol(reloc_target_idx) => Some(
+            symbols_file
+                .symbol_by_index(reloc_target_idx)
+                .map_err(|_| Warning::RelocationSymbol { offset })?,
+        ),
+        RelocationTarget::Absolute => None,
+        target => return Err(Warning::RelocationTarget { offset, target }),
+    };
+
+    // If the reloc is relative to a non-section symbol, we can directly use this symbol as target.
+    if let Some(ref target_symbol) = target_symbol {
+        if target_symbol.kind() != SymbolKind::Section {
+            let Some(section_index) = target_symbol.section_index() else {
+                return Ok(());
+            };
+            let reloc_target = ir::Id::entry(section_index.0, target_symbol.index().0);
+            if let Some(reloc_target) = symbols.canonical(reloc_target) {
+                items.add_edge(reloc_source, reloc_target, edge_kind(&reloc));
             }
-            let reloc_target = file.symbol_by_index(reloc_target_idx).unwrap();
-            add_edge_for_symbol(items, reloc_source, reloc_target);
-            return;
+            return Ok(());
         }
-
-        // Otherwise we need to compute the target address and find the symbol covering this address.
-        _ => {}
     }
 
+    // Otherwise we need to compute the target address and find the symbol covering this address.
     let implicit_addend = match file.architecture() {
         Architecture::Arm => {
-            assert_eq!(file.endianness(), Endianness::Little);
-            assert!(reloc.has_implicit_addend());
+            if file.endianness() != Endianness::Little || !reloc.has_implicit_addend() {
+                return Err(Warning::RelocationLayout { offset });
+            }
             match reloc.flags() {
                 RelocationFlags::Elf {
                     r_type: elf::R_ARM_ABS32,
-                } => u64::from(u32::from_le_bytes(read_at(file, offset))) as i64,
-                ty => todo!("{:?}", ty),
+                } => {
+                    let addend =
+                        read_at(file, offset).ok_or(Warning::RelocationAddend { offset })?;
+                    u64::from(u32::from_le_bytes(addend)) as i64
+                }
+                flags => return Err(Warning::RelocationType { offset, flags }),
             }
         }
         Architecture::X86_64 => {
-            assert!(!reloc.has_implicit_addend());
+            if reloc.has_implicit_addend() {
+                return Err(Warning::RelocationLayout { offset });
+            }
             match reloc.flags() {
                 RelocationFlags::Elf {
                     r_type: elf::R_X86_64_PC32 | elf::R_X86_64_PLT32,
@@ -167,55 +389,48 @@ fn edge_for_reloc(
                 RelocationFlags::Elf {
                     r_type: elf::R_X86_64_64,
                 } => 0,
-                ty => todo!("{:?}", ty),
+                flags => return Err(Warning::RelocationType { offset, flags }),
             }
         }
-        arch => todo!("relocations for architecture {:?} not yet supported", arch),
-    };
-
-    let symbol_addr = match reloc.target() {
-        RelocationTarget::Symbol(reloc_target_idx) => {
-            file.symbol_by_index(reloc_target_idx).unwrap().address()
+        architecture => {
+            return Err(Warning::RelocationArchitecture {
+                offset,
+                architecture,
+            })
         }
-        RelocationTarget::Absolute => 0,
-        _ => todo!(),
     };
 
-    let target_addr = (symbol_addr as i64 + reloc.addend() + implicit_addend) as u64;
-    let Some(reloc_target) = symbol_for_addr(file, symbols, target_addr) else {
-        return;
-    };
-    add_edge_for_symbol(items, reloc_source, reloc_target);
-}
-
-fn symbol_for_addr<'data, 'file>(
-    file: &'file File<'data>,
-    symbols: &Vec<(u64, u64, SymbolIndex)>,
-    offset: u64,
-) -> Option<Symbol<'data, 'file>> {
-    let Some(&(_, _, reloc_source_idx)) = symbols
-        .iter()
-        .find(|&&(addr, size, _idx)| (addr..addr + size).contains(&offset))
-    else {
-        return None;
-    };
-
-    Some(file.symbol_by_index(reloc_source_idx).unwrap())
+    let symbol_addr = target_symbol.map_or(0, |symbol| symbol.address());
+    let target_addr = (symbol_addr as i64)
+        .wrapping_add(reloc.addend())
+        .wrapping_add(implicit_addend) as u64;
+    if let Some(reloc_target) = symbols.covering(target_addr) {
+        items.add_edge(reloc_source, reloc_target, edge_kind(&reloc));
+    }
+    Ok(())
 }
 
-fn add_edge_for_symbol(
-    items: &mut twiggy_ir::ItemsBuilder,
-    reloc_source: Symbol<'_, '_>,
-    reloc_target: Symbol<'_, '_>,
-) {
-    items.add_edge(
-        ir::Id::entry(
-            reloc_source.section_index().unwrap().0,
-            reloc_source.index().0,
-        ),
-        ir::Id::entry(
-            reloc_target.section_index().unwrap().0,
-            reloc_target.index().0,
+/// The kind of edge a relocation makes: calls and jumps through branch
+/// relocations, and references to the target's address otherwise.
+fn edge_kind(reloc: &Relocation) -> ir::EdgeKind {
+    let branch = match (reloc.kind(), reloc.encoding()) {
+        (RelocationKind::PltRelative, _)
+        | (_, RelocationEncoding::X86Branch)
+        | (_, RelocationEncoding::AArch64Call)
+        | (_, RelocationEncoding::LoongArchBranch) => true,
+        _ => matches!(
+            reloc.flags(),
+            RelocationFlags::Elf {
+                r_type: elf::R_ARM_CALL
+                    | elf::R_ARM_JUMP24
+                    | elf::R_ARM_THM_PC22
+                    | elf::R_ARM_THM_JUMP24
+            }
         ),
-    );
+    };
+    if branch {
+        ir::EdgeKind::Call
+    } else {
+        ir::EdgeKind::Data
+    }
 }
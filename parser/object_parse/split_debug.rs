@@ -0,0 +1,123 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use object::{File, Object};
+
+use crate::DebugInfoSearch;
+
+/// Find and read the split debug info file for the given (presumably
+/// stripped) binary, if there is one.
+pub(super) fn read_debug_file(
+    path: &Path,
+    file: &File<'_>,
+    search: &DebugInfoSearch,
+) -> anyhow::Result<Option<Vec<u8>>> {
+    let build_id = file.build_id().ok().flatten();
+    let debuglink = file.gnu_debuglink().ok().flatten();
+
+    for candidate in candidates(path, build_id, debuglink.map(|(name, _)| name), search) {
+        let data = match fs::read(&candidate) {
+            Ok(data) => data,
+            Err(_) => continue,
+        };
+        let debug_file = match File::parse(&data[..]) {
+            Ok(debug_file) => debug_file,
+            Err(_) => continue,
+        };
+        if matches_binary(build_id, debuglink.map(|(_, crc)| crc), &debug_file, &data) {
+            return Ok(Some(data));
+        }
+    }
+
+    if !search.is_empty() {
+        eprintln!(
+            "Warning: Couldn't find split debug info matching the build-id or \
+             .gnu_debuglink of {}.\n",
+            path.display()
+        );
+    }
+
+    Ok(None)
+}
+
+/// The paths that may hold the split debug info, in the order they should be
+/// tried.
+fn candidates(
+    path: &Path,
+    build_id: Option<&[u8]>,
+    debuglink: Option<&[u8]>,
+    search: &DebugInfoSearch,
+) -> Vec<PathBuf> {
+    let mut candidates = search.files.clone();
+
+    // The same layout that `gdb` uses for `debug-file-directory`:
+    // `.build-id/ab/cdef0123.debug`.
+    let build_id_path = build_id.filter(|id| id.len() > 1).map(|id| {
+        let hex: String = id.iter().map(|byte| format!("{:02x}", byte)).collect();
+        Path::new(".build-id")
+            .join(&hex[..2])
+            .join(format!("{}.debug", &hex[2..]))
+    });
+    let debuglink = debuglink.and_then(|name| std::str::from_utf8(name).ok());
+
+    for dir in &search.dirs {
+        if let Some(ref build_id_path) = build_id_path {
+            candidates.push(dir.join(build_id_path));
+        }
+        if let Some(debuglink) = debuglink {
+            candidates.push(dir.join(debuglink));
+            candidates.push(dir.join(".debug").join(debuglink));
+        }
+    }
+
+    if let Some(debuglink) = debuglink {
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        let same_dir = dir.join(debuglink);
+        // Don't pick up the binary itself when it links to its own name.
+        if same_dir.file_name() != path.file_name() {
+            candidates.push(same_dir);
+        }
+        candidates.push(dir.join(".debug").join(debuglink));
+    }
+
+    candidates
+}
+
+/// Does the given debug file belong to the binary with the given build-id
+/// and `.gnu_debuglink` CRC?
+fn matches_binary(
+    build_id: Option<&[u8]>,
+    debuglink_crc: Option<u32>,
+    debug_file: &File<'_>,
+    debug_data: &[u8],
+) -> bool {
+    if let (Some(build_id), Ok(Some(debug_build_id))) = (build_id, debug_file.build_id()) {
+        return build_id == debug_build_id;
+    }
+    match debuglink_crc {
+        Some(crc) => crc32(debug_data) == crc,
+        // Nothing to check against, so trust whoever pointed us at this file.
+        None => true,
+    }
+}
+
+/// The CRC-32 (as used by zlib) that `.gnu_debuglink` records for the debug
+/// file.
+fn crc32(data: &[u8]) -> u32 {
+    let mut table = [0u32; 256];
+    for (i, entry) in table.iter_mut().enumerate() {
+        let mut crc = i as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+        *entry = crc;
+    }
+
+    !data.iter().fold(!0u32, |crc, &byte| {
+        table[((crc ^ u32::from(byte)) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
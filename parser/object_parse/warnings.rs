@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use object::{Architecture, RelocationFlags, RelocationTarget};
+
+/// Something in an object file that we could not make sense of. The offending
+/// symbol, section or relocation is skipped, and parsing carries on with
+/// whatever is left.
+#[derive(Debug)]
+pub(super) enum Warning {
+    /// A section whose name could not be read.
+    SectionName { index: usize },
+    /// A symbol whose name could not be read.
+    SymbolName { index: usize },
+    /// A defined symbol that does not belong to any section we can find.
+    SymbolSection { index: usize },
+    /// A symbol whose size runs past the end of the address space.
+    SymbolRange { address: u64, size: u64 },
+    /// The DWARF debug info could not be read.
+    Dwarf { error: String },
+    /// A relocation referring to a symbol that does not exist.
+    RelocationSymbol { offset: u64 },
+    /// A relocation relative to something other than a symbol or an absolute
+    /// address.
+    RelocationTarget {
+        offset: u64,
+        target: RelocationTarget,
+    },
+    /// A relocation type that we don't know how to compute the target of.
+    RelocationType { offset: u64, flags: RelocationFlags },
+    /// A relocation for an architecture we don't support.
+    RelocationArchitecture {
+        offset: u64,
+        architecture: Architecture,
+    },
+    /// A relocation that is not laid out the way its architecture says it
+    /// should be, e.g. big endian ARM, or an explicit addend where an
+    /// implicit one is expected.
+    RelocationLayout { offset: u64 },
+    /// A relocation whose implicit addend could not be read from the binary.
+    RelocationAddend { offset: u64 },
+}
+
+/// What gets skipped because of a warning.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Skipped {
+    Section,
+    Symbol,
+    DebugInfo,
+    Relocation,
+}
+
+impl Skipped {
+    /// Describe `count` skipped things of this kind, e.g. "3 relocations".
+    fn count(self, count: usize) -> String {
+        let (one, many) = match self {
+            Skipped::Section => ("section", "sections"),
+            Skipped::Symbol => ("symbol", "symbols"),
+            Skipped::DebugInfo => return "the DWARF debug info".to_string(),
+            Skipped::Relocation => ("relocation", "relocations"),
+        };
+        format!("{} {}", count, if count == 1 { one } else { many })
+    }
+}
+
+impl Warning {
+    fn skipped(&self) -> Skipped {
+        match *self {
+            Warning::SectionName { .. } => Skipped::Section,
+            Warning::SymbolName { .. }
+            | Warning::SymbolSection { .. }
+            | Warning::SymbolRange { .. } => Skipped::Symbol,
+            Warning::Dwarf { .. } => Skipped::DebugInfo,
+            Warning::RelocationSymbol { .. }
+            | Warning::RelocationTarget { .. }
+            | Warning::RelocationType { .. }
+            | Warning::RelocationArchitecture { .. }
+            | Warning::RelocationLayout { .. }
+            | Warning::RelocationAddend { .. } => Skipped::Relocation,
+        }
+    }
+
+    /// A short description of this kind of warning, shared by all warnings of
+    /// the same kind.
+    fn reason(&self) -> &'static str {
+        match *self {
+            Warning::SectionName { .. } => "unreadable name",
+            Warning::SymbolName { .. } => "unreadable name",
+            Warning::SymbolSection { .. } => "not in any section",
+            Warning::SymbolRange { .. } => "past the end of the address space",
+            Warning::Dwarf { .. } => "unreadable",
+            Warning::RelocationSymbol { .. } => "unknown target symbol",
+            Warning::RelocationTarget { .. } => "unsupported target",
+            Warning::RelocationType { .. } => "unsupported relocation type",
+            Warning::RelocationArchitecture { .. } => "unsupported architecture",
+            Warning::RelocationLayout { .. } => "unexpected layout",
+            Warning::RelocationAddend { .. } => "unreadable addend",
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Warning::SectionName { index } => write!(f, "section #{}", index),
+            Warning::SymbolName { index } | Warning::SymbolSection { index } => {
+                write!(f, "symbol #{}", index)
+            }
+            Warning::SymbolRange { address, size } => {
+                write!(f, "at {:#x} with size {:#x}", address, size)
+            }
+            Warning::Dwarf { ref error } => write!(f, "{}", error),
+            Warning::RelocationSymbol { offset }
+            | Warning::RelocationLayout { offset }
+            | Warning::RelocationAddend { offset } => write!(f, "at {:#x}", offset),
+            Warning::RelocationTarget { offset, target } => {
+                write!(f, "{:?} at {:#x}", target, offset)
+            }
+            Warning::RelocationType { offset, flags } => write!(f, "{:?} at {:#x}", flags, offset),
+            Warning::RelocationArchitecture {
+                offset,
+                architecture,
+            } => write!(f, "{:?} at {:#x}", architecture, offset),
+        }
+    }
+}
+
+/// The warnings collected while parsing an object file.
+#[derive(Debug, Default)]
+pub(super) struct Warnings {
+    warnings: Vec<Warning>,
+}
+
+impl Warnings {
+    /// Record a warning.
+    pub(super) fn push(&mut self, warning: Warning) {
+        self.warnings.push(warning);
+    }
+
+    /// Print a summary of the collected warnings to `stderr`: how many
+    /// things were skipped, by kind and reason, with the first occurrence of
+    /// each as an example.
+    pub(super) fn report(&self) {
+        if self.warnings.is_empty() {
+            return;
+        }
+
+        let mut by_reason: BTreeMap<(Skipped, &str), (usize, &Warning)> = BTreeMap::new();
+        for warning in &self.warnings {
+            by_reason
+                .entry((warning.skipped(), warning.reason()))
+                .or_insert((0, warning))
+                .0 += 1;
+        }
+
+        let mut totals: BTreeMap<Skipped, usize> = BTreeMap::new();
+        for (&(skipped, _), &(count, _)) in &by_reason {
+            *totals.entry(skipped).or_default() += count;
+        }
+        let totals: Vec<String> = totals
+            .into_iter()
+            .map(|(skipped, count)| skipped.count(count))
+            .collect();
+
+        eprintln!(
+            "Warning: Skipped {} that could not be parsed. The results may be incomplete.",
+            totals.join(", ")
+        );
+        for (&(skipped, reason), &(count, example)) in &by_reason {
+            eprintln!("  {}: {} (e.g. {})", skipped.count(count), reason, example);
+        }
+        eprintln!();
+    }
+}
@@ -11,16 +11,66 @@ use std::path;
 use twiggy_ir as ir;
 use twiggy_traits as traits;
 
+mod map_parse;
 #[cfg(feature = "dwarf")]
 mod object_parse;
+mod snapshot;
 mod wasm_parse;
 
+pub use snapshot::write_snapshot;
+
 const WASM_MAGIC_NUMBER: [u8; 4] = [0x00, 0x61, 0x73, 0x6D];
 
+/// Where to look for split debug info (symbols and DWARF) of stripped object
+/// files.
+///
+/// Explicitly given files are tried first, then each search directory (by
+/// build-id, then by `.gnu_debuglink` name), and finally the directory the
+/// binary itself lives in. A candidate is only used if its build-id, or the
+/// CRC recorded in the binary's `.gnu_debuglink`, matches the binary.
+#[derive(Clone, Debug, Default)]
+pub struct DebugInfoSearch {
+    files: Vec<path::PathBuf>,
+    dirs: Vec<path::PathBuf>,
+}
+
+impl DebugInfoSearch {
+    /// Construct a new, empty `DebugInfoSearch`.
+    pub fn new() -> DebugInfoSearch {
+        DebugInfoSearch::default()
+    }
+
+    /// Add a file that may contain the split debug info.
+    pub fn add_file<P: Into<path::PathBuf>>(&mut self, file: P) {
+        self.files.push(file.into());
+    }
+
+    /// Add a directory to search for split debug info.
+    pub fn add_dir<P: Into<path::PathBuf>>(&mut self, dir: P) {
+        self.dirs.push(dir.into());
+    }
+
+    /// Were any debug files or search directories explicitly given?
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty() && self.dirs.is_empty()
+    }
+}
+
 /// Parse the file at the given path into IR items.
 pub fn read_and_parse<P: AsRef<path::Path>>(
     path: P,
     mode: traits::ParseMode,
+) -> anyhow::Result<ir::Items> {
+    read_and_parse_with_debug_info(path, mode, &DebugInfoSearch::default())
+}
+
+/// Parse the file at the given path into IR items, taking symbols and DWARF
+/// from split debug info found through `debug_info` when the file is a
+/// stripped object file.
+pub fn read_and_parse_with_debug_info<P: AsRef<path::Path>>(
+    path: P,
+    mode: traits::ParseMode,
+    debug_info: &DebugInfoSearch,
 ) -> anyhow::Result<ir::Items> {
     let path = path.as_ref();
     let mut file = fs::File::open(path)?;
@@ -29,9 +79,11 @@ pub fn read_and_parse<P: AsRef<path::Path>>(
 
     match mode {
         traits::ParseMode::Wasm => parse_wasm(&data),
+        traits::ParseMode::Map => map_parse::parse(&data),
+        traits::ParseMode::Snapshot => snapshot::parse(&data),
         #[cfg(feature = "dwarf")]
-        traits::ParseMode::Dwarf => parse_other(&data),
-        traits::ParseMode::Auto => parse_auto(path.extension(), &data),
+        traits::ParseMode::Dwarf => parse_other(path, &data, debug_info),
+        traits::ParseMode::Auto => parse_auto(path, &data, debug_info),
     }
 }
 
@@ -64,14 +116,23 @@ pub(crate) trait Parse<'a> {
     ) -> anyhow::Result<()>;
 }
 
-fn parse_auto(extension: Option<&OsStr>, data: &[u8]) -> anyhow::Result<ir::Items> {
-    if sniff_wasm(extension, &data) {
-        parse_wasm(&data)
+#[cfg_attr(not(feature = "dwarf"), allow(unused_variables))]
+fn parse_auto(
+    path: &path::Path,
+    data: &[u8],
+    debug_info: &DebugInfoSearch,
+) -> anyhow::Result<ir::Items> {
+    if sniff_wasm(path.extension(), data) {
+        parse_wasm(data)
+    } else if snapshot::sniff(data) {
+        snapshot::parse(data)
+    } else if sniff_map(path.extension(), data) {
+        map_parse::parse(data)
     } else {
         #[cfg(feature = "dwarf")]
-        let res = parse_other(&data);
+        let res = parse_other(path, data, debug_info);
         #[cfg(not(feature = "dwarf"))]
-        let res = parse_fallback(&data);
+        let res = parse_fallback(data);
         res
     }
 }
@@ -83,8 +144,15 @@ fn sniff_wasm(extension: Option<&OsStr>, data: &[u8]) -> bool {
     }
 }
 
+fn sniff_map(extension: Option<&OsStr>, data: &[u8]) -> bool {
+    match extension.and_then(|s| s.to_str()) {
+        Some("map") => true,
+        _ => map_parse::sniff(data),
+    }
+}
+
 fn parse_wasm(data: &[u8]) -> anyhow::Result<ir::Items> {
-    let mut items = ir::ItemsBuilder::new(data.len() as u32);
+    let mut items = ir::ItemsBuilder::new(data.len() as u64);
 
     let mut module1 = wasm_parse::ModuleReader::new(data);
     module1.parse_items(&mut items, ())?;
@@ -95,8 +163,12 @@ fn parse_wasm(data: &[u8]) -> anyhow::Result<ir::Items> {
 }
 
 #[cfg(feature = "dwarf")]
-fn parse_other(data: &[u8]) -> anyhow::Result<ir::Items> {
-    object_parse::parse(&data)
+fn parse_other(
+    path: &path::Path,
+    data: &[u8],
+    debug_info: &DebugInfoSearch,
+) -> anyhow::Result<ir::Items> {
+    object_parse::parse(path, data, debug_info)
 }
 
 fn parse_fallback(data: &[u8]) -> anyhow::Result<ir::Items> {
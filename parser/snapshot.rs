@@ -0,0 +1,54 @@
+//! Reading and writing snapshots of parsed `Items`.
+
+use std::io;
+
+use anyhow::anyhow;
+use twiggy_ir as ir;
+
+/// Every snapshot starts with this, followed by its format version.
+const SNAPSHOT_MAGIC: &[u8] = b"{\"twiggy_snapshot\":";
+
+/// Write a snapshot of the given items, which can later be read back in
+/// place of the binary they were parsed from.
+pub fn write_snapshot(items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+    serde_json::to_writer(&mut *dest, &items.snapshot())?;
+    writeln!(dest)?;
+    Ok(())
+}
+
+pub(crate) fn sniff(data: &[u8]) -> bool {
+    data.starts_with(SNAPSHOT_MAGIC)
+}
+
+/// Read just the format version that follows `SNAPSHOT_MAGIC`, so that it can
+/// be checked before reading the rest of the snapshot.
+fn version(data: &[u8]) -> anyhow::Result<u32> {
+    let rest = data
+        .strip_prefix(SNAPSHOT_MAGIC)
+        .ok_or_else(|| anyhow!("Failed to parse snapshot: missing the twiggy_snapshot header"))?;
+    match serde_json::Deserializer::from_slice(rest)
+        .into_iter::<u32>()
+        .next()
+    {
+        Some(Ok(version)) => Ok(version),
+        Some(Err(err)) => Err(anyhow!("Failed to parse snapshot with err: {}", err)),
+        None => Err(anyhow!(
+            "Failed to parse snapshot: missing its format version"
+        )),
+    }
+}
+
+pub(crate) fn parse(data: &[u8]) -> anyhow::Result<ir::Items> {
+    let version = version(data)?;
+    if version != ir::SNAPSHOT_VERSION {
+        return Err(anyhow!(
+            "Unsupported snapshot format version {}, this version of twiggy reads version {}",
+            version,
+            ir::SNAPSHOT_VERSION
+        ));
+    }
+
+    let snapshot: ir::Snapshot = serde_json::from_slice(data)
+        .map_err(|err| anyhow!("Failed to parse snapshot with err: {}", err))?;
+    Ok(snapshot.into_items())
+}
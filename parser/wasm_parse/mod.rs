@@ -1,6 +1,7 @@
 use super::Parse;
 use anyhow::anyhow;
 use std::collections::HashMap;
+use std::ops::Range;
 use twiggy_ir::{self as ir, Id};
 use wasmparser::SectionWithLimitedItems;
 use wasmparser::{self, Operator, SectionReader, Type};
@@ -21,12 +22,14 @@ struct CodeSection<'a> {
     index: usize,
     reader: wasmparser::CodeSectionReader<'a>,
     byte_size: usize,
+    range: Range<u64>,
 }
 
 struct FunctionSection<'a> {
     index: usize,
     reader: wasmparser::FunctionSectionReader<'a>,
     byte_size: usize,
+    range: Range<u64>,
 }
 
 pub struct ModuleReader<'a> {
@@ -36,9 +39,9 @@ pub struct ModuleReader<'a> {
 }
 
 impl<'a> ModuleReader<'a> {
-    pub fn new(data: &[u8]) -> ModuleReader {
+    pub fn new(data: &[u8]) -> ModuleReader<'_> {
         ModuleReader {
-            data: data,
+            data,
             offset: 0,
             parser: wasmparser::Parser::new(0),
         }
@@ -71,12 +74,13 @@ impl<'a> ModuleReader<'a> {
         byte_range: wasmparser::Range,
     ) -> anyhow::Result<CodeSection<'a>> {
         Ok(CodeSection {
-            index: index,
+            index,
             reader: wasmparser::CodeSectionReader::new(
                 &self.data[byte_range.start..byte_range.end],
                 byte_range.start,
             )?,
             byte_size: byte_range.end - start_offset,
+            range: start_offset as u64..byte_range.end as u64,
         })
     }
 }
@@ -88,7 +92,7 @@ impl<'a> Parse<'a> for ModuleReader<'a> {
         let mut sections: Vec<IndexedSection<'_>> = Vec::new();
         let mut code_section: Option<CodeSection<'_>> = None;
         let mut function_section: Option<FunctionSection<'_>> = None;
-        let mut sizes: HashMap<usize, u32> = HashMap::new();
+        let mut ranges: HashMap<usize, Range<u64>> = HashMap::new();
 
         // The function and code sections must be handled differently, so these
         // are not placed in the same `sections` array as the rest.
@@ -107,7 +111,10 @@ impl<'a> Parse<'a> for ModuleReader<'a> {
                     wasmparser::Chunk::Parsed { consumed, payload } => (payload, consumed),
                 };
             self.offset += bytes_consumed;
-            let size = self.current_position() - start;
+            let range = start as u64..self.current_position() as u64;
+            if let Some(name) = get_section_id_name(&section) {
+                items.name_section(idx, name);
+            }
             let indexed_section = IndexedSection(idx, section);
             match indexed_section.1 {
                 wasmparser::Payload::CodeSectionStart { range, .. } => {
@@ -117,7 +124,8 @@ impl<'a> Parse<'a> for ModuleReader<'a> {
                     function_section = Some(FunctionSection {
                         index: idx,
                         byte_size: reader.range().end - start,
-                        reader: reader,
+                        range: start as u64..reader.range().end as u64,
+                        reader,
                     });
                 }
                 wasmparser::Payload::CodeSectionEntry { .. } => {
@@ -125,7 +133,7 @@ impl<'a> Parse<'a> for ModuleReader<'a> {
                 }
                 _ => sections.push(indexed_section),
             };
-            sizes.insert(idx, size as u32);
+            ranges.insert(idx, range);
             idx += 1;
         }
 
@@ -211,11 +219,16 @@ impl<'a> Parse<'a> for ModuleReader<'a> {
             };
             let id = Id::section(idx);
             let added = items.size_added() - start;
-            let size = sizes
-                .get(&idx)
+            let range = ranges
+                .remove(&idx)
                 .ok_or_else(|| anyhow!("Could not find section size"))?;
-            assert!(added <= *size);
-            items.add_root(ir::Item::new(id, name, size - added, ir::Misc::new()));
+            let size = range.end - range.start;
+            assert!(added <= size);
+            let mut item = ir::Item::new(id, name, size - added, ir::Section::new());
+            for range in items.uncovered_file_ranges(range) {
+                item.add_file_range(range);
+            }
+            items.add_root(item);
         }
 
         Ok(())
@@ -240,7 +253,8 @@ impl<'a> Parse<'a> for ModuleReader<'a> {
                     function_section = Some(FunctionSection {
                         index: idx,
                         byte_size: reader.range().end - start,
-                        reader: reader,
+                        range: start as u64..reader.range().end as u64,
+                        reader,
                     });
                 }
                 _ => sections.push(IndexedSection(idx, section)),
@@ -427,6 +441,28 @@ fn get_section_name(section: &wasmparser::Payload<'_>) -> String {
     }
 }
 
+/// Get the short name of a section, like `code` or `data`, that its items are
+/// placed in. Custom sections go by their own name.
+fn get_section_id_name(section: &wasmparser::Payload<'_>) -> Option<String> {
+    let name = match section {
+        wasmparser::Payload::CustomSection { name, .. } => name,
+        wasmparser::Payload::TypeSection(_) => "type",
+        wasmparser::Payload::ImportSection(_) => "import",
+        wasmparser::Payload::FunctionSection(_) => "function",
+        wasmparser::Payload::TableSection(_) => "table",
+        wasmparser::Payload::MemorySection(_) => "memory",
+        wasmparser::Payload::GlobalSection(_) => "global",
+        wasmparser::Payload::ExportSection(_) => "export",
+        wasmparser::Payload::StartSection { .. } => "start",
+        wasmparser::Payload::ElementSection(_) => "element",
+        wasmparser::Payload::CodeSectionStart { .. } => "code",
+        wasmparser::Payload::DataSection(_) => "data",
+        wasmparser::Payload::DataCountSection { .. } => "datacount",
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
 #[derive(Default)]
 struct Names<'a> {
     function_names: HashMap<usize, &'a str>,
@@ -501,29 +537,33 @@ impl<'a> Parse<'a> for (FunctionSection<'a>, CodeSection<'a>) {
         let (func_section, code_section) = self;
 
         let func_section_index = func_section.index;
-        let func_items: Vec<ir::Item> = iterate_with_size(&mut func_section.reader)
+        let func_items: Vec<ir::Item> = iterate_with_range(&mut func_section.reader)
             .enumerate()
             .map(|(i, func)| {
-                let (_func, size) = func?;
+                let (_func, range) = func?;
                 let id = Id::entry(func_section_index, i);
                 let name = format!("func[{}]", i);
-                let item = ir::Item::new(id, name, size, ir::Misc::new());
-                Ok(item)
+                Ok(item_at(id, name, range, ir::Misc::new()))
             })
             .collect::<anyhow::Result<_>>()?;
 
         let code_section_index = code_section.index;
-        let code_items: Vec<ir::Item> = iterate_with_size(&mut code_section.reader)
-            .zip(func_items.into_iter())
+        let code_items: Vec<ir::Item> = iterate_with_range(&mut code_section.reader)
+            .zip(func_items)
             .enumerate()
             .map(|(i, (body, func))| {
-                let (_body, size) = body?;
+                let (_body, range) = body?;
                 let id = Id::entry(code_section_index, i);
                 let name = names
                     .get(&(i + imported_functions))
                     .map_or_else(|| format!("code[{}]", i), |name| name.to_string());
                 let code = ir::Code::new(&name);
-                let item = ir::Item::new(id, name, size + func.size(), code);
+                let size = (range.end - range.start) + func.size();
+                let mut item = ir::Item::new(id, name, size, code);
+                for range in func.file_ranges() {
+                    item.add_file_range(range.clone());
+                }
+                item.add_file_range(range);
                 Ok(item)
             })
             .collect::<anyhow::Result<_>>()?;
@@ -535,12 +575,18 @@ impl<'a> Parse<'a> for (FunctionSection<'a>, CodeSection<'a>) {
         }
         let id = Id::section(code_section.index);
         let added = items.size_added() - start;
-        let code_section_size = code_section.byte_size as u32;
-        let func_section_size = func_section.byte_size as u32;
+        let code_section_size = code_section.byte_size as u64;
+        let func_section_size = func_section.byte_size as u64;
         let size = code_section_size + func_section_size;
 
         assert!(added <= size);
-        items.add_root(ir::Item::new(id, name, size - added, ir::Misc::new()));
+        let mut item = ir::Item::new(id, name, size - added, ir::Section::new());
+        for section in [&func_section.range, &code_section.range] {
+            for range in items.uncovered_file_ranges(section.clone()) {
+                item.add_file_range(range);
+            }
+        }
+        items.add_root(item);
 
         Ok(())
     }
@@ -554,24 +600,24 @@ impl<'a> Parse<'a> for (FunctionSection<'a>, CodeSection<'a>) {
     ) -> anyhow::Result<()> {
         let (function_section, code_section) = self;
 
-        type Edge = (ir::Id, ir::Id);
+        type Edge = (ir::Id, ir::Id, ir::EdgeKind);
 
         let mut edges: Vec<Edge> = Vec::new();
 
         // Function section reader parsing.
-        for (func_i, type_ref) in iterate_with_size(&mut function_section.reader).enumerate() {
+        for (func_i, type_ref) in iterate_with_range(&mut function_section.reader).enumerate() {
             let (type_ref, _) = type_ref?;
             if let Some(type_idx) = indices.type_ {
                 let type_id = Id::entry(type_idx, type_ref as usize);
                 if let Some(code_idx) = indices.code {
                     let body_id = Id::entry(code_idx, func_i);
-                    edges.push((body_id, type_id));
+                    edges.push((body_id, type_id, ir::EdgeKind::Type));
                 }
             }
         }
 
         // Code section reader parsing.
-        for (b_i, body) in iterate_with_size(&mut code_section.reader).enumerate() {
+        for (b_i, body) in iterate_with_range(&mut code_section.reader).enumerate() {
             let (body, _size) = body?;
             let body_id = Id::entry(code_section.index, b_i);
 
@@ -581,7 +627,7 @@ impl<'a> Parse<'a> for (FunctionSection<'a>, CodeSection<'a>) {
                 match op? {
                     Operator::Call { function_index } => {
                         let f_id = indices.functions[function_index as usize];
-                        edges.push((body_id, f_id));
+                        edges.push((body_id, f_id, ir::EdgeKind::Call));
                     }
 
                     // TODO: Rather than looking at indirect calls, need to look
@@ -591,7 +637,7 @@ impl<'a> Parse<'a> for (FunctionSection<'a>, CodeSection<'a>) {
 
                     Operator::GlobalGet { global_index } | Operator::GlobalSet { global_index } => {
                         let g_id = indices.globals[global_index as usize];
-                        edges.push((body_id, g_id));
+                        edges.push((body_id, g_id, ir::EdgeKind::Global));
                     }
 
                     Operator::I32Load { memarg }
@@ -610,7 +656,7 @@ impl<'a> Parse<'a> for (FunctionSection<'a>, CodeSection<'a>) {
                     | Operator::F64Load { memarg } => {
                         if let Some(Operator::I32Const { value }) = prev {
                             if let Some(data_id) = items.get_data(value as u64 + memarg.offset) {
-                                edges.push((body_id, data_id));
+                                edges.push((body_id, data_id, ir::EdgeKind::Data));
                             }
                         }
                     }
@@ -621,7 +667,7 @@ impl<'a> Parse<'a> for (FunctionSection<'a>, CodeSection<'a>) {
 
         edges
             .into_iter()
-            .for_each(|(from, to)| items.add_edge(from, to));
+            .for_each(|(from, to, kind)| items.add_edge(from, to, kind));
 
         Ok(())
     }
@@ -641,7 +687,7 @@ impl<'a> Parse<'a> for wasmparser::NameSectionReader<'a> {
             } else {
                 continue;
             };
-            let size = (self.original_position() - start) as u32;
+            let range = start as u64..self.original_position() as u64;
             let name = match subsection {
                 wasmparser::Name::Module(_) => "\"module name\" subsection",
                 wasmparser::Name::Function(_) => "\"function names\" subsection",
@@ -656,7 +702,7 @@ impl<'a> Parse<'a> for wasmparser::NameSectionReader<'a> {
                 wasmparser::Name::Data(_) => "\"data names\" subsection",
             };
             let id = Id::entry(idx, i);
-            items.add_root(ir::Item::new(id, name, size, ir::DebugInfo::new()));
+            items.add_root(item_at(id, name, range, ir::DebugInfo::new()));
             i += 1;
         }
 
@@ -684,10 +730,11 @@ impl<'a> Parse<'a> for CustomSectionReader<'a> {
             wasmparser::NameSectionReader::new(self.data, self.data_offset)?
                 .parse_items(items, idx)?;
         } else {
-            let size = self.data.len() as u32;
+            let start = self.data_offset as u64;
+            let range = start..start + self.data.len() as u64;
             let id = Id::entry(idx, 0);
             let name = format!("custom section '{}'", self.name);
-            items.add_item(ir::Item::new(id, name, size, ir::Misc::new()));
+            items.add_item(item_at(id, name, range, ir::Misc::new()));
         }
         Ok(())
     }
@@ -703,8 +750,8 @@ impl<'a> Parse<'a> for wasmparser::TypeSectionReader<'a> {
     type ItemsExtra = usize;
 
     fn parse_items(&mut self, items: &mut ir::ItemsBuilder, idx: usize) -> anyhow::Result<()> {
-        for (i, ty) in iterate_with_size(self).enumerate() {
-            let (ty, size) = ty?;
+        for (i, ty) in iterate_with_range(self).enumerate() {
+            let (ty, range) = ty?;
             let id = Id::entry(idx, i);
 
             match ty {
@@ -722,18 +769,21 @@ impl<'a> Parse<'a> for wasmparser::TypeSectionReader<'a> {
                         0 => name.push_str("nil"),
                         1 => name.push_str(ty2str(func.returns[0])),
                         _ => {
-                            name.push_str("(");
+                            name.push('(');
                             for (i, result) in func.returns.iter().enumerate() {
                                 if i != 0 {
                                     name.push_str(", ");
                                 }
                                 name.push_str(ty2str(*result));
                             }
-                            name.push_str(")");
+                            name.push(')');
                         }
                     }
 
-                    items.add_item(ir::Item::new(id, name, size, ir::Misc::new()));
+                    let params = func.params.iter().map(|t| ty2str(*t).to_string());
+                    let results = func.returns.iter().map(|t| ty2str(*t).to_string());
+                    let ty = ir::Type::new(params.collect(), results.collect());
+                    items.add_item(item_at(id, name, range, ty));
                 }
                 wasmparser::TypeDef::Module(_module) => {}
                 wasmparser::TypeDef::Instance(_instance) => {}
@@ -753,11 +803,22 @@ impl<'a> Parse<'a> for wasmparser::ImportSectionReader<'a> {
     type ItemsExtra = usize;
 
     fn parse_items(&mut self, items: &mut ir::ItemsBuilder, idx: usize) -> anyhow::Result<()> {
-        for (i, imp) in iterate_with_size(self).enumerate() {
-            let (imp, size) = imp?;
+        for (i, imp) in iterate_with_range(self).enumerate() {
+            let (imp, range) = imp?;
             let id = Id::entry(idx, i);
-            let name = format!("import {}::{}", imp.module, imp.field.unwrap_or("unknown"));
-            items.add_item(ir::Item::new(id, name, size, ir::Misc::new()));
+            let field = imp.field.unwrap_or("unknown");
+            let name = format!("import {}::{}", imp.module, field);
+            let kind = match imp.ty {
+                wasmparser::ImportSectionEntryType::Function(_) => Some(ir::ExternalKind::Function),
+                wasmparser::ImportSectionEntryType::Table(_) => Some(ir::ExternalKind::Table),
+                wasmparser::ImportSectionEntryType::Memory(_) => Some(ir::ExternalKind::Memory),
+                wasmparser::ImportSectionEntryType::Global(_) => Some(ir::ExternalKind::Global),
+                wasmparser::ImportSectionEntryType::Tag(_) => Some(ir::ExternalKind::Tag),
+                wasmparser::ImportSectionEntryType::Module(_)
+                | wasmparser::ImportSectionEntryType::Instance(_) => None,
+            };
+            let import = ir::Import::new(imp.module.to_string(), field.to_string(), kind);
+            items.add_item(item_at(id, name, range, import));
         }
         Ok(())
     }
@@ -773,11 +834,11 @@ impl<'a> Parse<'a> for wasmparser::TableSectionReader<'a> {
     type ItemsExtra = usize;
 
     fn parse_items(&mut self, items: &mut ir::ItemsBuilder, idx: usize) -> anyhow::Result<()> {
-        for (i, entry) in iterate_with_size(self).enumerate() {
-            let (_entry, size) = entry?;
+        for (i, entry) in iterate_with_range(self).enumerate() {
+            let (_entry, range) = entry?;
             let id = Id::entry(idx, i);
             let name = format!("table[{}]", i);
-            items.add_root(ir::Item::new(id, name, size, ir::Misc::new()));
+            items.add_root(item_at(id, name, range, ir::Table::new()));
         }
         Ok(())
     }
@@ -793,11 +854,11 @@ impl<'a> Parse<'a> for wasmparser::MemorySectionReader<'a> {
     type ItemsExtra = usize;
 
     fn parse_items(&mut self, items: &mut ir::ItemsBuilder, idx: usize) -> anyhow::Result<()> {
-        for (i, mem) in iterate_with_size(self).enumerate() {
-            let (_mem, size) = mem?;
+        for (i, mem) in iterate_with_range(self).enumerate() {
+            let (_mem, range) = mem?;
             let id = Id::entry(idx, i);
             let name = format!("memory[{}]", i);
-            items.add_item(ir::Item::new(id, name, size, ir::Misc::new()));
+            items.add_item(item_at(id, name, range, ir::Memory::new()));
         }
         Ok(())
     }
@@ -813,12 +874,13 @@ impl<'a> Parse<'a> for wasmparser::GlobalSectionReader<'a> {
     type ItemsExtra = usize;
 
     fn parse_items(&mut self, items: &mut ir::ItemsBuilder, idx: usize) -> anyhow::Result<()> {
-        for (i, g) in iterate_with_size(self).enumerate() {
-            let (g, size) = g?;
+        for (i, g) in iterate_with_range(self).enumerate() {
+            let (g, range) = g?;
             let id = Id::entry(idx, i);
             let name = format!("global[{}]", i);
             let ty = ty2str(g.ty.content_type).to_string();
-            items.add_item(ir::Item::new(id, name, size, ir::Data::new(Some(ty))));
+            let global = ir::Global::new(ty, g.ty.mutable);
+            items.add_item(item_at(id, name, range, global));
         }
         Ok(())
     }
@@ -834,11 +896,15 @@ impl<'a> Parse<'a> for wasmparser::ExportSectionReader<'a> {
     type ItemsExtra = usize;
 
     fn parse_items(&mut self, items: &mut ir::ItemsBuilder, idx: usize) -> anyhow::Result<()> {
-        for (i, exp) in iterate_with_size(self).enumerate() {
-            let (exp, size) = exp?;
+        for (i, exp) in iterate_with_range(self).enumerate() {
+            let (exp, range) = exp?;
             let id = Id::entry(idx, i);
             let name = format!("export \"{}\"", exp.field);
-            items.add_root(ir::Item::new(id, name, size, ir::Misc::new()));
+            let kind: ir::ItemKind = match external_kind(exp.kind) {
+                Some(target) => ir::Export::new(target).into(),
+                None => ir::Misc::new().into(),
+            };
+            items.add_root(item_at(id, name, range, kind));
         }
         Ok(())
     }
@@ -850,21 +916,37 @@ impl<'a> Parse<'a> for wasmparser::ExportSectionReader<'a> {
         items: &mut ir::ItemsBuilder,
         (indices, idx): Self::EdgesExtra,
     ) -> anyhow::Result<()> {
-        for (i, exp) in iterate_with_size(self).enumerate() {
+        for (i, exp) in iterate_with_range(self).enumerate() {
             let (exp, _) = exp?;
             let exp_id = Id::entry(idx, i);
             match exp.kind {
                 wasmparser::ExternalKind::Function => {
-                    items.add_edge(exp_id, indices.functions[exp.index as usize]);
+                    items.add_edge(
+                        exp_id,
+                        indices.functions[exp.index as usize],
+                        ir::EdgeKind::Export,
+                    );
                 }
                 wasmparser::ExternalKind::Table => {
-                    items.add_edge(exp_id, indices.tables[exp.index as usize]);
+                    items.add_edge(
+                        exp_id,
+                        indices.tables[exp.index as usize],
+                        ir::EdgeKind::Export,
+                    );
                 }
                 wasmparser::ExternalKind::Memory => {
-                    items.add_edge(exp_id, indices.memories[exp.index as usize]);
+                    items.add_edge(
+                        exp_id,
+                        indices.memories[exp.index as usize],
+                        ir::EdgeKind::Export,
+                    );
                 }
                 wasmparser::ExternalKind::Global => {
-                    items.add_edge(exp_id, indices.globals[exp.index as usize]);
+                    items.add_edge(
+                        exp_id,
+                        indices.globals[exp.index as usize],
+                        ir::EdgeKind::Export,
+                    );
                 }
                 wasmparser::ExternalKind::Tag
                 | wasmparser::ExternalKind::Type
@@ -899,6 +981,7 @@ impl<'a> Parse<'a> for StartSection<'a> {
         items.add_edge(
             Id::section(idx),
             indices.functions[self.function_index as usize],
+            ir::EdgeKind::Start,
         );
         Ok(())
     }
@@ -908,11 +991,11 @@ impl<'a> Parse<'a> for wasmparser::ElementSectionReader<'a> {
     type ItemsExtra = usize;
 
     fn parse_items(&mut self, items: &mut ir::ItemsBuilder, idx: usize) -> anyhow::Result<()> {
-        for (i, elem) in iterate_with_size(self).enumerate() {
-            let (_elem, size) = elem?;
+        for (i, elem) in iterate_with_range(self).enumerate() {
+            let (_elem, range) = elem?;
             let id = Id::entry(idx, i);
             let name = format!("elem[{}]", i);
-            items.add_item(ir::Item::new(id, name, size, ir::Misc::new()));
+            items.add_item(item_at(id, name, range, ir::Element::new()));
         }
         Ok(())
     }
@@ -924,13 +1007,17 @@ impl<'a> Parse<'a> for wasmparser::ElementSectionReader<'a> {
         items: &mut ir::ItemsBuilder,
         (indices, idx): Self::EdgesExtra,
     ) -> anyhow::Result<()> {
-        for (i, elem) in iterate_with_size(self).enumerate() {
+        for (i, elem) in iterate_with_range(self).enumerate() {
             let (elem, _size) = elem?;
             let elem_id = Id::entry(idx, i);
 
             match elem.kind {
                 wasmparser::ElementKind::Active { table_index, .. } => {
-                    items.add_edge(indices.tables[table_index as usize], elem_id);
+                    items.add_edge(
+                        indices.tables[table_index as usize],
+                        elem_id,
+                        ir::EdgeKind::Table,
+                    );
                 }
                 wasmparser::ElementKind::Declared => {}
                 wasmparser::ElementKind::Passive => {}
@@ -939,7 +1026,11 @@ impl<'a> Parse<'a> for wasmparser::ElementSectionReader<'a> {
                 let element_item = element_item?;
                 match element_item {
                     wasmparser::ElementItem::Func(func_idx) => {
-                        items.add_edge(elem_id, indices.functions[func_idx as usize]);
+                        items.add_edge(
+                            elem_id,
+                            indices.functions[func_idx as usize],
+                            ir::EdgeKind::Table,
+                        );
                     }
                     wasmparser::ElementItem::Null(_ty) => {}
                 }
@@ -958,14 +1049,18 @@ impl<'a> Parse<'a> for wasmparser::DataSectionReader<'a> {
         items: &mut ir::ItemsBuilder,
         (idx, names): Self::ItemsExtra,
     ) -> anyhow::Result<()> {
-        for (i, d) in iterate_with_size(self).enumerate() {
-            let (d, size) = d?;
+        for (i, d) in iterate_with_range(self).enumerate() {
+            let (d, range) = d?;
             let id = Id::entry(idx, i);
             let name = names.get(&i).map_or_else(
                 || format!("data[{}]", i),
                 |name| format!("data segment \"{}\"", name),
             );
-            items.add_item(ir::Item::new(id, name, size, ir::Data::new(None)));
+            let mode = match d.kind {
+                wasmparser::DataKind::Active { .. } => ir::SegmentMode::Active,
+                wasmparser::DataKind::Passive => ir::SegmentMode::Passive,
+            };
+            items.add_item(item_at(id, name, range, ir::Data::segment(mode)));
 
             // Get the constant address (if any) from the initialization
             // expression.
@@ -993,21 +1088,45 @@ impl<'a> Parse<'a> for wasmparser::DataSectionReader<'a> {
     }
 }
 
-fn iterate_with_size<'a, S: SectionWithLimitedItems + SectionReader>(
+fn iterate_with_range<'a, S: SectionWithLimitedItems + SectionReader>(
     s: &'a mut S,
-) -> impl Iterator<Item = anyhow::Result<(S::Item, u32)>> + 'a {
+) -> impl Iterator<Item = anyhow::Result<(S::Item, Range<u64>)>> + 'a {
     let count = s.get_count();
     (0..count).map(move |i| {
         let start = s.original_position();
         let item = s.read()?;
-        let size = (s.original_position() - start) as u32;
+        let range = start as u64..s.original_position() as u64;
         if i == count - 1 {
             s.ensure_end()?;
         }
-        Ok((item, size))
+        Ok((item, range))
     })
 }
 
+/// Construct an item made of the bytes in the given range of the file.
+fn item_at<S, K>(id: Id, name: S, range: Range<u64>, kind: K) -> ir::Item
+where
+    S: Into<String>,
+    K: Into<ir::ItemKind>,
+{
+    let mut item = ir::Item::new(id, name, range.end - range.start, kind);
+    item.add_file_range(range);
+    item
+}
+
+fn external_kind(kind: wasmparser::ExternalKind) -> Option<ir::ExternalKind> {
+    match kind {
+        wasmparser::ExternalKind::Function => Some(ir::ExternalKind::Function),
+        wasmparser::ExternalKind::Table => Some(ir::ExternalKind::Table),
+        wasmparser::ExternalKind::Memory => Some(ir::ExternalKind::Memory),
+        wasmparser::ExternalKind::Global => Some(ir::ExternalKind::Global),
+        wasmparser::ExternalKind::Tag => Some(ir::ExternalKind::Tag),
+        wasmparser::ExternalKind::Type
+        | wasmparser::ExternalKind::Module
+        | wasmparser::ExternalKind::Instance => None,
+    }
+}
+
 fn ty2str(t: Type) -> &'static str {
     match t {
         Type::I32 => "i32",
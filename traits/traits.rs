@@ -18,23 +18,22 @@ pub trait Analyze {
 }
 
 /// Selects the parse mode for the input data.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Default)]
 pub enum ParseMode {
     /// WebAssembly file parse mode.
     Wasm,
     /// DWARF sections parse mode.
     #[cfg(feature = "dwarf")]
     Dwarf,
+    /// Linker map file (GNU ld, lld or link.exe) parse mode.
+    Map,
+    /// Snapshot written by `twiggy snapshot` parse mode.
+    Snapshot,
     /// Automatically determined mode of parsing, e.g. based on file extension.
+    #[default]
     Auto,
 }
 
-impl Default for ParseMode {
-    fn default() -> ParseMode {
-        ParseMode::Auto
-    }
-}
-
 impl FromStr for ParseMode {
     type Err = anyhow::Error;
 
@@ -43,6 +42,8 @@ impl FromStr for ParseMode {
             "wasm" => Ok(ParseMode::Wasm),
             #[cfg(feature = "dwarf")]
             "dwarf" => Ok(ParseMode::Dwarf),
+            "map" => Ok(ParseMode::Map),
+            "snapshot" => Ok(ParseMode::Snapshot),
             "auto" => Ok(ParseMode::Auto),
             _ => Err(anyhow!("Unknown parse mode: {}", s)),
         }
@@ -72,6 +73,7 @@ pub enum OutputFormat {
 #[cfg(feature = "emit_text")]
 #[cfg(feature = "emit_csv")]
 #[cfg(feature = "emit_json")]
+#[allow(clippy::derivable_impls)]
 impl Default for OutputFormat {
     fn default() -> OutputFormat {
         OutputFormat::Text
@@ -94,6 +96,93 @@ impl FromStr for OutputFormat {
     }
 }
 
+/// What to group items by, rather than showing each item.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GroupBy {
+    /// The category that classification rules put the item in.
+    Category,
+    /// The crate that the item belongs to.
+    Crate,
+    /// The generic function that the item is a monomorphization of.
+    Generic,
+    /// The section that the item is in.
+    Section,
+    /// The kind of the item.
+    Kind,
+}
+
+impl GroupBy {
+    /// The name of this grouping, as accepted by `GroupBy::from_str`.
+    pub fn name(self) -> &'static str {
+        match self {
+            GroupBy::Category => "category",
+            GroupBy::Crate => "crate",
+            GroupBy::Generic => "generic",
+            GroupBy::Section => "section",
+            GroupBy::Kind => "kind",
+        }
+    }
+}
+
+impl FromStr for GroupBy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "category" => Ok(GroupBy::Category),
+            "crate" => Ok(GroupBy::Crate),
+            "generic" => Ok(GroupBy::Generic),
+            "section" => Ok(GroupBy::Section),
+            "kind" => Ok(GroupBy::Kind),
+            _ => Err(anyhow!("Unknown group: {}", s)),
+        }
+    }
+}
+
+/// How an item, or a group of items, changed between two versions of a
+/// binary.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Change {
+    /// Only in the new version.
+    Added,
+    /// Only in the old version.
+    Removed,
+    /// Larger in the new version.
+    Grown,
+    /// Smaller in the new version.
+    Shrunk,
+    /// Given a new name, and otherwise the same size.
+    Renamed,
+}
+
+impl Change {
+    /// The name of this change, as accepted by `Change::from_str`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Change::Added => "added",
+            Change::Removed => "removed",
+            Change::Grown => "grown",
+            Change::Shrunk => "shrunk",
+            Change::Renamed => "renamed",
+        }
+    }
+}
+
+impl FromStr for Change {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "added" => Ok(Change::Added),
+            "removed" => Ok(Change::Removed),
+            "grown" => Ok(Change::Grown),
+            "shrunk" => Ok(Change::Shrunk),
+            "renamed" => Ok(Change::Renamed),
+            _ => Err(anyhow!("Unknown change: {}", s)),
+        }
+    }
+}
+
 /// Anything that can write itself in the given output format to the given
 /// destination.
 pub trait Emit {
@@ -0,0 +1,134 @@
+test!(
+    categories_top,
+    "top",
+    "./fixtures/wee_alloc.wasm",
+    "--rules",
+    "./fixtures/rules.toml",
+    "--group-by",
+    "category"
+);
+
+test!(
+    categories_top_retained,
+    "top",
+    "./fixtures/wee_alloc.wasm",
+    "--rules",
+    "./fixtures/rules.toml",
+    "--group-by",
+    "category",
+    "--retained",
+    "-n",
+    "3"
+);
+
+test!(
+    categories_top_json,
+    "top",
+    "./fixtures/wee_alloc.wasm",
+    "--rules",
+    "./fixtures/rules.toml",
+    "--group-by",
+    "category",
+    "-f",
+    "json"
+);
+
+test!(
+    categories_top_csv,
+    "top",
+    "./fixtures/wee_alloc.wasm",
+    "--rules",
+    "./fixtures/rules.toml",
+    "--group-by",
+    "category",
+    "-f",
+    "csv"
+);
+
+test!(
+    categories_top_filter,
+    "top",
+    "./fixtures/wee_alloc.wasm",
+    "--rules",
+    "./fixtures/rules.toml",
+    "--category",
+    "allocator",
+    "--category",
+    "app"
+);
+
+test!(
+    categories_top_uncategorized,
+    "top",
+    "./fixtures/wee_alloc.wasm",
+    "--rules",
+    "./fixtures/rules.toml",
+    "--category",
+    "[uncategorized]",
+    "-n",
+    "5"
+);
+
+test!(
+    categories_top_elf_sections,
+    "top",
+    "./fixtures/hello_elf",
+    "--rules",
+    "./fixtures/rules.toml",
+    "--group-by",
+    "category"
+);
+
+test!(
+    categories_dominators,
+    "dominators",
+    "./fixtures/wee_alloc.wasm",
+    "--rules",
+    "./fixtures/rules.toml",
+    "--category",
+    "app"
+);
+
+test!(
+    categories_diff,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--rules",
+    "./fixtures/rules.toml",
+    "--group-by",
+    "category"
+);
+
+test!(
+    categories_diff_filter,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--rules",
+    "./fixtures/rules.toml",
+    "--category",
+    "allocator"
+);
+
+test!(
+    categories_garbage,
+    "garbage",
+    "./fixtures/garbage.wasm",
+    "--rules",
+    "./fixtures/rules.toml",
+    "--group-by",
+    "category"
+);
+
+test!(
+    categories_diff_retained,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--rules",
+    "./fixtures/rules.toml",
+    "--group-by",
+    "category",
+    "--retained"
+);
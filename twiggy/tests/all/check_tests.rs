@@ -0,0 +1,63 @@
+test!(
+    check_wee_alloc,
+    failing,
+    "check",
+    "./fixtures/wee_alloc.wasm",
+    "--budget",
+    "./fixtures/budget.toml"
+);
+
+test!(
+    check_wee_alloc_baseline,
+    "check",
+    "./fixtures/wee_alloc.2.wasm",
+    "--budget",
+    "./fixtures/budget.toml",
+    "--baseline",
+    "./fixtures/wee_alloc.wasm"
+);
+
+test!(
+    check_wee_alloc_json,
+    failing,
+    "check",
+    "./fixtures/wee_alloc.wasm",
+    "--budget",
+    "./fixtures/budget.toml",
+    "--baseline",
+    "./fixtures/wee_alloc.2.wasm",
+    "-f",
+    "json"
+);
+
+test!(
+    check_wee_alloc_csv,
+    "check",
+    "./fixtures/wee_alloc.2.wasm",
+    "--budget",
+    "./fixtures/budget.toml",
+    "--baseline",
+    "./fixtures/wee_alloc.snapshot",
+    "-f",
+    "csv"
+);
+
+test!(
+    check_fractional_units,
+    failing,
+    "check",
+    "./fixtures/wee_alloc.wasm",
+    "--budget",
+    "./fixtures/budget_units.toml",
+    "-f",
+    "csv"
+);
+
+test!(
+    check_size_overflow,
+    failing stderr,
+    "check",
+    "./fixtures/wee_alloc.wasm",
+    "--budget",
+    "./fixtures/budget_overflow.toml"
+);
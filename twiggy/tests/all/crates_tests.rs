@@ -0,0 +1,50 @@
+test!(crates_wasm, "crates", "./fixtures/monos.wasm");
+
+test!(
+    crates_max_depth,
+    "crates",
+    "./fixtures/monos.wasm",
+    "-d",
+    "3",
+    "-n",
+    "3"
+);
+
+test!(
+    crates_retained,
+    "crates",
+    "./fixtures/monos.wasm",
+    "--retained",
+    "-n",
+    "5"
+);
+
+test!(
+    crates_rust_v0_json,
+    "crates",
+    "./fixtures/monos_v0",
+    "-d",
+    "2",
+    "-f",
+    "json"
+);
+
+test!(
+    crates_csv,
+    "crates",
+    "./fixtures/monos.wasm",
+    "-d",
+    "2",
+    "-n",
+    "3",
+    "-f",
+    "csv"
+);
+
+test!(
+    crates_elf_code,
+    "crates",
+    "./fixtures/hello_elf",
+    "--kind",
+    "code"
+);
@@ -0,0 +1,178 @@
+//! Tests on a synthetic chain of items that is far deeper than any recursion
+//! over the graph could go without overflowing the stack.
+
+use std::fmt::Write;
+use std::fs;
+use std::process::Command;
+use std::sync::OnceLock;
+
+/// The number of items in the chain.
+const DEPTH: u32 = 150_000;
+
+/// Write a snapshot of the chain `f0 -> f1 -> ... -> f149999`, rooted at `f0`,
+/// with the given size for the last item and a size of one for the others, to
+/// the given file in the target directory, and return its path.
+fn write_chain(file_name: &str, last_size: u64) -> String {
+    let mut items = String::new();
+    let mut edges = String::new();
+    for i in 0..DEPTH {
+        if i > 0 {
+            items.push(',');
+        }
+        let size = if i + 1 < DEPTH { 1 } else { last_size };
+        write!(
+            items,
+            r#"{{"id":{},"name":"f{}","size":{},"kind":"code"}}"#,
+            i, i, size
+        )
+        .unwrap();
+        if i + 1 < DEPTH {
+            if i > 0 {
+                edges.push(',');
+            }
+            write!(edges, r#"[{},[[{},"call"]]]"#, i, i + 1).unwrap();
+        }
+    }
+    let snapshot = format!(
+        r#"{{"twiggy_snapshot":3,"size":{},"items":[{}],"roots":[0],"edges":[{}]}}"#,
+        u64::from(DEPTH) - 1 + last_size,
+        items,
+        edges
+    );
+
+    let path = format!("{}/{}", env!("CARGO_TARGET_TMPDIR"), file_name);
+    fs::write(&path, snapshot).unwrap();
+    path
+}
+
+/// The chain, with every item of size one.
+fn deep_chain() -> &'static str {
+    static PATH: OnceLock<String> = OnceLock::new();
+    PATH.get_or_init(|| write_chain("deep_chain.snapshot", 1))
+}
+
+/// The chain, with its last item grown to two bytes, which grows the retained
+/// size of every item.
+fn deep_chain_grown() -> &'static str {
+    static PATH: OnceLock<String> = OnceLock::new();
+    PATH.get_or_init(|| write_chain("deep_chain_grown.snapshot", 2))
+}
+
+/// Run `twiggy` with the given arguments and return what it wrote to stdout,
+/// for output over the whole chain, which is too large to keep as an
+/// expectation.
+fn run_twiggy(args: &[&str]) -> String {
+    let output = Command::new("cargo")
+        .arg("run")
+        .arg("--quiet")
+        .arg("--")
+        .args(args)
+        .current_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/all/"))
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "should have run `twiggy {}` OK\n\n{}",
+        args.join(" "),
+        String::from_utf8_lossy(&output.stderr),
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+test!(
+    deep_top_retained,
+    "top",
+    deep_chain(),
+    "-n",
+    "3",
+    "--retained"
+);
+
+test!(deep_dominators, "dominators", deep_chain(), "-d", "3");
+
+test!(deep_paths, "paths", deep_chain(), "f149999", "-d", "3");
+
+test!(
+    deep_paths_descending,
+    "paths",
+    deep_chain(),
+    "f0",
+    "--descending",
+    "-d",
+    "3"
+);
+
+test!(deep_garbage, "garbage", deep_chain());
+
+// The whole tree, with no maximum depth, nests one object per item.
+#[test]
+fn deep_dominators_json() {
+    let json = run_twiggy(&["dominators", deep_chain(), "-f", "json"]);
+    assert!(json.starts_with(r#"{"items":[{"name":"<meta root>","#));
+    assert_eq!(json.matches(r#""name":"#).count(), DEPTH as usize + 1);
+    assert_eq!(json.matches('{').count(), json.matches('}').count());
+    assert!(json.contains(r#"{"name":"f149999","shallow_size":1,"#));
+}
+
+#[test]
+fn deep_dominators_csv() {
+    let csv = run_twiggy(&["dominators", deep_chain(), "-f", "csv"]);
+    let lines: Vec<&str> = csv.lines().collect();
+    // The header, the meta root and every item.
+    assert_eq!(lines.len(), DEPTH as usize + 2);
+    assert_eq!(
+        lines.last().unwrap(),
+        &"149999,f149999,1,0.0006666666666666666,1,0.0006666666666666666,149998"
+    );
+}
+
+test!(
+    deep_diff_dominators_unchanged,
+    "diff",
+    deep_chain(),
+    deep_chain(),
+    "--dominators"
+);
+
+test!(
+    deep_diff_dominators,
+    "diff",
+    deep_chain(),
+    deep_chain_grown(),
+    "--dominators",
+    "-n",
+    "5"
+);
+
+test!(
+    deep_diff_dominators_json,
+    "diff",
+    deep_chain(),
+    deep_chain_grown(),
+    "--dominators",
+    "-n",
+    "3",
+    "-f",
+    "json"
+);
+
+// Every item's retained size changed, so the whole chain is shown.
+#[test]
+fn deep_diff_dominators_json_all() {
+    let json = run_twiggy(&[
+        "diff",
+        deep_chain(),
+        deep_chain_grown(),
+        "--dominators",
+        "-n",
+        "200000",
+        "-f",
+        "json",
+    ]);
+    assert_eq!(
+        json.matches(r#""retained_delta_bytes":1,"#).count(),
+        DEPTH as usize + 1
+    );
+    assert_eq!(json.matches('{').count(), json.matches('}').count());
+    assert!(json.contains(r#"{"name":"f149999","retained_delta_bytes":1,"#));
+}
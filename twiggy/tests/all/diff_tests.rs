@@ -89,3 +89,241 @@ test!(
     "hello",
     "goodbye"
 );
+
+test!(
+    diff_wee_alloc_kind_data,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--kind",
+    "data"
+);
+
+test!(
+    diff_wee_alloc_exact_names,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--exact-names"
+);
+
+test!(
+    diff_renames,
+    "diff",
+    "./fixtures/renames.old.snapshot",
+    "./fixtures/renames.new.snapshot"
+);
+
+test!(
+    diff_renames_exact_names,
+    "diff",
+    "./fixtures/renames.old.snapshot",
+    "./fixtures/renames.new.snapshot",
+    "--exact-names"
+);
+
+test!(
+    diff_renames_json,
+    "diff",
+    "./fixtures/renames.old.snapshot",
+    "./fixtures/renames.new.snapshot",
+    "-f",
+    "json"
+);
+
+test!(
+    diff_renames_csv,
+    "diff",
+    "./fixtures/renames.old.snapshot",
+    "./fixtures/renames.new.snapshot",
+    "-f",
+    "csv"
+);
+
+test!(
+    diff_renames_retained,
+    "diff",
+    "./fixtures/renames.old.snapshot",
+    "./fixtures/renames.new.snapshot",
+    "--retained"
+);
+
+test!(
+    diff_renames_retained_json,
+    "diff",
+    "./fixtures/renames.old.snapshot",
+    "./fixtures/renames.new.snapshot",
+    "--retained",
+    "-f",
+    "json"
+);
+
+test!(
+    diff_renames_change,
+    "diff",
+    "./fixtures/renames.old.snapshot",
+    "./fixtures/renames.new.snapshot",
+    "--change",
+    "added",
+    "--change",
+    "renamed"
+);
+
+test!(
+    diff_wee_alloc_change_shrunk,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--change",
+    "shrunk"
+);
+
+test!(
+    diff_wee_alloc_explain,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--explain"
+);
+
+test!(
+    diff_wee_alloc_explain_json,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--explain",
+    "-n",
+    "8",
+    "-f",
+    "json"
+);
+
+test!(
+    diff_wee_alloc_explain_csv,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--explain",
+    "-n",
+    "8",
+    "-f",
+    "csv"
+);
+
+test!(
+    diff_wee_alloc_group_by_crate,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--group-by",
+    "crate"
+);
+
+test!(
+    diff_wee_alloc_group_by_generic,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--group-by",
+    "generic"
+);
+
+test!(
+    diff_wee_alloc_group_by_section,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--group-by",
+    "section"
+);
+
+test!(
+    diff_wee_alloc_group_by_kind,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--group-by",
+    "kind",
+    "--retained"
+);
+
+test!(
+    diff_wee_alloc_group_by_crate_expand,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--group-by",
+    "crate",
+    "--expand",
+    "-n",
+    "3"
+);
+
+test!(
+    diff_wee_alloc_group_by_crate_expand_json,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--group-by",
+    "crate",
+    "--expand",
+    "-n",
+    "3",
+    "-f",
+    "json"
+);
+
+test!(
+    diff_wee_alloc_group_by_crate_expand_csv,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--group-by",
+    "crate",
+    "--expand",
+    "-n",
+    "3",
+    "-f",
+    "csv"
+);
+
+test!(
+    diff_wee_alloc_dominators,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--dominators"
+);
+
+test!(
+    diff_wee_alloc_dominators_items,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--dominators",
+    "hello"
+);
+
+test!(
+    diff_wee_alloc_dominators_json,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--dominators",
+    "-n",
+    "4",
+    "-f",
+    "json"
+);
+
+test!(
+    diff_wee_alloc_dominators_csv,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--dominators",
+    "-n",
+    "4",
+    "-f",
+    "csv"
+);
@@ -79,3 +79,21 @@ test!(
     "-d",
     "1"
 );
+
+test!(
+    dominators_wee_alloc_exclude_type_edges,
+    "dominators",
+    "./fixtures/wee_alloc.wasm",
+    "-r",
+    "12",
+    "--exclude-edges",
+    "type"
+);
+
+test!(
+    dominators_wee_alloc_kind_export,
+    "dominators",
+    "./fixtures/wee_alloc.wasm",
+    "--kind",
+    "export"
+);
@@ -56,3 +56,135 @@ test!(
     "3" //"-f",
         //"json"
 );
+
+test!(
+    elf_top_split_debug_debuglink,
+    "top",
+    "./fixtures/split_debug"
+);
+
+test!(
+    elf_top_split_debug_file,
+    "top",
+    "./fixtures/split_debug",
+    "--debug-file",
+    "./fixtures/split_debug.debug"
+);
+
+test!(
+    elf_top_split_debug_build_id_dwarf,
+    "top",
+    "./fixtures/split_debug",
+    "--debug-dir",
+    "./fixtures/debug"
+);
+
+test!(
+    elf_paths_split_debug,
+    "paths",
+    "./fixtures/split_debug",
+    "main"
+);
+
+test!(
+    elf_top_unsupported_relocs,
+    "top",
+    "./fixtures/unsupported_relocs"
+);
+
+test!(
+    elf_top_unsupported_relocs_warnings,
+    stderr,
+    "top",
+    "./fixtures/unsupported_relocs"
+);
+
+test!(
+    elf_paths_unsupported_relocs,
+    "paths",
+    "./fixtures/unsupported_relocs",
+    "pick"
+);
+
+// `aliases`, with the size of `main` patched to run past the end of the
+// address space.
+test!(
+    elf_top_symbol_overflow_warnings,
+    stderr,
+    "top",
+    "./fixtures/symbol_overflow"
+);
+
+test!(elf_top_aliases, "top", "./fixtures/aliases");
+
+test!(
+    elf_top_aliases_json,
+    "top",
+    "-n",
+    "3",
+    "-f",
+    "json",
+    "./fixtures/aliases"
+);
+
+test!(elf_paths_alias, "paths", "./fixtures/aliases", "crc_compat");
+
+test!(
+    elf_paths_call_edges,
+    "paths",
+    "--edges",
+    "call",
+    "./fixtures/aliases",
+    "crc_compat"
+);
+
+test!(
+    elf_top_aliases_csv,
+    "top",
+    "-n",
+    "3",
+    "-f",
+    "csv",
+    "./fixtures/aliases"
+);
+
+// Items that share a name are listed separately, with a suffix.
+test!(
+    elf_top_duplicates,
+    "top",
+    "-n",
+    "7",
+    "./fixtures/duplicates"
+);
+
+test!(
+    elf_dominators_duplicates,
+    "dominators",
+    "./fixtures/duplicates"
+);
+
+// A plain name finds every item that has it.
+test!(
+    elf_paths_duplicates,
+    "paths",
+    "./fixtures/duplicates",
+    "step"
+);
+
+// A suffixed name finds just that item.
+test!(
+    elf_dominators_duplicate_suffix,
+    "dominators",
+    "./fixtures/duplicates",
+    "step #2"
+);
+
+test!(
+    elf_top_duplicates_json,
+    "top",
+    "-n",
+    "4",
+    "-f",
+    "json",
+    "./fixtures/duplicates"
+);
@@ -0,0 +1,24 @@
+// Compiled with:
+//
+//     gcc -O1 -Wl,--emit-relocs -o aliases aliases.c
+//
+// `checksum`, `crc_compat` and `__checksum_internal` all name the same code,
+// the way identical code folding leaves functions behind.
+
+#include <stdio.h>
+
+static unsigned __checksum_internal(const char *s) {
+    unsigned sum = 0;
+    while (*s) {
+        sum = sum * 31 + (unsigned char)*s++;
+    }
+    return sum;
+}
+
+unsigned checksum(const char *s) __attribute__((alias("__checksum_internal")));
+unsigned crc_compat(const char *s) __attribute__((weak, alias("__checksum_internal")));
+
+int main(int argc, char **argv) {
+    printf("%u %u\n", checksum(argv[0]), crc_compat(argv[argc - 1]));
+    return 0;
+}
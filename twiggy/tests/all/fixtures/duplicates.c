@@ -0,0 +1,23 @@
+// Compiled with:
+//
+//     gcc -O1 -fno-inline -ffunction-sections -fdata-sections -Wl,--emit-relocs \
+//         -o duplicates duplicates.c duplicates_helper.c
+//
+// Both files define a `static` function `step` and a `static` table `weights`,
+// so the binary has two items named `step` and two named `weights`.
+
+#include <stdio.h>
+
+int helper(int x);
+
+static int weights[16] = {1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16};
+
+static int step(int x) {
+    return x * weights[x & 15] + 1;
+}
+
+int main(int argc, char **argv) {
+    (void)argv;
+    printf("%d\n", step(argc) + helper(argc));
+    return 0;
+}
@@ -0,0 +1,16 @@
+// Part of the `duplicates` fixture; see `duplicates.c`.
+
+static int weights[32] = {3, 1, 4, 1, 5, 9, 2, 6, 5, 3, 5, 8, 9, 7, 9, 3,
+                          2, 3, 8, 4, 6, 2, 6, 4, 3, 3, 8, 3, 2, 7, 9, 5};
+
+static int step(int x) {
+    int sum = 0;
+    for (int i = 0; i < x; i++) {
+        sum += weights[i & 31] * (x - i);
+    }
+    return sum;
+}
+
+int helper(int x) {
+    return step(x) * 2;
+}
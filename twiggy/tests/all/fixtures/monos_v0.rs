@@ -0,0 +1,65 @@
+//! Rebuild `monos_v0` with:
+//!
+//! ```
+//! rustc --target x86_64-unknown-linux-gnu monos_v0.rs -o monos_v0 -C opt-level=1 -C panic=abort -C symbol-mangling-version=v0 -Clink-arg=-Wl,--emit-relocs
+//! ```
+
+#![no_std]
+#![crate_type = "cdylib"]
+
+use core::panic::PanicInfo;
+
+#[panic_handler]
+fn panic(_: &PanicInfo) -> ! {
+    loop {}
+}
+
+/// A generic function, instantiated with a few different types below.
+#[inline(never)]
+fn checksum<T: Copy + Into<u64>>(values: &[T]) -> u64 {
+    values
+        .iter()
+        .fold(0, |sum: u64, &value| sum.rotate_left(5) ^ value.into())
+}
+
+/// A generic type, whose method is instantiated for each `T`.
+struct Pair<T> {
+    first: T,
+    second: T,
+}
+
+impl<T: Copy + PartialOrd> Pair<T> {
+    #[inline(never)]
+    fn max(&self) -> T {
+        if self.first > self.second {
+            self.first
+        } else {
+            self.second
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn checksum_u8s(ptr: *const u8, len: usize) -> u64 {
+    checksum(unsafe { core::slice::from_raw_parts(ptr, len) })
+}
+
+#[no_mangle]
+pub extern "C" fn checksum_u16s(ptr: *const u16, len: usize) -> u64 {
+    checksum(unsafe { core::slice::from_raw_parts(ptr, len) })
+}
+
+#[no_mangle]
+pub extern "C" fn checksum_u32s(ptr: *const u32, len: usize) -> u64 {
+    checksum(unsafe { core::slice::from_raw_parts(ptr, len) })
+}
+
+#[no_mangle]
+pub extern "C" fn max_u8(first: u8, second: u8) -> u8 {
+    Pair { first, second }.max()
+}
+
+#[no_mangle]
+pub extern "C" fn max_f64(first: f64, second: f64) -> f64 {
+    Pair { first, second }.max()
+}
@@ -0,0 +1,54 @@
+// Compiled with:
+//
+//     gcc -O1 -g -Wl,--emit-relocs -Wl,--build-id -o split_debug split_debug.c
+//     objcopy --only-keep-debug split_debug split_debug.debug
+//     objcopy --strip-all --add-gnu-debuglink=split_debug.debug split_debug
+//     strip --strip-all --keep-section=".debug_*" \
+//         -o debug/.build-id/74/1ff688718378dadda72826317dae6431aeb076.debug \
+//         split_debug.debug
+
+#include <stdio.h>
+#include <string.h>
+
+static const char *GREETINGS[] = {
+    "hello",
+    "bonjour",
+    "hola",
+    "hallo",
+    "ciao",
+};
+
+__attribute__((noinline)) static unsigned checksum(const char *s) {
+    unsigned sum = 0;
+    while (*s) {
+        sum = sum * 31 + (unsigned char)*s++;
+    }
+    return sum;
+}
+
+__attribute__((noinline)) static void greet(int which) {
+    const char *greeting = GREETINGS[which % 5];
+    printf("%s (%u)\n", greeting, checksum(greeting));
+}
+
+__attribute__((noinline)) static int count_vowels(const char *s) {
+    int count = 0;
+    for (; *s; s++) {
+        if (strchr("aeiou", *s)) {
+            count++;
+        }
+    }
+    return count;
+}
+
+__attribute__((noinline)) void never_called(void) {
+    for (int i = 0; i < 5; i++) {
+        printf("%d\n", count_vowels(GREETINGS[i]));
+    }
+}
+
+int main(int argc, char **argv) {
+    (void)argv;
+    greet(argc);
+    return 0;
+}
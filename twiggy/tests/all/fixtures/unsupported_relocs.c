@@ -0,0 +1,24 @@
+// Compiled with:
+//
+//     gcc -O1 -fno-pic -no-pie -mcmodel=small -Wl,--emit-relocs \
+//         -o unsupported_relocs unsupported_relocs.c
+//
+// Non-PIC code refers to globals with `R_X86_64_32` relocations, which the
+// ELF parser does not know how to follow.
+
+#include <stdio.h>
+
+static const char *NAMES[] = {"alpha", "beta", "gamma"};
+
+int counter;
+
+__attribute__((noinline)) const char *pick(int i) {
+    counter++;
+    return NAMES[i % 3];
+}
+
+int main(int argc, char **argv) {
+    (void)argv;
+    puts(pick(argc));
+    return counter;
+}
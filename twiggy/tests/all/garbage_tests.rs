@@ -91,3 +91,29 @@ test!(
     "-n",
     "2"
 );
+
+test!(
+    garbage_wee_alloc_call_and_export_edges,
+    "garbage",
+    "./fixtures/wee_alloc.wasm",
+    "--edges",
+    "call",
+    "--edges",
+    "export"
+);
+
+test!(
+    garbage_kind_type,
+    "garbage",
+    "./fixtures/garbage.wasm",
+    "--kind",
+    "type"
+);
+
+test!(
+    garbage_group_by_kind,
+    "garbage",
+    "./fixtures/garbage.wasm",
+    "--group-by",
+    "kind"
+);
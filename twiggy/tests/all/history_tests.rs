@@ -0,0 +1,55 @@
+test!(
+    history_wee_alloc,
+    "history",
+    "./fixtures/wee_alloc.snapshot",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "-n",
+    "8"
+);
+
+test!(
+    history_wee_alloc_group_by_crate,
+    "history",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "./fixtures/wee_alloc.wasm",
+    "--label",
+    "v1",
+    "--label",
+    "v2",
+    "--label",
+    "v3",
+    "--group-by",
+    "crate"
+);
+
+test!(
+    history_wee_alloc_json,
+    "history",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--label",
+    "v1",
+    "--label",
+    "v2",
+    "-n",
+    "2",
+    "-f",
+    "json"
+);
+
+test!(
+    history_wee_alloc_csv,
+    "history",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--label",
+    "v1",
+    "--label",
+    "v2",
+    "-n",
+    "2",
+    "-f",
+    "csv"
+);
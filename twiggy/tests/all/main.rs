@@ -10,7 +10,27 @@ fn slurp<P: AsRef<Path>>(path: P) -> io::Result<Vec<u8>> {
 }
 
 macro_rules! test {
+    // A test of a `twiggy` command that should fail, but still write out the
+    // expected output, as `check` does when budgets are exceeded.
+    ( $name:ident, failing $( , $args:expr )* ) => {
+        test!(@run $name, false, stdout $( , $args )*);
+    };
+
+    // A test of the error that a `twiggy` command fails with.
+    ( $name:ident, failing stderr $( , $args:expr )* ) => {
+        test!(@run $name, false, stderr $( , $args )*);
+    };
+
+    // A test of the warnings that a `twiggy` command writes to stderr.
+    ( $name:ident, stderr $( , $args:expr )* ) => {
+        test!(@run $name, true, stderr $( , $args )*);
+    };
+
     ( $name:ident $( , $args:expr )* ) => {
+        test!(@run $name, true, stdout $( , $args )*);
+    };
+
+    ( @run $name:ident, $success:expr, $stream:ident $( , $args:expr )* ) => {
         #[test]
         fn $name() {
             use std::fs;
@@ -21,6 +41,7 @@ macro_rules! test {
 
             let output = Command::new("cargo")
                 .arg("run")
+                .arg("--quiet")
                 .arg("--")
                 $(
                     .arg($args)
@@ -30,12 +51,13 @@ macro_rules! test {
                 .unwrap();
 
             assert!(
-                output.status.success(),
-                "should have run `twiggy` OK\n\n\
+                output.status.success() == $success,
+                "should have run `twiggy` {}\n\n\
                  ============================== stdout ==============================\n\n\
                  {}\n\n\
                  ============================== stderr ==============================\n\n\
                  {}\n\n",
+                if $success { "OK" } else { "and failed" },
                 String::from_utf8_lossy(&output.stdout),
                 String::from_utf8_lossy(&output.stderr),
             );
@@ -51,7 +73,7 @@ macro_rules! test {
             let expected = String::from_utf8_lossy(&expected);
             let expected_lines = expected.lines().collect::<Vec<&str>>();
 
-            let actual = String::from_utf8_lossy(&output.stdout);
+            let actual = String::from_utf8_lossy(&output.$stream);
 
             if ::std::env::var("TWIGGY_UPDATE_TEST_EXPECTATIONS").is_ok() {
                 fs::write(expected_path, actual.as_ref()).unwrap();
@@ -95,10 +117,18 @@ macro_rules! test {
     }
 }
 
+mod categories_tests;
+mod check_tests;
+mod crates_tests;
+mod deep_tests;
 mod diff_tests;
 mod dominators_tests;
 mod elf_format_tests;
 mod garbage_tests;
+mod history_tests;
+mod map_format_tests;
 mod monos_tests;
 mod paths_tests;
+mod snapshot_tests;
 mod top_tests;
+mod whatif_tests;
@@ -0,0 +1,36 @@
+test!(map_top_gnu_ld, "top", "./fixtures/gnu_ld.map");
+
+test!(map_top_lld, "top", "./fixtures/lld.map");
+
+test!(map_top_link_exe, "top", "./fixtures/link_exe.map");
+
+test!(
+    map_top_retained_link_exe,
+    "top",
+    "--retained",
+    "-n",
+    "8",
+    "./fixtures/link_exe.map"
+);
+
+test!(
+    map_paths_gnu_ld_cref,
+    "paths",
+    "./fixtures/gnu_ld.map",
+    "count_vowels",
+    "checksum_table"
+);
+
+test!(map_dominators_lld, "dominators", "./fixtures/lld.map");
+
+test!(
+    map_top_explicit_mode,
+    "top",
+    "--mode",
+    "map",
+    "-n",
+    "5",
+    "./fixtures/gnu_ld.map"
+);
+
+test!(map_top_lld_overflow, "top", "./fixtures/lld_overflow.map");
@@ -75,3 +75,49 @@ test!(
     "alloc::slice::insert_head",
     "alloc::slice::merge_sort"
 );
+
+test!(monos_rust_v0, "monos", "./fixtures/monos_v0");
+
+test!(
+    monos_rust_v0_json,
+    "monos",
+    "./fixtures/monos_v0",
+    "-f",
+    "json"
+);
+
+test!(
+    monos_rust_v0_functions,
+    "monos",
+    "./fixtures/monos_v0",
+    "<monos_v0::Pair<_>>::max"
+);
+
+test!(monos_by_type, "monos", "./fixtures/monos_v0", "--by-type");
+
+test!(
+    monos_by_type_json,
+    "monos",
+    "./fixtures/monos_v0",
+    "--by-type",
+    "-f",
+    "json"
+);
+
+test!(
+    monos_by_type_max_types,
+    "monos",
+    "./fixtures/monos_v0",
+    "--by-type",
+    "-m",
+    "2",
+    "-n",
+    "1"
+);
+
+test!(
+    monos_by_type_cpp,
+    "monos",
+    "./fixtures/cpp-monos.wasm",
+    "--by-type"
+);
@@ -249,3 +249,30 @@ test!(
     "./fixtures/mappings.wasm",
     "std::io::error::Error::new::h8c006d5367bc92ed"
 );
+
+test!(
+    paths_wee_alloc_exclude_table_edges,
+    "paths",
+    "./fixtures/wee_alloc.wasm",
+    "wee_alloc::alloc_first_fit::h9a72de3af77ef93f",
+    "--exclude-edges",
+    "table"
+);
+
+test!(
+    paths_wee_alloc_call_and_export_edges,
+    "paths",
+    "./fixtures/wee_alloc.wasm",
+    "wee_alloc::alloc_first_fit::h9a72de3af77ef93f",
+    "--edges",
+    "call,export"
+);
+
+test!(
+    paths_wee_alloc_kind_export_descending,
+    "paths",
+    "./fixtures/wee_alloc.wasm",
+    "--descending",
+    "--kind",
+    "export"
+);
@@ -0,0 +1,58 @@
+test!(snapshot_wee_alloc, "snapshot", "./fixtures/wee_alloc.wasm");
+
+test!(
+    snapshot_top_wee_alloc,
+    "top",
+    "-n",
+    "10",
+    "./fixtures/wee_alloc.snapshot"
+);
+
+test!(
+    snapshot_dominators_wee_alloc,
+    "dominators",
+    "./fixtures/wee_alloc.snapshot"
+);
+
+test!(
+    snapshot_paths_wee_alloc,
+    "paths",
+    "--mode",
+    "snapshot",
+    "./fixtures/wee_alloc.snapshot",
+    "wee_alloc::alloc_first_fit::h9a72de3af77ef93f"
+);
+
+test!(
+    snapshot_diff_wee_alloc,
+    "diff",
+    "./fixtures/wee_alloc.snapshot",
+    "./fixtures/wee_alloc.2.wasm"
+);
+
+test!(snapshot_elf_aliases, "snapshot", "./fixtures/aliases");
+
+// Sizes over 4 GiB must not wrap around.
+test!(snapshot_top_huge, "top", "./fixtures/huge.snapshot");
+
+test!(
+    snapshot_top_huge_retained_json,
+    "top",
+    "./fixtures/huge.snapshot",
+    "--retained",
+    "-f",
+    "json"
+);
+
+test!(
+    snapshot_dominators_huge,
+    "dominators",
+    "./fixtures/huge.snapshot"
+);
+
+test!(
+    snapshot_garbage_huge,
+    "garbage",
+    "./fixtures/huge.snapshot",
+    "--show-data-segments"
+);
@@ -87,3 +87,106 @@ test!(
     "-n",
     "10"
 );
+
+test!(
+    top_wee_alloc_kind_export_json,
+    "top",
+    "./fixtures/wee_alloc.wasm",
+    "--kind",
+    "export",
+    "-f",
+    "json"
+);
+
+test!(
+    top_wee_alloc_kind_type_json,
+    "top",
+    "./fixtures/wee_alloc.wasm",
+    "--kind",
+    "type",
+    "-n",
+    "4",
+    "-f",
+    "json"
+);
+
+test!(
+    top_cpp_monos_kind_import_and_global_json,
+    "top",
+    "./fixtures/cpp-monos.wasm",
+    "--kind",
+    "import,global",
+    "-f",
+    "json"
+);
+
+test!(
+    top_retaining_paths,
+    "top",
+    "./fixtures/paths_test.wasm",
+    "--retaining-paths"
+);
+
+test!(
+    top_retaining_paths_max_paths_and_depth,
+    "top",
+    "./fixtures/paths_test.wasm",
+    "-r",
+    "--max-paths",
+    "1",
+    "--max-depth",
+    "1"
+);
+
+test!(
+    top_retaining_paths_unreachable,
+    "top",
+    "./fixtures/garbage.wasm",
+    "-r",
+    "-n",
+    "5"
+);
+
+test!(
+    top_retaining_paths_json,
+    "top",
+    "./fixtures/paths_test.wasm",
+    "-r",
+    "-n",
+    "4",
+    "--kind",
+    "code",
+    "-f",
+    "json"
+);
+
+test!(
+    top_retaining_paths_csv,
+    "top",
+    "./fixtures/paths_test.wasm",
+    "-r",
+    "--kind",
+    "code",
+    "-f",
+    "csv"
+);
+
+test!(
+    top_group_by_crate,
+    "top",
+    "./fixtures/wee_alloc.wasm",
+    "--kind",
+    "code",
+    "--group-by",
+    "crate"
+);
+
+test!(
+    top_group_by_section_csv,
+    "top",
+    "./fixtures/wee_alloc.wasm",
+    "--group-by",
+    "section",
+    "-f",
+    "csv"
+);
@@ -0,0 +1,45 @@
+test!(
+    whatif_wee_alloc_remove_export,
+    "whatif",
+    "./fixtures/wee_alloc.wasm",
+    "--remove-export",
+    "hello"
+);
+
+test!(
+    whatif_wee_alloc_remove_edge,
+    "whatif",
+    "./fixtures/wee_alloc.wasm",
+    "--remove-edge",
+    "export \"hello\"->hello"
+);
+
+test!(
+    whatif_wee_alloc_many_json,
+    "whatif",
+    "./fixtures/wee_alloc.wasm",
+    "-f",
+    "json",
+    "--remove-item",
+    "wee_alloc::alloc_first_fit::h9a72de3af77ef93f",
+    "--remove-edge",
+    "export \"hello\"->hello",
+    "--remove-root",
+    "export \"goodbye\""
+);
+
+test!(
+    whatif_garbage_add_root,
+    "whatif",
+    "./fixtures/garbage.wasm",
+    "--add-root",
+    "unusedAddThreeNumbers"
+);
+
+test!(
+    whatif_duplicates_remove_item,
+    "whatif",
+    "./fixtures/duplicates",
+    "--remove-item",
+    "step #2"
+);
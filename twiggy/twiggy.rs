@@ -3,11 +3,14 @@
 #![deny(missing_docs)]
 #![deny(missing_debug_implementations)]
 
+use anyhow::anyhow;
+use std::fs;
 use std::process;
 use structopt::StructOpt;
 use twiggy_analyze as analyze;
 use twiggy_opt::{self as opt, CommonCliOptions};
 use twiggy_parser as parser;
+use twiggy_traits::Emit;
 
 fn main() {
     let options = opt::Options::from_args();
@@ -18,18 +21,107 @@ fn main() {
 }
 
 fn run(opts: &opt::Options) -> anyhow::Result<()> {
-    let mut items = parser::read_and_parse(opts.input(), opts.parse_mode())?;
+    let mut debug_info = parser::DebugInfoSearch::new();
+    for file in opts.debug_files() {
+        debug_info.add_file(file);
+    }
+    for dir in opts.debug_dirs() {
+        debug_info.add_dir(dir);
+    }
+
+    let mut items =
+        parser::read_and_parse_with_debug_info(opts.input(), opts.parse_mode(), &debug_info)?;
+
+    let rules = match opts.rules() {
+        Some(path) => {
+            let rules = fs::read_to_string(path)
+                .map_err(|e| anyhow!("failed to read `{}`: {}", path.display(), e))?;
+            let rules = analyze::Rules::parse(&rules)
+                .map_err(|e| anyhow!("invalid rules in `{}`: {}", path.display(), e))?;
+            rules.classify(&mut items);
+            Some(rules)
+        }
+        None => None,
+    };
 
     let data = match opts {
         opt::Options::Top(ref top) => analyze::top(&mut items, top)?,
         opt::Options::Dominators(ref doms) => analyze::dominators(&mut items, doms)?,
         opt::Options::Paths(ref paths) => analyze::paths(&mut items, paths)?,
         opt::Options::Monos(ref monos) => analyze::monos(&mut items, monos)?,
-        opt::Options::Garbage(ref garbo) => analyze::garbage(&items, garbo)?,
+        opt::Options::Crates(ref crates) => analyze::crates(&mut items, crates)?,
+        opt::Options::Garbage(ref garbo) => analyze::garbage(&mut items, garbo)?,
+        opt::Options::WhatIf(ref whatif) => analyze::whatif(&mut items, whatif)?,
         opt::Options::Diff(ref diff) => {
-            let mut new_items = parser::read_and_parse(diff.new_input(), opts.parse_mode())?;
+            let mut new_items = parser::read_and_parse_with_debug_info(
+                diff.new_input(),
+                opts.parse_mode(),
+                &debug_info,
+            )?;
+            if let Some(ref rules) = rules {
+                rules.classify(&mut new_items);
+            }
             analyze::diff(&mut items, &mut new_items, diff)?
         }
+        opt::Options::Check(ref check) => {
+            let path = check.budget();
+            let budgets = fs::read_to_string(path)
+                .map_err(|e| anyhow!("failed to read `{}`: {}", path.display(), e))?;
+            let budgets = analyze::Budgets::parse(&budgets)
+                .map_err(|e| anyhow!("invalid budgets in `{}`: {}", path.display(), e))?;
+            let mut baseline = match check.baseline() {
+                Some(baseline) => Some(parser::read_and_parse_with_debug_info(
+                    baseline,
+                    opts.parse_mode(),
+                    &debug_info,
+                )?),
+                None => None,
+            };
+            let report = analyze::check(&mut items, baseline.as_mut(), &budgets)?;
+
+            let mut dest = opts.output_destination().open()?;
+            report.emit(&items, &mut *dest, opts.output_format())?;
+            if report.violations() > 0 {
+                return Err(anyhow!(
+                    "{} of {} budget limits exceeded",
+                    report.violations(),
+                    report.checked()
+                ));
+            }
+            return Ok(());
+        }
+        opt::Options::History(ref history) => {
+            let mut builds = vec![items];
+            for input in &history.inputs()[1..] {
+                let mut items =
+                    parser::read_and_parse_with_debug_info(input, opts.parse_mode(), &debug_info)?;
+                if let Some(ref rules) = rules {
+                    rules.classify(&mut items);
+                }
+                builds.push(items);
+            }
+            // Name the builds after their files, unless given other names.
+            let labels: Vec<String> = if history.labels().is_empty() {
+                history
+                    .inputs()
+                    .iter()
+                    .map(|input| match input.file_name() {
+                        Some(name) => name.to_string_lossy().into_owned(),
+                        None => input.display().to_string(),
+                    })
+                    .collect()
+            } else {
+                history.labels().to_vec()
+            };
+            let data = analyze::history(&builds, &labels, history)?;
+
+            let mut dest = opts.output_destination().open()?;
+            return data.emit(&builds[0], &mut *dest, opts.output_format());
+        }
+        opt::Options::Snapshot(_) => {
+            let mut dest = opts.output_destination().open()?;
+            return parser::write_snapshot(&items, &mut *dest);
+        }
     };
 
     let mut dest = opts.output_destination().open()?;